@@ -1,31 +1,62 @@
 import { useEffect, useMemo, useRef, useState } from 'react';
 import { listen } from '@tauri-apps/api/event';
+import CatalogUpdatesPanel from './components/CatalogUpdatesPanel';
+import CommandOutputPane, { type LiveCommandRun } from './components/CommandOutputPane';
 import MonitorPanel from './components/MonitorPanel';
 import SharedCommandsPanel from './components/SharedCommandsPanel';
 import {
+  acceptCatalogUpdate,
   checkAll,
   checkAutoAppItems,
   checkAutoCliItems,
   checkRuntimeItems,
+  cancelRun,
   checkItem,
+  listActiveRuns,
   loadConfig,
   getActiveNodeVersion,
+  getSchedulerStatus,
   loadHistory,
   loadLatestResults,
   detectInstalledItems,
+  dismissCatalogUpdate,
+  listCatalogUpdates,
+  restoreCatalogItem,
   runAdHocCommand,
   runItemUpdate,
   saveConfig,
 } from './lib/ipc';
+import { followCommandRun } from './lib/commandRuns';
 import { normalizeConfig } from './lib/config';
 import { applyThemeMode } from './lib/theme';
-import type { AppConfig, CheckResult, ExecutionHistoryEntry, SoftwareItem, ThemeMode } from './types/app';
+import type {
+  AppConfig,
+  CatalogUpdate,
+  CheckPolicy,
+  CheckResult,
+  CommandRunFinished,
+  ExecutionHistoryEntry,
+  ItemGroup,
+  LatestResultSnapshot,
+  MigrationReport,
+  SchedulerStatus,
+  SoftwareItem,
+  ThemeMode,
+} from './types/app';
 const formatError = (error: unknown): string => (error instanceof Error ? error.message : String(error));
 const themeModeLabel = (mode: ThemeMode): string => ({ system: '跟随系统', light: '浅色', dark: '深色' })[mode];
 const isThemeMode = (value: unknown): value is ThemeMode =>
   value === 'system' || value === 'light' || value === 'dark';
-const isManualItem = (item: SoftwareItem): boolean => item.id === 'brew' || item.id === 'bun';
-const mapLatestResultsToResultMap = (items: Record<string, { item_id: string; checked_at: string; has_update: boolean; current_version: string | null; latest_version: string | null; error: string | null }>): Record<string, CheckResult> => {
+const itemGroup = (item: SoftwareItem): ItemGroup =>
+  item.group ?? (item.kind === 'gui' || item.kind === 'app' ? 'gui' : item.kind === 'runtime' ? 'runtime' : 'cli');
+// Runtimes without a policy stay manual, as before check_policy existed.
+const itemPolicy = (item: SoftwareItem): CheckPolicy =>
+  item.check_policy ?? (itemGroup(item) === 'runtime' ? 'manual' : 'auto');
+const isManualItem = (item: SoftwareItem): boolean =>
+  itemPolicy(item) === 'manual' && itemGroup(item) !== 'runtime';
+const isAutoItemIn = (item: SoftwareItem, group: ItemGroup): boolean =>
+  itemPolicy(item) === 'auto' && itemGroup(item) === group;
+const mapLatestResultsToResultMap = (items: Record<string, LatestResultSnapshot>): Record<string, CheckResult> => {
   const next: Record<string, CheckResult> = {};
   Object.values(items).forEach((value) => {
     next[value.item_id] = {
@@ -34,6 +65,7 @@ const mapLatestResultsToResultMap = (items: Record<string, { item_id: string; ch
       has_update: value.has_update,
       current_version: value.current_version,
       latest_version: value.latest_version,
+      version_comparison: value.version_comparison ?? null,
       details: 'latest snapshot',
       error: value.error,
     };
@@ -41,38 +73,15 @@ const mapLatestResultsToResultMap = (items: Record<string, { item_id: string; ch
   return next;
 };
 
-interface CheckSectionReport {
-  checkedCount: number;
-  errorCount: number;
-  firstError: string | null;
-  skipped: boolean;
-}
-
-interface AutoCycleInsight {
-  running: boolean;
-  lastRunAt: string | null;
-  nextRunAt: string | null;
-  lastDurationMs: number | null;
-  lastCheckedCount: number;
-  lastFailureReason: string | null;
-}
-
-const emptySectionReport = (): CheckSectionReport => ({
-  checkedCount: 0,
-  errorCount: 0,
-  firstError: null,
-  skipped: false,
-});
-
-const sectionReportFromResults = (results: CheckResult[]): CheckSectionReport => {
-  const firstError = results.find((item) => item.error)?.error ?? null;
-  const errorCount = results.filter((item) => item.error).length;
-  return {
-    checkedCount: results.length,
-    errorCount,
-    firstError,
-    skipped: false,
-  };
+const formatMigrationReport = (report: MigrationReport): string => {
+  const steps = report.steps
+    .filter((step) => step.changes.length > 0)
+    .map((step) => {
+      const items = new Set(step.changes.map((change) => change.item_id ?? '全局设置'));
+      return `${step.description}（${[...items].join('、')}）`;
+    });
+  const backup = report.backup_path ? `原配置已备份到 ${report.backup_path}。` : '';
+  return `配置已从 v${report.from_version} 迁移到 v${report.to_version}：${steps.join('；') || '无字段变更'}。${backup}`;
 };
 
 const formatDateTimeLabel = (raw: string | null): string =>
@@ -88,16 +97,6 @@ const formatDurationLabel = (value: number | null): string => {
   return `${(value / 1000).toFixed(1)} s`;
 };
 
-const getLatestCheckedAt = (map: Record<string, CheckResult>): string | null => {
-  let latest: string | null = null;
-  for (const r of Object.values(map)) {
-    if (r.checked_at && (!latest || r.checked_at > latest)) {
-      latest = r.checked_at;
-    }
-  }
-  return latest;
-};
-
 export default function App() {
   const [config, setConfig] = useState<AppConfig | null>(null);
   const [message, setMessage] = useState('正在加载配置...');
@@ -105,6 +104,7 @@ export default function App() {
   const [checkingMap, setCheckingMap] = useState<Record<string, boolean>>({});
   const [autoCheckingMap, setAutoCheckingMap] = useState<Record<string, boolean>>({});
   const [updatingMap, setUpdatingMap] = useState<Record<string, boolean>>({});
+  const [liveRun, setLiveRun] = useState<LiveCommandRun | null>(null);
   const [historyEntries, setHistoryEntries] = useState<ExecutionHistoryEntry[]>([]);
   const [activeNodeVersion, setActiveNodeVersion] = useState('');
   const [installedMap, setInstalledMap] = useState<Record<string, boolean>>({});
@@ -112,18 +112,10 @@ export default function App() {
   const [runtimeCheckRunning, setRuntimeCheckRunning] = useState(false);
   const [autoCliCheckRunning, setAutoCliCheckRunning] = useState(false);
   const [autoAppCheckRunning, setAutoAppCheckRunning] = useState(false);
-  const [autoCycleInsight, setAutoCycleInsight] = useState<AutoCycleInsight>({
-    running: false,
-    lastRunAt: null,
-    nextRunAt: null,
-    lastDurationMs: null,
-    lastCheckedCount: 0,
-    lastFailureReason: null,
-  });
+  const [schedulerStatus, setSchedulerStatus] = useState<SchedulerStatus | null>(null);
+  const [catalogUpdates, setCatalogUpdates] = useState<CatalogUpdate[]>([]);
   const checkAllRunningRef = useRef(false);
   const runtimeCheckRunningRef = useRef(false);
-  const autoCheckCycleRunningRef = useRef(false);
-  const handleAutoCheckCycleRef = useRef<() => Promise<void>>(() => Promise.resolve());
   const autoCliCheckRunningRef = useRef(false);
   const autoAppCheckRunningRef = useRef(false);
   const enabledItems = useMemo(
@@ -132,15 +124,15 @@ export default function App() {
   );
   const manualItems = useMemo(() => enabledItems.filter((item) => isManualItem(item)), [enabledItems]);
   const autoCliItems = useMemo(
-    () => enabledItems.filter((item) => !isManualItem(item) && item.kind === 'cli'),
+    () => enabledItems.filter((item) => isAutoItemIn(item, 'cli')),
     [enabledItems]
   );
   const runtimeItems = useMemo(
-    () => enabledItems.filter((item) => item.kind === 'runtime'),
+    () => enabledItems.filter((item) => itemGroup(item) === 'runtime' && itemPolicy(item) !== 'disabled'),
     [enabledItems]
   );
   const autoAppItems = useMemo(
-    () => enabledItems.filter((item) => !isManualItem(item) && (item.kind === 'gui' || item.kind === 'app')),
+    () => enabledItems.filter((item) => isAutoItemIn(item, 'gui')),
     [enabledItems]
   );
   const mergedCheckingMap = useMemo(
@@ -177,70 +169,43 @@ export default function App() {
     }
   };
   const reloadConfig = async (): Promise<void> => {
-    const nextConfig = normalizeConfig((await loadConfig()) as Partial<AppConfig>);
+    const loaded = await loadConfig();
+    const nextConfig = normalizeConfig(loaded.config as Partial<AppConfig>);
     setConfig(nextConfig);
     detectInstalledItems()
       .then(setInstalledMap)
       .catch((error) => console.error('检测安装状态失败', error));
+    listCatalogUpdates()
+      .then(setCatalogUpdates)
+      .catch((error) => console.error('加载目录更新失败', error));
     await refreshLatestResults();
     await refreshHistory();
     await refreshActiveNodeVersion();
-    setMessage('配置已加载。');
+    setMessage(loaded.migration_report ? formatMigrationReport(loaded.migration_report) : '配置已加载。');
   };
   useEffect(() => {
     void reloadConfig().catch((error) => setMessage(`加载配置失败：${formatError(error)}`));
   }, []);
   useEffect(() => (config ? applyThemeMode(config.theme_mode) : undefined), [config?.theme_mode]);
 
-  const schedulerEnabled = config?.auto_check_enabled ?? false;
-  const schedulerInterval = config?.check_interval_minutes ?? 480;
-
-  useEffect(() => {
-    if (!schedulerEnabled) {
-      setAutoCycleInsight((prev) => ({ ...prev, running: false, nextRunAt: null }));
-      return;
-    }
-
-    const intervalMs = Math.max(schedulerInterval, 1) * 60 * 1000;
-    const lastCheckTime = getLatestCheckedAt(resultMap);
-    const elapsed = lastCheckTime
-      ? Date.now() - new Date(lastCheckTime).getTime()
-      : Infinity;
-    const initialDelay = elapsed >= intervalMs ? 0 : intervalMs - elapsed;
-
-    setAutoCycleInsight((prev) => ({
-      ...prev,
-      nextRunAt: new Date(Date.now() + initialDelay).toISOString(),
-    }));
-
-    let regularTimer: ReturnType<typeof setInterval> | undefined;
-
-    const kickoff = () => {
-      void handleAutoCheckCycleRef.current();
-      regularTimer = setInterval(
-        () => void handleAutoCheckCycleRef.current(),
-        intervalMs,
-      );
-    };
-
-    const delayTimer =
-      initialDelay > 0 ? setTimeout(kickoff, initialDelay) : (kickoff(), undefined);
-
-    return () => {
-      if (delayTimer) clearTimeout(delayTimer);
-      if (regularTimer) clearInterval(regularTimer);
-    };
-  }, [schedulerEnabled, schedulerInterval]);
   useEffect(() => {
     let unlistenConfig: (() => void) | undefined;
+    let unlistenConfigError: (() => void) | undefined;
     let unlistenLatest: (() => void) | undefined;
     let unlistenHistory: (() => void) | undefined;
     let unlistenThemeMode: (() => void) | undefined;
+    let unlistenScheduler: (() => void) | undefined;
 
+    getSchedulerStatus()
+      .then(setSchedulerStatus)
+      .catch((error) => console.error('加载调度状态失败', error));
     void (async () => {
       unlistenConfig = await listen('patchpilot://config-updated', () => {
         void reloadConfig();
       });
+      unlistenConfigError = await listen<string>('patchpilot://config-error', (event) => {
+        setMessage(`配置文件有误，仍显示上次加载的配置：${event.payload}`);
+      });
       unlistenLatest = await listen('patchpilot://latest-results-updated', () => {
         void refreshLatestResults();
       });
@@ -254,13 +219,18 @@ export default function App() {
         }
         setConfig((prev) => (prev ? { ...prev, theme_mode: mode } : prev));
       });
+      unlistenScheduler = await listen<SchedulerStatus>('patchpilot://scheduler-status', (event) => {
+        setSchedulerStatus(event.payload);
+      });
     })();
 
     return () => {
       unlistenConfig?.();
+      unlistenConfigError?.();
       unlistenLatest?.();
       unlistenHistory?.();
       unlistenThemeMode?.();
+      unlistenScheduler?.();
     };
   }, []);
   const setAutoItemsChecking = (itemIds: string[], checking: boolean): void => {
@@ -291,15 +261,13 @@ export default function App() {
       setCheckingMap((prev) => ({ ...prev, [itemId]: false }));
     }
   };
-  const runManualCheck = async (silent = false): Promise<CheckSectionReport> => {
+  const handleCheckAll = async (): Promise<void> => {
     if (!config || checkAllRunningRef.current) {
-      return { ...emptySectionReport(), skipped: true };
+      return;
     }
     checkAllRunningRef.current = true;
     setCheckAllRunning(true);
-    if (!silent) {
-      setMessage('正在检查全部启用项...');
-    }
+    setMessage('正在检查全部启用项...');
     try {
       const results = await checkAll();
       const nextMap = results.reduce<Record<string, CheckResult>>((acc, item) => {
@@ -308,33 +276,20 @@ export default function App() {
       }, {});
       setResultMap((prev) => ({ ...prev, ...nextMap }));
       await refreshHistory();
-      if (!silent) {
-        setMessage('全量检查完成。');
-      }
-      return sectionReportFromResults(results);
+      setMessage('全量检查完成。');
     } catch (error) {
       const text = formatError(error);
-      const skipped = text.includes('already running');
-      if (!silent) {
-        setMessage(skipped ? '全量检查已跳过：上一轮仍在执行。' : `全量检查失败：${text}`);
-      }
-      return {
-        ...emptySectionReport(),
-        errorCount: skipped ? 0 : 1,
-        firstError: skipped ? null : text,
-        skipped,
-      };
+      setMessage(
+        text.includes('already running') ? '全量检查已跳过：上一轮仍在执行。' : `全量检查失败：${text}`
+      );
     } finally {
       checkAllRunningRef.current = false;
       setCheckAllRunning(false);
     }
   };
-  const handleCheckAll = async (): Promise<void> => {
-    await runManualCheck(false);
-  };
-  const runAutoCliCheck = async (): Promise<CheckSectionReport> => {
+  const handleAutoCliCheck = async (): Promise<void> => {
     if (!config || autoCliCheckRunningRef.current) {
-      return { ...emptySectionReport(), skipped: true };
+      return;
     }
     const itemIds = autoCliItems.map((item) => item.id);
     autoCliCheckRunningRef.current = true;
@@ -350,20 +305,14 @@ export default function App() {
         setResultMap((prev) => ({ ...prev, ...nextMap }));
       }
       await refreshHistory();
-      return sectionReportFromResults(results);
     } catch (error) {
-      const text = formatError(error);
       console.error('CLI 自动检查失败', error);
-      return { ...emptySectionReport(), errorCount: 1, firstError: text };
     } finally {
       setAutoItemsChecking(itemIds, false);
       autoCliCheckRunningRef.current = false;
       setAutoCliCheckRunning(false);
     }
   };
-  const handleAutoCliCheck = async (): Promise<void> => {
-    await runAutoCliCheck();
-  };
   const handleRuntimeCheck = async (): Promise<void> => {
     if (!config || runtimeCheckRunningRef.current) {
       return;
@@ -391,9 +340,9 @@ export default function App() {
       setRuntimeCheckRunning(false);
     }
   };
-  const runAutoAppCheck = async (): Promise<CheckSectionReport> => {
+  const handleAutoAppCheck = async (): Promise<void> => {
     if (!config || autoAppCheckRunningRef.current) {
-      return { ...emptySectionReport(), skipped: true };
+      return;
     }
     const itemIds = autoAppItems.map((item) => item.id);
     autoAppCheckRunningRef.current = true;
@@ -409,69 +358,54 @@ export default function App() {
         setResultMap((prev) => ({ ...prev, ...nextMap }));
       }
       await refreshHistory();
-      return sectionReportFromResults(results);
     } catch (error) {
-      const text = formatError(error);
       console.error('App 自动检查失败', error);
-      return { ...emptySectionReport(), errorCount: 1, firstError: text };
     } finally {
       setAutoItemsChecking(itemIds, false);
       autoAppCheckRunningRef.current = false;
       setAutoAppCheckRunning(false);
     }
   };
-  const handleAutoAppCheck = async (): Promise<void> => {
-    await runAutoAppCheck();
-  };
-  const handleAutoCheckCycle = async (): Promise<void> => {
-    if (!config || autoCheckCycleRunningRef.current) {
-      return;
-    }
-    const cycleStartedAt = Date.now();
-    const intervalMs = Math.max(config.check_interval_minutes, 1) * 60 * 1000;
-    autoCheckCycleRunningRef.current = true;
-    setAutoCycleInsight((prev) => ({ ...prev, running: true }));
+
+  const followRun = async (title: string, start: () => Promise<string>): Promise<CommandRunFinished> => {
+    setLiveRun({ runId: null, title, lines: [], running: true, cancelled: false, exitCode: null });
     try {
-      const reports: CheckSectionReport[] = [];
-      if (config.auto_check_manual_enabled) {
-        reports.push(await runManualCheck(true));
+      const finished = await followCommandRun(
+        start,
+        (runId) => setLiveRun((prev) => (prev && prev.runId === null ? { ...prev, runId } : prev)),
+        (line) =>
+          setLiveRun((prev) => (prev && prev.runId === line.run_id ? { ...prev, lines: [...prev.lines, line] } : prev))
+      );
+      setLiveRun((prev) =>
+        prev && prev.runId === finished.run_id
+          ? {
+              ...prev,
+              running: false,
+              cancelled: Boolean(finished.output?.cancelled),
+              exitCode: finished.output?.exit_code ?? null,
+            }
+          : prev
+      );
+      if (!finished.output) {
+        throw new Error(finished.error ?? '命令未返回输出');
       }
-      reports.push(await runAutoCliCheck());
-      reports.push(await runAutoAppCheck());
-      const lastFailureReason =
-        reports.find((item) => item.firstError)?.firstError ??
-        (reports.some((item) => item.errorCount > 0) ? '检查出现异常，请查看分区状态。' : null);
-      const checkedCount = reports.reduce((total, item) => total + item.checkedCount, 0);
-      setAutoCycleInsight({
-        running: false,
-        lastRunAt: new Date().toISOString(),
-        nextRunAt: new Date(Date.now() + intervalMs).toISOString(),
-        lastDurationMs: Date.now() - cycleStartedAt,
-        lastCheckedCount: checkedCount,
-        lastFailureReason,
-      });
+      return finished;
     } catch (error) {
-      setAutoCycleInsight((prev) => ({
-        ...prev,
-        running: false,
-        lastRunAt: new Date().toISOString(),
-        nextRunAt: new Date(Date.now() + intervalMs).toISOString(),
-        lastDurationMs: Date.now() - cycleStartedAt,
-        lastFailureReason: formatError(error),
-      }));
-    } finally {
-      autoCheckCycleRunningRef.current = false;
+      setLiveRun((prev) => (prev ? { ...prev, running: false } : prev));
+      throw error;
     }
   };
-  handleAutoCheckCycleRef.current = handleAutoCheckCycle;
-
   const handleRunUpdate = async (item: SoftwareItem): Promise<void> => {
     setUpdatingMap((prev) => ({ ...prev, [item.id]: true }));
     setMessage(`正在更新 ${item.name}...`);
     try {
-      const result = await runItemUpdate(item.id);
+      const finished = await followRun(`更新 ${item.name}`, () => runItemUpdate(item.id));
       await refreshHistory();
-      setMessage(`${item.name} 更新完成（退出码 ${result.output.exit_code}）。`);
+      if (finished.output?.cancelled) {
+        setMessage(`${item.name} 更新已取消。`);
+        return;
+      }
+      setMessage(`${item.name} 更新完成（退出码 ${finished.output?.exit_code}）。`);
       await handleCheckItem(item.id);
     } catch (error) {
       setMessage(`更新失败：${formatError(error)}`);
@@ -482,13 +416,36 @@ export default function App() {
   const handleRunSharedCommand = async (command: string): Promise<void> => {
     setMessage('正在执行共享命令...');
     try {
-      const output = await runAdHocCommand(command);
+      const finished = await followRun(command, () => runAdHocCommand(command));
       await refreshHistory();
-      setMessage(`共享命令执行完成（退出码 ${output.exit_code}）。`);
+      setMessage(
+        finished.output?.cancelled
+          ? '共享命令已取消。'
+          : `共享命令执行完成（退出码 ${finished.output?.exit_code}）。`
+      );
     } catch (error) {
       setMessage(`共享命令执行失败：${formatError(error)}`);
     }
   };
+  const handleCancelLiveRun = async (): Promise<void> => {
+    if (!liveRun?.runId) {
+      return;
+    }
+    try {
+      await cancelRun(liveRun.runId);
+    } catch (error) {
+      setMessage(`取消失败：${formatError(error)}`);
+    }
+  };
+  const handleCancelBatch = async (action: string): Promise<void> => {
+    try {
+      const runs = await listActiveRuns();
+      await Promise.all(runs.filter((run) => run.action === action).map((run) => cancelRun(run.run_id)));
+      setMessage('已请求取消检查。');
+    } catch (error) {
+      setMessage(`取消失败：${formatError(error)}`);
+    }
+  };
   const handleChangeThemeMode = async (mode: ThemeMode): Promise<void> => {
     if (!config || config.theme_mode === mode) {
       return;
@@ -502,6 +459,19 @@ export default function App() {
       setMessage(`主题保存失败：${formatError(error)}`);
     }
   };
+  const handleCatalogAction = async (
+    action: (id: string) => Promise<void>,
+    id: string,
+    label: string
+  ): Promise<void> => {
+    try {
+      await action(id);
+      await reloadConfig();
+      setMessage(`已${label}：${id}。`);
+    } catch (error) {
+      setMessage(`${label}失败：${formatError(error)}`);
+    }
+  };
   const handleToggleManualAutoCheck = async (enabled: boolean): Promise<void> => {
     if (!config || config.auto_check_manual_enabled === enabled) {
       return;
@@ -515,16 +485,16 @@ export default function App() {
       setMessage(`手动区自动检查设置保存失败：${formatError(error)}`);
     }
   };
-  const autoCycleStateLabel = !config?.auto_check_enabled
+  const autoCycleStateLabel = !schedulerStatus?.enabled
     ? '已暂停'
-    : autoCycleInsight.running
+    : schedulerStatus.running
       ? '运行中'
       : '空闲';
-  const autoCycleStateClass = !config?.auto_check_enabled
+  const autoCycleStateClass = !schedulerStatus?.enabled
     ? 'status-skipped'
-    : autoCycleInsight.running
+    : schedulerStatus.running
       ? 'status-running'
-      : autoCycleInsight.lastFailureReason
+      : schedulerStatus.last_failure_reason
         ? 'status-failed'
         : 'status-success';
   return (
@@ -544,23 +514,23 @@ export default function App() {
         <div className="scheduler-grid">
           <div className="scheduler-item">
             <span className="scheduler-label">下次自动检查</span>
-            <strong className="scheduler-value">{formatDateTimeLabel(autoCycleInsight.nextRunAt)}</strong>
+            <strong className="scheduler-value">{formatDateTimeLabel(schedulerStatus?.next_run_at ?? null)}</strong>
           </div>
           <div className="scheduler-item">
             <span className="scheduler-label">上次自动检查</span>
-            <strong className="scheduler-value">{formatDateTimeLabel(autoCycleInsight.lastRunAt)}</strong>
+            <strong className="scheduler-value">{formatDateTimeLabel(schedulerStatus?.last_run_at ?? null)}</strong>
           </div>
           <div className="scheduler-item">
             <span className="scheduler-label">上次耗时</span>
-            <strong className="scheduler-value">{formatDurationLabel(autoCycleInsight.lastDurationMs)}</strong>
+            <strong className="scheduler-value">{formatDurationLabel(schedulerStatus?.last_duration_ms ?? null)}</strong>
           </div>
           <div className="scheduler-item">
             <span className="scheduler-label">上次检查项数</span>
-            <strong className="scheduler-value">{autoCycleInsight.lastCheckedCount || '-'}</strong>
+            <strong className="scheduler-value">{schedulerStatus?.last_checked_count || '-'}</strong>
           </div>
         </div>
-        <p className={`scheduler-message ${autoCycleInsight.lastFailureReason ? 'scheduler-message-error' : ''}`}>
-          最近失败原因：{autoCycleInsight.lastFailureReason ?? '无'}
+        <p className={`scheduler-message ${schedulerStatus?.last_failure_reason ? 'scheduler-message-error' : ''}`}>
+          最近失败原因：{schedulerStatus?.last_failure_reason ?? '无'}
         </p>
       </section>
       {config && (
@@ -575,8 +545,20 @@ export default function App() {
           onToggleManualAutoCheck={handleToggleManualAutoCheck}
         />
       )}
+      {config && (
+        <CatalogUpdatesPanel
+          updates={catalogUpdates}
+          removedItems={config.removed_catalog_items ?? []}
+          onAccept={(itemId) => handleCatalogAction(acceptCatalogUpdate, itemId, '接受目录更新')}
+          onDismiss={(itemId) => handleCatalogAction(dismissCatalogUpdate, itemId, '忽略目录更新')}
+          onRestore={(catalogId) => handleCatalogAction(restoreCatalogItem, catalogId, '恢复内置软件项')}
+        />
+      )}
+      {liveRun && (
+        <CommandOutputPane run={liveRun} onCancel={handleCancelLiveRun} onClose={() => setLiveRun(null)} />
+      )}
       <MonitorPanel
-        title="Homebrew 与 Bun（手动区）"
+        title="手动区（默认 Homebrew 与 Bun）"
         batchLabel="手动全量检查"
         items={manualItems}
         resultMap={resultMap}
@@ -586,6 +568,7 @@ export default function App() {
         latestCheckAllEntry={latestCheckAllEntry}
         onCheckItem={handleCheckItem}
         onCheckAll={handleCheckAll}
+        onCancelAll={() => handleCancelBatch('check-all')}
         onRunUpdate={handleRunUpdate}
       />
       <MonitorPanel
@@ -599,6 +582,7 @@ export default function App() {
         latestCheckAllEntry={latestAutoCliCheckEntry}
         onCheckItem={handleCheckItem}
         onCheckAll={handleAutoCliCheck}
+        onCancelAll={() => handleCancelBatch('auto-check-cli')}
         onRunUpdate={handleRunUpdate}
       />
       <p className="runtime-hint">当前系统 Node 版本：{activeNodeVersion || '-'}</p>
@@ -613,6 +597,7 @@ export default function App() {
         latestCheckAllEntry={latestRuntimeCheckEntry}
         onCheckItem={handleCheckItem}
         onCheckAll={handleRuntimeCheck}
+        onCancelAll={() => handleCancelBatch('check-runtime')}
         onRunUpdate={handleRunUpdate}
       />
       <MonitorPanel
@@ -627,6 +612,7 @@ export default function App() {
         latestCheckAllEntry={latestAutoAppCheckEntry}
         onCheckItem={handleCheckItem}
         onCheckAll={handleAutoAppCheck}
+        onCancelAll={() => handleCancelBatch('auto-check-app')}
         onRunUpdate={handleRunUpdate}
       />
     </main>
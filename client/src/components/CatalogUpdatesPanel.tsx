@@ -0,0 +1,59 @@
+import type { CatalogUpdate } from '../types/app';
+
+interface CatalogUpdatesPanelProps {
+  updates: CatalogUpdate[];
+  removedItems: string[];
+  onAccept: (itemId: string) => Promise<void>;
+  onDismiss: (itemId: string) => Promise<void>;
+  onRestore: (catalogId: string) => Promise<void>;
+}
+
+const formatValue = (value: unknown): string =>
+  value == null ? '（空）' : typeof value === 'string' ? value : JSON.stringify(value);
+
+export default function CatalogUpdatesPanel({
+  updates,
+  removedItems,
+  onAccept,
+  onDismiss,
+  onRestore,
+}: CatalogUpdatesPanelProps) {
+  if (updates.length === 0 && removedItems.length === 0) {
+    return null;
+  }
+  return (
+    <section className="panel">
+      <div className="panel-header">
+        <h2>内置目录更新</h2>
+        <span className="muted">以下软件项已被修改，目录更新不会自动覆盖</span>
+      </div>
+      {updates.map((update) => (
+        <div key={update.item_id} className="inline-actions">
+          <strong>{update.item_id}</strong>
+          <small>
+            目录 r{update.from_revision} → r{update.to_revision}：
+            {update.changes
+              .map((change) => `${change.field}: ${formatValue(change.before)} → ${formatValue(change.after)}`)
+              .join('；')}
+          </small>
+          <button type="button" className="btn btn-primary" onClick={() => void onAccept(update.item_id)}>
+            接受
+          </button>
+          <button type="button" className="btn" onClick={() => void onDismiss(update.item_id)}>
+            忽略
+          </button>
+        </div>
+      ))}
+      {removedItems.length > 0 && (
+        <div className="inline-actions">
+          <span className="muted">已删除的内置软件项：</span>
+          {removedItems.map((catalogId) => (
+            <button key={catalogId} type="button" className="btn" onClick={() => void onRestore(catalogId)}>
+              恢复 {catalogId}
+            </button>
+          ))}
+        </div>
+      )}
+    </section>
+  );
+}
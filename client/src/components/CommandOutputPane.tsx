@@ -0,0 +1,82 @@
+import { useEffect, useRef } from 'react';
+import type { CommandOutputLine } from '../types/app';
+
+export interface LiveCommandRun {
+  runId: string | null;
+  title: string;
+  lines: CommandOutputLine[];
+  running: boolean;
+  cancelled: boolean;
+  exitCode: number | null;
+}
+
+interface CommandOutputPaneProps {
+  run: LiveCommandRun;
+  onCancel: () => Promise<void>;
+  onClose: () => void;
+}
+
+const statusLabel = (run: LiveCommandRun): string => {
+  if (run.running) {
+    return '运行中';
+  }
+  if (run.cancelled) {
+    return '已取消';
+  }
+  return run.exitCode === null ? '已结束' : `退出码 ${run.exitCode}`;
+};
+
+const statusClass = (run: LiveCommandRun): string => {
+  if (run.running) {
+    return 'status-running';
+  }
+  if (run.cancelled) {
+    return 'status-cancelled';
+  }
+  return run.exitCode === 0 ? 'status-success' : 'status-failed';
+};
+
+export default function CommandOutputPane({ run, onCancel, onClose }: CommandOutputPaneProps) {
+  const scrollRef = useRef<HTMLPreElement | null>(null);
+
+  useEffect(() => {
+    const node = scrollRef.current;
+    if (node) {
+      node.scrollTop = node.scrollHeight;
+    }
+  }, [run.lines.length]);
+
+  return (
+    <section className="panel">
+      <div className="panel-header">
+        <h2>{run.title}</h2>
+        <div className="inline-actions">
+          <span className={`status-badge ${statusClass(run)}`}>
+            {statusLabel(run)}
+          </span>
+          {run.running && (
+            <button type="button" className="btn" disabled={!run.runId} onClick={() => void onCancel()}>
+              取消
+            </button>
+          )}
+          <button type="button" className="btn" disabled={run.running} onClick={onClose}>
+            关闭
+          </button>
+        </div>
+      </div>
+      <pre ref={scrollRef} className="command-output">
+        {run.lines.length === 0
+          ? '等待输出...'
+          : run.lines.map((line, index) => (
+              <span
+                key={`${line.timestamp}-${index}`}
+                className={line.stream === 'stderr' ? 'command-output-stderr' : undefined}
+              >
+                {line.line}
+                {'\n'}
+              </span>
+            ))}
+      </pre>
+    </section>
+  );
+}
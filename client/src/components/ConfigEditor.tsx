@@ -1,11 +1,27 @@
+import { useMemo } from 'react';
+import { validateAgainstSchema, type JsonSchema } from '../lib/configSchema';
+import type { ConfigIssue } from '../types/app';
+
 interface ConfigEditorProps {
   value: string;
+  schema: JsonSchema | null;
   onChange: (nextValue: string) => void;
   onSave: () => Promise<void>;
   onReload: () => Promise<void>;
 }
 
-export default function ConfigEditor({ value, onChange, onSave, onReload }: ConfigEditorProps) {
+const findIssues = (value: string, schema: JsonSchema | null): ConfigIssue[] => {
+  let parsed: unknown;
+  try {
+    parsed = JSON.parse(value);
+  } catch (error) {
+    return [{ field: '$', message: `JSON 格式错误：${error instanceof Error ? error.message : String(error)}` }];
+  }
+  return schema ? validateAgainstSchema(parsed, schema) : [];
+};
+
+export default function ConfigEditor({ value, schema, onChange, onSave, onReload }: ConfigEditorProps) {
+  const issues = useMemo(() => findIssues(value, schema), [value, schema]);
   return (
     <section className="panel">
       <div className="panel-header">
@@ -14,7 +30,12 @@ export default function ConfigEditor({ value, onChange, onSave, onReload }: Conf
           <button type="button" className="btn" onClick={() => void onReload()}>
             重新加载
           </button>
-          <button type="button" className="btn btn-primary" onClick={() => void onSave()}>
+          <button
+            type="button"
+            className="btn btn-primary"
+            disabled={issues.length > 0}
+            onClick={() => void onSave()}
+          >
             保存
           </button>
         </div>
@@ -22,6 +43,15 @@ export default function ConfigEditor({ value, onChange, onSave, onReload }: Conf
       <p>
         你可以在这里编辑软件列表、检查命令和检查间隔。每次更新操作仍然需要手动确认。
       </p>
+      {issues.length > 0 && (
+        <ul className="muted">
+          {issues.map((issue) => (
+            <li key={`${issue.field}:${issue.message}`}>
+              <code>{issue.field}</code>：{issue.message}
+            </li>
+          ))}
+        </ul>
+      )}
       <textarea
         className="editor"
         value={value}
@@ -9,6 +9,9 @@ interface HistoryPanelProps {
 }
 
 const formatStatus = (entry: ExecutionHistoryEntry): string => {
+  if (entry.cancelled) {
+    return '已取消';
+  }
   if (entry.timed_out) {
     return '超时';
   }
@@ -25,6 +28,8 @@ const formatAction = (action: string): string => {
     'check-all-skip': '全量检查（跳过）',
     'auto-check': '自动检查',
     'auto-check-skip': '自动检查（跳过）',
+    'cli-check': '命令行检查',
+    'cli-check-skip': '命令行检查（跳过）',
     'run-item-update': '执行更新',
     'run-shared-command': '执行共享命令',
   };
@@ -41,7 +46,9 @@ const matchFilter = (entry: ExecutionHistoryEntry, filter: HistoryFilter): boole
     return (
       entry.action.startsWith('check-') ||
       entry.action === 'auto-check' ||
-      entry.action === 'auto-check-skip'
+      entry.action === 'auto-check-skip' ||
+      entry.action === 'cli-check' ||
+      entry.action === 'cli-check-skip'
     );
   }
   if (filter === 'update') {
@@ -151,6 +158,14 @@ export default function HistoryPanel({
               {new Date(selectedEntry.recorded_at).toLocaleString('zh-CN')} |{' '}
               {formatAction(selectedEntry.action)} | {selectedEntry.target}
             </p>
+            {selectedEntry.truncated && (
+              <p className="muted">
+                输出过长，仅保留首尾部分。
+                {selectedEntry.full_output_log
+                  ? `完整输出：${selectedEntry.full_output_log}`
+                  : '未保存完整输出。'}
+              </p>
+            )}
             {selectedEntry.command && (
               <>
                 <strong>命令</strong>
@@ -13,10 +13,11 @@ interface MonitorPanelProps {
   latestCheckAllEntry: ExecutionHistoryEntry | null;
   onCheckItem: (itemId: string) => Promise<void>;
   onCheckAll: () => Promise<void>;
+  onCancelAll?: () => Promise<void>;
   onRunUpdate: (item: SoftwareItem) => Promise<void>;
 }
 
-type CheckAllState = 'running' | 'success' | 'failed' | 'skipped' | 'idle';
+type CheckAllState = 'running' | 'success' | 'failed' | 'skipped' | 'cancelled' | 'idle';
 type ItemState = 'outdated' | 'latest' | 'checking' | 'error' | 'unknown' | 'disabled';
 
 const statusText = (item: SoftwareItem, result?: CheckResult): string => {
@@ -29,6 +30,12 @@ const statusText = (item: SoftwareItem, result?: CheckResult): string => {
   if (result.error) {
     return `错误：${result.error}`;
   }
+  if (result.version_comparison === 'older') {
+    return '本地版本较新';
+  }
+  if (result.version_comparison === 'incomparable') {
+    return '版本无法比较';
+  }
   return result.has_update ? '有可用更新' : '已是最新';
 };
 
@@ -89,6 +96,9 @@ const resolveCheckAllState = (
   if (latestCheckAllEntry.action.endsWith('-skip')) {
     return 'skipped';
   }
+  if (latestCheckAllEntry.cancelled) {
+    return 'cancelled';
+  }
   return latestCheckAllEntry.success ? 'success' : 'failed';
 };
 
@@ -104,6 +114,7 @@ export default function MonitorPanel({
   latestCheckAllEntry,
   onCheckItem,
   onCheckAll,
+  onCancelAll,
   onRunUpdate,
 }: MonitorPanelProps) {
   const checkAllState = resolveCheckAllState(checkAllRunning, latestCheckAllEntry);
@@ -112,6 +123,7 @@ export default function MonitorPanel({
     success: '成功',
     failed: '失败',
     skipped: '已跳过',
+    cancelled: '已取消',
     idle: '空闲',
   }[checkAllState];
   const checkAllStatus = checkAllRunning
@@ -165,6 +177,11 @@ export default function MonitorPanel({
           >
             {checkAllRunning ? '检查中...' : batchLabel}
           </button>
+          {checkAllRunning && onCancelAll && (
+            <button type="button" className="btn" onClick={() => void onCancelAll()}>
+              取消
+            </button>
+          )}
         </div>
       </div>
       <p className="muted">{checkAllStatus}</p>
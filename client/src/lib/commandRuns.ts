@@ -0,0 +1,50 @@
+import { listen } from '@tauri-apps/api/event';
+import type { CommandOutputLine, CommandRunFinished } from '../types/app';
+
+const OUTPUT_EVENT = 'patchpilot://command-output';
+const FINISHED_EVENT = 'patchpilot://command-finished';
+
+// Listeners are attached before the run starts, so events that arrive before
+// the run id is known are buffered and replayed once it is.
+export const followCommandRun = async (
+  start: () => Promise<string>,
+  onStarted: (runId: string) => void,
+  onLine: (line: CommandOutputLine) => void
+): Promise<CommandRunFinished> => {
+  let runId: string | null = null;
+  const pendingLines: CommandOutputLine[] = [];
+  const finishedRuns = new Map<string, CommandRunFinished>();
+  let resolveFinished: ((value: CommandRunFinished) => void) | null = null;
+
+  const unlistenOutput = await listen<CommandOutputLine>(OUTPUT_EVENT, (event) => {
+    if (runId === null) {
+      pendingLines.push(event.payload);
+    } else if (event.payload.run_id === runId) {
+      onLine(event.payload);
+    }
+  });
+  const unlistenFinished = await listen<CommandRunFinished>(FINISHED_EVENT, (event) => {
+    if (runId === null) {
+      finishedRuns.set(event.payload.run_id, event.payload);
+    } else if (event.payload.run_id === runId) {
+      resolveFinished?.(event.payload);
+    }
+  });
+
+  try {
+    const finished = new Promise<CommandRunFinished>((resolve) => {
+      resolveFinished = resolve;
+    });
+    runId = await start();
+    onStarted(runId);
+    pendingLines.filter((line) => line.run_id === runId).forEach(onLine);
+    const early = finishedRuns.get(runId);
+    if (early) {
+      return early;
+    }
+    return await finished;
+  } finally {
+    unlistenOutput();
+    unlistenFinished();
+  }
+};
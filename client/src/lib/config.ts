@@ -1,4 +1,31 @@
-import type { AppConfig } from '../types/app';
+import type { AppConfig, CheckSchedule, ExecutionProfile } from '../types/app';
+
+const defaultExecutionProfile: ExecutionProfile = {
+  shell: 'zsh',
+  shell_args: [],
+  login: true,
+  interactive: false,
+  env: {},
+  working_directory: null,
+};
+
+const clockPattern = /^([01]\d|2[0-3]):[0-5]\d$/;
+
+const validateSchedule = (schedule: CheckSchedule | null | undefined, label: string): string | null => {
+  if (!schedule) {
+    return null;
+  }
+  if (
+    schedule.interval_minutes != null &&
+    (!Number.isInteger(schedule.interval_minutes) || schedule.interval_minutes < 1)
+  ) {
+    return `${label}.interval_minutes 必须是大于等于 1 的整数`;
+  }
+  if (schedule.cron != null && schedule.cron.trim().split(/\s+/).length !== 5) {
+    return `${label}.cron 必须是 5 段 cron 表达式（分 时 日 月 周）`;
+  }
+  return null;
+};
 
 export const validateConfig = (config: AppConfig): string | null => {
   if (!Number.isFinite(config.check_interval_minutes) || config.check_interval_minutes < 1) {
@@ -7,9 +34,60 @@ export const validateConfig = (config: AppConfig): string | null => {
   if (!Number.isFinite(config.command_timeout_seconds) || config.command_timeout_seconds < 1) {
     return 'command_timeout_seconds 必须大于等于 1';
   }
+  if (!Number.isInteger(config.max_parallel_checks) || config.max_parallel_checks < 1) {
+    return 'max_parallel_checks 必须是大于等于 1 的整数';
+  }
+  if (!Number.isInteger(config.host_request_interval_ms) || config.host_request_interval_ms < 0) {
+    return 'host_request_interval_ms 必须是大于等于 0 的整数';
+  }
+  if (!Number.isInteger(config.max_output_bytes) || config.max_output_bytes < 1) {
+    return 'max_output_bytes 必须是大于等于 1 的整数';
+  }
+  if (typeof config.keep_full_output_logs !== 'boolean') {
+    return 'keep_full_output_logs 必须是 boolean';
+  }
+  if (!Number.isInteger(config.run_log_retention_days) || config.run_log_retention_days < 1) {
+    return 'run_log_retention_days 必须是大于等于 1 的整数';
+  }
+  if (!Number.isInteger(config.run_log_max_total_mb) || config.run_log_max_total_mb < 1) {
+    return 'run_log_max_total_mb 必须是大于等于 1 的整数';
+  }
+  if (!Number.isInteger(config.history_retention_days) || config.history_retention_days < 1) {
+    return 'history_retention_days 必须是大于等于 1 的整数';
+  }
+  if (typeof config.execution_profile?.shell !== 'string' || !config.execution_profile.shell.trim()) {
+    return 'execution_profile.shell 不能为空';
+  }
   if (!Array.isArray(config.items) || config.items.length === 0) {
     return 'items 必须是非空数组';
   }
+  for (const [group, schedule] of Object.entries(config.group_schedules ?? {})) {
+    const error = validateSchedule(schedule, `group_schedules.${group}`);
+    if (error) {
+      return error;
+    }
+  }
+  for (const item of config.items) {
+    const error = validateSchedule(item.schedule, `${item.id}.schedule`);
+    if (error) {
+      return error;
+    }
+    if (!['cli', 'gui', 'app', 'runtime'].includes(item.kind)) {
+      return `${item.id}.kind 仅支持 cli / gui / app / runtime`;
+    }
+    if (item.check_policy != null && !['manual', 'auto', 'disabled'].includes(item.check_policy)) {
+      return `${item.id}.check_policy 仅支持 manual / auto / disabled`;
+    }
+    if (item.group != null && !['cli', 'gui', 'runtime'].includes(item.group)) {
+      return `${item.id}.group 仅支持 cli / gui / runtime`;
+    }
+  }
+  if (
+    config.quiet_hours &&
+    (!clockPattern.test(config.quiet_hours.start) || !clockPattern.test(config.quiet_hours.end))
+  ) {
+    return 'quiet_hours.start / end 必须是 HH:MM 格式';
+  }
   if (!['light', 'dark', 'system'].includes(config.theme_mode)) {
     return 'theme_mode 仅支持 light / dark / system';
   }
@@ -25,7 +103,18 @@ export const validateConfig = (config: AppConfig): string | null => {
 export const normalizeConfig = (payload: Partial<AppConfig>): AppConfig => ({
   ...payload,
   check_interval_minutes: Number(payload.check_interval_minutes),
+  group_schedules: payload.group_schedules ?? {},
+  quiet_hours: payload.quiet_hours ?? null,
   command_timeout_seconds: Number(payload.command_timeout_seconds ?? 120),
+  max_parallel_checks: Number(payload.max_parallel_checks ?? 4),
+  host_request_interval_ms: Number(payload.host_request_interval_ms ?? 500),
+  execution_profile: { ...defaultExecutionProfile, ...payload.execution_profile },
+  max_output_bytes: Number(payload.max_output_bytes ?? 65536),
+  keep_full_output_logs:
+    typeof payload.keep_full_output_logs === 'boolean' ? payload.keep_full_output_logs : true,
+  run_log_retention_days: Number(payload.run_log_retention_days ?? 14),
+  run_log_max_total_mb: Number(payload.run_log_max_total_mb ?? 200),
+  history_retention_days: Number(payload.history_retention_days ?? 180),
   theme_mode:
     payload.theme_mode === 'light' || payload.theme_mode === 'dark' || payload.theme_mode === 'system'
       ? payload.theme_mode
@@ -35,6 +124,7 @@ export const normalizeConfig = (payload: Partial<AppConfig>): AppConfig => ({
     typeof payload.auto_check_manual_enabled === 'boolean'
       ? payload.auto_check_manual_enabled
       : true,
+  removed_catalog_items: payload.removed_catalog_items ?? [],
   shared_update_commands: payload.shared_update_commands ?? [],
   items: payload.items ?? [],
 });
@@ -0,0 +1,108 @@
+import type { ConfigIssue } from '../types/app';
+
+// Subset of JSON Schema emitted by the backend's `get_config_schema`.
+export interface JsonSchema {
+  $ref?: string;
+  type?: string | string[];
+  enum?: unknown[];
+  properties?: Record<string, JsonSchema>;
+  required?: string[];
+  additionalProperties?: JsonSchema | boolean;
+  items?: JsonSchema;
+  oneOf?: JsonSchema[];
+  anyOf?: JsonSchema[];
+  allOf?: JsonSchema[];
+  minimum?: number;
+  definitions?: Record<string, JsonSchema>;
+}
+
+const typeOf = (value: unknown): string => {
+  if (value === null) {
+    return 'null';
+  }
+  if (Array.isArray(value)) {
+    return 'array';
+  }
+  if (typeof value === 'number') {
+    return Number.isInteger(value) ? 'integer' : 'number';
+  }
+  return typeof value;
+};
+
+const matchesType = (value: unknown, expected: string | string[]): boolean => {
+  const actual = typeOf(value);
+  const types = Array.isArray(expected) ? expected : [expected];
+  return types.some((type) => type === actual || (type === 'number' && actual === 'integer'));
+};
+
+const resolve = (schema: JsonSchema, root: JsonSchema): JsonSchema => {
+  if (!schema.$ref) {
+    return schema;
+  }
+  const name = schema.$ref.replace('#/definitions/', '');
+  return root.definitions?.[name] ?? {};
+};
+
+const validateNode = (
+  value: unknown,
+  node: JsonSchema,
+  root: JsonSchema,
+  path: string,
+  issues: ConfigIssue[]
+): void => {
+  const schema = resolve(node, root);
+  const field = path || '$';
+  for (const part of schema.allOf ?? []) {
+    validateNode(value, part, root, path, issues);
+  }
+  const alternatives = schema.oneOf ?? schema.anyOf;
+  if (alternatives) {
+    const matched = alternatives.some((option) => {
+      const optionIssues: ConfigIssue[] = [];
+      validateNode(value, option, root, path, optionIssues);
+      return optionIssues.length === 0;
+    });
+    if (!matched) {
+      issues.push({ field, message: 'does not match any allowed shape' });
+    }
+    return;
+  }
+  if (schema.type && !matchesType(value, schema.type)) {
+    issues.push({ field, message: `expected ${[schema.type].flat().join(' | ')}` });
+    return;
+  }
+  if (schema.enum && !schema.enum.includes(value)) {
+    issues.push({ field, message: `expected one of ${schema.enum.join(' / ')}` });
+    return;
+  }
+  if (schema.minimum != null && typeof value === 'number' && value < schema.minimum) {
+    issues.push({ field, message: `must be at least ${schema.minimum}` });
+  }
+  if (Array.isArray(value) && schema.items) {
+    value.forEach((entry, index) => validateNode(entry, schema.items!, root, `${path}[${index}]`, issues));
+  }
+  if (typeOf(value) !== 'object') {
+    return;
+  }
+  const record = value as Record<string, unknown>;
+  const prefix = path ? `${path}.` : '';
+  for (const key of schema.required ?? []) {
+    if (!(key in record)) {
+      issues.push({ field: `${prefix}${key}`, message: 'is required' });
+    }
+  }
+  for (const [key, entry] of Object.entries(record)) {
+    const property = schema.properties?.[key];
+    if (property) {
+      validateNode(entry, property, root, `${prefix}${key}`, issues);
+    } else if (typeof schema.additionalProperties === 'object') {
+      validateNode(entry, schema.additionalProperties, root, `${prefix}${key}`, issues);
+    }
+  }
+};
+
+export const validateAgainstSchema = (value: unknown, schema: JsonSchema): ConfigIssue[] => {
+  const issues: ConfigIssue[] = [];
+  validateNode(value, schema, schema, '', issues);
+  return issues;
+};
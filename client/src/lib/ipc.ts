@@ -1,22 +1,69 @@
 import { invoke } from '@tauri-apps/api/core';
+import type { JsonSchema } from './configSchema';
 import type {
+  ActiveRun,
   AppConfig,
+  CatalogUpdate,
   CheckResult,
-  CommandOutput,
+  ConfigIssue,
+  ConfigSaveError,
   ExecutionHistoryEntry,
+  FleetReport,
+  HistoryPage,
+  HistoryQuery,
+  ItemTimeline,
   LatestResultState,
-  UpdateResult,
+  LoadedConfig,
+  ReportFormat,
+  RunLogPage,
+  RunLogSummary,
+  SchedulerStatus,
 } from '../types/app';
 
-export const loadConfig = async (): Promise<AppConfig> => invoke('load_config');
+export const loadConfig = async (): Promise<LoadedConfig> => invoke('load_config');
+
+export class ConfigValidationError extends Error {
+  constructor(readonly issues: ConfigIssue[]) {
+    super(issues.map((issue) => `${issue.field}: ${issue.message}`).join('; '));
+    this.name = 'ConfigValidationError';
+  }
+}
+
+export const getConfigSchema = async (): Promise<JsonSchema> => invoke('get_config_schema');
+
+export const validateConfig = async (config: AppConfig): Promise<ConfigIssue[]> =>
+  invoke('validate_config', { config });
 
 export const saveConfig = async (config: AppConfig): Promise<void> => {
-  await invoke('save_config', { config });
+  try {
+    await invoke('save_config', { config });
+  } catch (error) {
+    const saveError = error as ConfigSaveError;
+    if (saveError?.kind === 'invalid') {
+      throw new ConfigValidationError(saveError.issues);
+    }
+    throw new Error(saveError?.kind === 'failed' ? saveError.message : String(error));
+  }
 };
 
+export const listCatalogUpdates = async (): Promise<CatalogUpdate[]> =>
+  invoke('list_catalog_updates');
+
+export const acceptCatalogUpdate = async (itemId: string): Promise<void> =>
+  invoke('accept_catalog_update', { itemId });
+
+export const dismissCatalogUpdate = async (itemId: string): Promise<void> =>
+  invoke('dismiss_catalog_update', { itemId });
+
+export const restoreCatalogItem = async (catalogId: string): Promise<void> =>
+  invoke('restore_catalog_item', { catalogId });
+
 export const loadLatestResults = async (): Promise<LatestResultState> =>
   invoke('load_latest_results');
 
+export const loadItemTimeline = async (itemId: string): Promise<ItemTimeline> =>
+  invoke('load_item_timeline', { itemId });
+
 export const checkItem = async (itemId: string): Promise<CheckResult> =>
   invoke('check_item', { itemId });
 
@@ -26,17 +73,47 @@ export const checkAutoCliItems = async (): Promise<CheckResult[]> => invoke('che
 export const checkAutoAppItems = async (): Promise<CheckResult[]> => invoke('check_auto_app_items');
 export const checkRuntimeItems = async (): Promise<CheckResult[]> => invoke('check_runtime_items');
 
-export const runItemUpdate = async (itemId: string): Promise<UpdateResult> =>
+export const runItemUpdate = async (itemId: string): Promise<string> =>
   invoke('run_item_update', { itemId });
 
-export const runAdHocCommand = async (command: string): Promise<CommandOutput> =>
+export const runAdHocCommand = async (command: string): Promise<string> =>
   invoke('run_ad_hoc_command', { command });
 
+export const cancelRun = async (runId: string): Promise<void> => {
+  await invoke('cancel_run', { runId });
+};
+
+export const listActiveRuns = async (): Promise<ActiveRun[]> => invoke('list_active_runs');
+
 export const getActiveNodeVersion = async (): Promise<string> =>
   invoke('get_active_node_version');
 
+export const generateReport = async (
+  format: ReportFormat,
+  path: string,
+  days?: number
+): Promise<FleetReport> => invoke('generate_report', { format, path, days });
+
+export const getSchedulerStatus = async (): Promise<SchedulerStatus> =>
+  invoke('get_scheduler_status');
+
 export const loadHistory = async (limit = 50): Promise<ExecutionHistoryEntry[]> =>
   invoke('load_history', { limit });
 
+export const queryHistory = async (query: HistoryQuery = {}): Promise<HistoryPage> =>
+  invoke('query_history', { query });
+
+export const listRunLogs = async (limit = 100): Promise<RunLogSummary[]> =>
+  invoke('list_run_logs', { limit });
+
+export const readRunLog = async (
+  logId: string,
+  offset = 0,
+  limit?: number
+): Promise<RunLogPage> => invoke('read_run_log', { logId, offset, limit });
+
+export const deleteRunLog = async (logId: string): Promise<void> =>
+  invoke('delete_run_log', { logId });
+
 export const detectInstalledItems = async (): Promise<Record<string, boolean>> =>
   invoke('detect_installed_items');
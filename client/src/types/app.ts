@@ -1,5 +1,59 @@
 export type SoftwareKind = 'cli' | 'gui' | 'app' | 'runtime';
+export type CheckPolicy = 'manual' | 'auto' | 'disabled';
+export type ItemGroup = 'cli' | 'gui' | 'runtime';
 export type ThemeMode = 'light' | 'dark' | 'system';
+export type VersionComparator = 'auto' | 'semver' | 'dotted' | 'calendar' | 'exact';
+export type VersionComparison = 'newer' | 'same' | 'older' | 'incomparable';
+
+export type VersionSource =
+  | { type: 'github_release'; repo: string }
+  | { type: 'npm_package'; package: string }
+  | { type: 'homebrew_cask'; token: string }
+  | { type: 'homebrew_formula'; name: string }
+  | { type: 'pypi'; package: string }
+  | { type: 'crates_io'; name: string }
+  | { type: 'json_url'; url: string; pointer: string }
+  | { type: 'html_regex'; url: string; pattern: string };
+
+export type InstalledVersionProbe =
+  | { type: 'plist_key'; path: string; key?: string }
+  | { type: 'binary_version'; binary: string; args?: string[]; pattern?: string | null }
+  | { type: 'npm_global_package'; package: string; prefix?: string | null }
+  | { type: 'rust_toolchain'; toolchain?: string };
+
+export interface ExecutionProfile {
+  shell: string;
+  shell_args: string[];
+  login: boolean;
+  interactive: boolean;
+  env: Record<string, string>;
+  working_directory: string | null;
+}
+
+export interface ExecutionProfileOverride {
+  shell?: string | null;
+  shell_args?: string[] | null;
+  login?: boolean | null;
+  interactive?: boolean | null;
+  env?: Record<string, string>;
+  working_directory?: string | null;
+}
+
+export interface CheckSchedule {
+  interval_minutes?: number | null;
+  cron?: string | null;
+}
+
+export interface QuietHours {
+  start: string;
+  end: string;
+}
+
+export interface ItemOrigin {
+  catalog_id: string;
+  revision: number;
+  fingerprint: string;
+}
 
 export interface SoftwareItem {
   id: string;
@@ -8,15 +62,37 @@ export interface SoftwareItem {
   enabled: boolean;
   description: string;
   current_version_command: string | null;
+  installed_probe?: InstalledVersionProbe | null;
   latest_version_command: string | null;
+  version_source?: VersionSource | null;
   update_check_command: string | null;
   update_check_regex: string | null;
   update_command: string;
+  version_comparator?: VersionComparator;
+  execution?: ExecutionProfileOverride | null;
+  schedule?: CheckSchedule | null;
+  check_policy?: CheckPolicy | null;
+  group?: ItemGroup | null;
+  origin?: ItemOrigin | null;
 }
 
 export interface AppConfig {
+  $schema?: string | null;
+  schema_version?: number;
+  catalog_revision?: number;
+  removed_catalog_items?: string[];
   check_interval_minutes: number;
+  group_schedules: Record<string, CheckSchedule>;
+  quiet_hours: QuietHours | null;
   command_timeout_seconds: number;
+  max_parallel_checks: number;
+  host_request_interval_ms: number;
+  execution_profile: ExecutionProfile;
+  max_output_bytes: number;
+  keep_full_output_logs: boolean;
+  run_log_retention_days: number;
+  run_log_max_total_mb: number;
+  history_retention_days: number;
   theme_mode: ThemeMode;
   auto_check_enabled: boolean;
   auto_check_manual_enabled: boolean;
@@ -30,6 +106,7 @@ export interface CheckResult {
   has_update: boolean;
   current_version: string | null;
   latest_version: string | null;
+  version_comparison: VersionComparison | null;
   details: string;
   error: string | null;
 }
@@ -40,6 +117,7 @@ export interface LatestResultSnapshot {
   has_update: boolean;
   current_version: string | null;
   latest_version: string | null;
+  version_comparison: VersionComparison | null;
   error: string | null;
 }
 
@@ -55,12 +133,32 @@ export interface CommandOutput {
   stderr: string;
   duration_ms: number;
   timed_out: boolean;
+  cancelled: boolean;
+  truncated: boolean;
+  full_output_log: string | null;
 }
 
-export interface UpdateResult {
-  item_id: string;
-  updated_at: string;
-  output: CommandOutput;
+export type CommandStream = 'stdout' | 'stderr';
+
+export interface CommandOutputLine {
+  run_id: string;
+  stream: CommandStream;
+  line: string;
+  timestamp: string;
+}
+
+export interface ActiveRun {
+  run_id: string;
+  action: string;
+  target: string;
+  started_at: string;
+}
+
+export interface CommandRunFinished {
+  run_id: string;
+  target: string;
+  output: CommandOutput | null;
+  error: string | null;
 }
 
 export interface ExecutionHistoryEntry {
@@ -74,6 +172,158 @@ export interface ExecutionHistoryEntry {
   success: boolean;
   exit_code: number | null;
   timed_out: boolean;
+  cancelled?: boolean;
+  truncated?: boolean;
+  full_output_log?: string | null;
   duration_ms: number | null;
   summary: string;
 }
+
+export interface VersionTransition {
+  observed_at: string;
+  last_seen_at: string;
+  current_version: string | null;
+  latest_version: string | null;
+  has_update: boolean;
+}
+
+export interface UpdateLag {
+  version: string;
+  first_seen_at: string;
+  installed_at: string;
+  hours: number;
+}
+
+export interface TimelineMetrics {
+  days_behind_latest: number | null;
+  total_days_behind: number;
+  update_lags: UpdateLag[];
+  average_hours_to_update: number | null;
+}
+
+export interface ItemTimeline {
+  item_id: string;
+  transitions: VersionTransition[];
+  metrics: TimelineMetrics;
+}
+
+export type ReportFormat = 'markdown' | 'html' | 'json';
+
+export interface OutdatedItem {
+  item_id: string;
+  name: string;
+  current_version: string | null;
+  latest_version: string | null;
+  checked_at: string;
+  days_behind: number | null;
+}
+
+export interface ItemErrorSummary {
+  item_id: string;
+  count: number;
+  last_error: string;
+  last_at: string;
+}
+
+export interface ReportRun {
+  action: string;
+  target: string;
+  recorded_at: string;
+  success: boolean;
+  duration_ms: number | null;
+  summary: string;
+}
+
+export interface FleetReport {
+  generated_at: string;
+  since: string;
+  until: string;
+  outdated: OutdatedItem[];
+  errors: ItemErrorSummary[];
+  updates: ReportRun[];
+  average_hours_to_update: number | null;
+  slowest_runs: ReportRun[];
+}
+
+export interface HistoryQuery {
+  target?: string | null;
+  action?: string | null;
+  success?: boolean | null;
+  since?: string | null;
+  until?: string | null;
+  text?: string | null;
+  cursor?: string | null;
+  limit?: number | null;
+}
+
+export interface HistoryPage {
+  entries: ExecutionHistoryEntry[];
+  next_cursor: string | null;
+}
+
+export interface MigrationChange {
+  item_id: string | null;
+  field: string | null;
+  before: unknown;
+  after: unknown;
+}
+
+export interface MigrationStepReport {
+  id: string;
+  description: string;
+  changes: MigrationChange[];
+}
+
+export interface MigrationReport {
+  from_version: number;
+  to_version: number;
+  backup_path: string | null;
+  steps: MigrationStepReport[];
+}
+
+export interface CatalogUpdate {
+  item_id: string;
+  catalog_id: string;
+  from_revision: number;
+  to_revision: number;
+  changes: MigrationChange[];
+}
+
+export interface LoadedConfig {
+  config: AppConfig;
+  migration_report: MigrationReport | null;
+}
+
+export interface ConfigIssue {
+  field: string;
+  message: string;
+}
+
+export type ConfigSaveError =
+  | { kind: 'invalid'; issues: ConfigIssue[] }
+  | { kind: 'failed'; message: string };
+
+export interface SchedulerStatus {
+  enabled: boolean;
+  running: boolean;
+  last_run_at: string | null;
+  next_run_at: string | null;
+  last_duration_ms: number | null;
+  last_checked_count: number;
+  last_failure_reason: string | null;
+}
+
+export interface RunLogSummary {
+  id: string;
+  path: string | null;
+  size_bytes: number;
+  modified_at: string;
+  entry: ExecutionHistoryEntry | null;
+}
+
+export interface RunLogPage {
+  id: string;
+  offset: number;
+  next_offset: number | null;
+  lines: string[];
+}
@@ -0,0 +1,572 @@
+//! Headless entry point: checks, updates, history and reports against the
+//! same stores as the app, for terminals, SSH sessions, cron and CI.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use patchpilot_lib::model::{
+    AppConfig, CheckPolicy, CheckResult, HistoryQuery, ItemGroup, LatestResultSnapshot,
+    ReportFormat, SoftwareItem,
+};
+use patchpilot_lib::services::run_registry::{self, RunHandle};
+use patchpilot_lib::services::shell_runner::LineSink;
+use patchpilot_lib::services::storage_paths::StoragePaths;
+use patchpilot_lib::services::store::FileStore;
+use patchpilot_lib::services::{
+    config_store, config_validation, history_store, operations, report, result_store,
+};
+
+const USAGE: &str = "用法：patchpilot-cli [--data-dir <目录>] [--json] <命令>
+
+命令：
+  check [--item <id> | --group <cli|gui|runtime>]
+      检查更新；默认检查所有启用且未停用检查的项目
+  update <id>
+      执行项目的更新命令，实时输出命令输出
+  list
+      列出项目及最近一次检查结果
+  history [--item <id>] [--action <动作>] [--failed] [--limit <n>]
+      查看执行历史（默认最近 20 条）
+  config validate [<文件>]
+      校验配置文件（默认为当前使用的配置）
+  config repair
+      配置文件无法解析时从备份恢复（应用启动时也会自动执行）
+  report [--format markdown|html|json] [--days <n>] [--output <文件>]
+      生成巡检报告；未指定 --output 时输出到标准输出
+
+--data-dir 默认为应用的配置目录；--json 以 JSON 输出结果。
+有检查错误、更新失败或配置不合法时退出码为 1，参数有误时为 2。";
+
+const DEFAULT_HISTORY_LIMIT: u32 = 20;
+/// Longer cells (errors, summaries) are cut so each row stays on one line;
+/// `--json` has the full text.
+const MAX_CELL_WIDTH: usize = 60;
+
+enum Failure {
+    /// Bad arguments; printed together with the usage.
+    Usage(String),
+    Error(String),
+}
+
+impl From<String> for Failure {
+    fn from(error: String) -> Self {
+        Failure::Error(error)
+    }
+}
+
+/// The arguments not consumed yet.
+struct Args {
+    items: Vec<String>,
+}
+
+impl Args {
+    fn flag(&mut self, name: &str) -> bool {
+        let position = self.items.iter().position(|item| item == name);
+        if let Some(position) = position {
+            self.items.remove(position);
+        }
+        position.is_some()
+    }
+
+    fn value(&mut self, name: &str) -> Result<Option<String>, Failure> {
+        let Some(position) = self.items.iter().position(|item| item == name) else {
+            return Ok(None);
+        };
+        if position + 1 >= self.items.len() {
+            return Err(Failure::Usage(format!("{name} 需要一个值")));
+        }
+        self.items.remove(position);
+        Ok(Some(self.items.remove(position)))
+    }
+
+    /// The first argument that is not an option; take options first.
+    fn positional(&mut self) -> Option<String> {
+        let position = self.items.iter().position(|item| !item.starts_with('-'))?;
+        Some(self.items.remove(position))
+    }
+
+    fn finish(self) -> Result<(), Failure> {
+        if self.items.is_empty() {
+            Ok(())
+        } else {
+            Err(Failure::Usage(format!(
+                "无法识别的参数：{}",
+                self.items.join(" ")
+            )))
+        }
+    }
+}
+
+/// Parses an option value the way the app's JSON would spell it.
+fn parse_enum<T: DeserializeOwned>(name: &str, raw: &str) -> Result<T, Failure> {
+    serde_json::from_value(serde_json::Value::String(raw.to_string()))
+        .map_err(|_| Failure::Usage(format!("{name} 的取值无效：{raw}")))
+}
+
+fn parse_number(name: &str, raw: Option<String>) -> Result<Option<u32>, Failure> {
+    raw.map(|raw| {
+        raw.parse()
+            .map_err(|_| Failure::Usage(format!("{name} 需要一个正整数：{raw}")))
+    })
+    .transpose()
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), Failure> {
+    let text = serde_json::to_string_pretty(value)
+        .map_err(|error| format!("failed to serialize output: {error}"))?;
+    println!("{text}");
+    Ok(())
+}
+
+/// Terminal columns taken by `ch`; CJK and full-width characters take two.
+fn char_width(ch: char) -> usize {
+    match ch as u32 {
+        0x1100..=0x115F
+        | 0x2E80..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6 => 2,
+        _ => 1,
+    }
+}
+
+fn display_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+fn table_cell(text: &str) -> String {
+    let text = text.replace('\n', " ");
+    if display_width(&text) <= MAX_CELL_WIDTH {
+        return text;
+    }
+    let mut cell = String::new();
+    let mut width = 0;
+    for ch in text.chars() {
+        width += char_width(ch);
+        if width >= MAX_CELL_WIDTH {
+            break;
+        }
+        cell.push(ch);
+    }
+    cell.push('…');
+    cell
+}
+
+fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.iter().map(|cell| table_cell(cell)).collect())
+        .collect();
+    let mut widths: Vec<usize> = headers.iter().map(|header| display_width(header)).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(display_width(cell));
+        }
+    }
+    let line = |cells: &[&str]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell}{}", " ".repeat(width - display_width(cell))))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    println!("{}", line(headers));
+    for row in &rows {
+        let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+        println!("{}", line(&cells));
+    }
+}
+
+fn format_time(raw: &str) -> String {
+    DateTime::parse_from_rfc3339(raw)
+        .map(|value| {
+            value
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|_| raw.to_string())
+}
+
+fn format_version(version: &Option<String>) -> String {
+    version.clone().unwrap_or_else(|| "-".to_string())
+}
+
+fn status_text(has_update: bool, error: &Option<String>) -> String {
+    match (error, has_update) {
+        (Some(error), _) => format!("错误：{error}"),
+        (None, true) => "有更新".to_string(),
+        (None, false) => "已是最新".to_string(),
+    }
+}
+
+/// Items a plain `check` covers: everything enabled that batch checks do not skip.
+fn is_cli_checked(item: &SoftwareItem) -> bool {
+    item.enabled && item.policy() != CheckPolicy::Disabled
+}
+
+fn check(store: &FileStore, mut args: Args, json: bool) -> Result<bool, Failure> {
+    let item_id = args.value("--item")?;
+    let group = args
+        .value("--group")?
+        .map(|raw| parse_enum::<ItemGroup>("--group", &raw))
+        .transpose()?;
+    args.finish()?;
+
+    let results: Vec<CheckResult> = match (item_id, group) {
+        (Some(_), Some(_)) => {
+            return Err(Failure::Usage("--item 与 --group 不能同时使用".to_string()))
+        }
+        (Some(item_id), None) => vec![operations::check_item(store, &item_id)?],
+        (None, group) => operations::check_items(
+            store,
+            "cli-check",
+            "cli-check-skip",
+            "已跳过：上一轮命令行检查仍在运行",
+            |item| is_cli_checked(item) && group.is_none_or(|group| item.group() == group),
+        )
+        .map_err(String::from)?,
+    };
+
+    if json {
+        print_json(&results)?;
+    } else {
+        let rows: Vec<Vec<String>> = results
+            .iter()
+            .map(|result| {
+                vec![
+                    result.item_id.clone(),
+                    format_version(&result.current_version),
+                    format_version(&result.latest_version),
+                    status_text(result.has_update, &result.error),
+                ]
+            })
+            .collect();
+        print_table(&["项目", "当前版本", "最新版本", "状态"], &rows);
+    }
+    Ok(results.iter().all(|result| result.error.is_none()))
+}
+
+fn update(store: &FileStore, mut args: Args, json: bool) -> Result<bool, Failure> {
+    let item_id = args
+        .positional()
+        .ok_or_else(|| Failure::Usage("update 需要项目 id".to_string()))?;
+    args.finish()?;
+
+    let config = config_store::load_or_init_config(store)?;
+    let item = operations::find_item(&config, &item_id)
+        .ok_or_else(|| format!("item not found: {item_id}"))?;
+    let run = RunHandle::register("run-item-update", &item_id);
+    let sink: Option<LineSink> = (!json).then(|| {
+        Arc::new(|stream: &str, line: &str| {
+            if stream == "stderr" {
+                eprintln!("{line}");
+            } else {
+                println!("{line}");
+            }
+        }) as LineSink
+    });
+    let output = operations::run_item_update(store, &config, item, &run.token(), sink)?;
+
+    if json {
+        print_json(&output)?;
+    } else if output.cancelled {
+        eprintln!("已取消更新 {}", item.name);
+    } else if output.timed_out {
+        eprintln!("更新 {} 超时", item.name);
+    } else {
+        eprintln!(
+            "更新 {}（退出码 {}，耗时 {:.1} 秒）",
+            item.name,
+            output.exit_code,
+            output.duration_ms as f64 / 1000.0
+        );
+    }
+    Ok(output.exit_code == 0 && !output.timed_out && !output.cancelled)
+}
+
+fn list(store: &FileStore, args: Args, json: bool) -> Result<bool, Failure> {
+    args.finish()?;
+    let config = config_store::load_or_init_config(store)?;
+    let latest = result_store::load_state(store)?;
+
+    if json {
+        let items: Vec<serde_json::Value> = config
+            .items
+            .iter()
+            .map(|item| {
+                serde_json::json!({
+                    "id": item.id,
+                    "name": item.name,
+                    "enabled": item.enabled,
+                    "group": item.group(),
+                    "check_policy": item.policy(),
+                    "latest": latest.items.get(&item.id),
+                })
+            })
+            .collect();
+        print_json(&items)?;
+        return Ok(true);
+    }
+
+    let rows: Vec<Vec<String>> = config
+        .items
+        .iter()
+        .map(|item| {
+            let snapshot: Option<&LatestResultSnapshot> = latest.items.get(&item.id);
+            vec![
+                item.id.clone(),
+                item.name.clone(),
+                item.group().as_str().to_string(),
+                if item.enabled {
+                    item.policy().as_str().to_string()
+                } else {
+                    "未启用".to_string()
+                },
+                snapshot.map_or_else(
+                    || "-".to_string(),
+                    |snapshot| format_version(&snapshot.current_version),
+                ),
+                snapshot.map_or_else(
+                    || "-".to_string(),
+                    |snapshot| format_version(&snapshot.latest_version),
+                ),
+                snapshot.map_or_else(
+                    || "未检查".to_string(),
+                    |snapshot| status_text(snapshot.has_update, &snapshot.error),
+                ),
+                snapshot.map_or_else(
+                    || "-".to_string(),
+                    |snapshot| format_time(&snapshot.checked_at),
+                ),
+            ]
+        })
+        .collect();
+    print_table(
+        &[
+            "ID",
+            "名称",
+            "分组",
+            "策略",
+            "当前版本",
+            "最新版本",
+            "状态",
+            "检查时间",
+        ],
+        &rows,
+    );
+    Ok(true)
+}
+
+fn history(store: &FileStore, mut args: Args, json: bool) -> Result<bool, Failure> {
+    let query = HistoryQuery {
+        target: args.value("--item")?,
+        action: args.value("--action")?,
+        success: args.flag("--failed").then_some(false),
+        limit: Some(
+            parse_number("--limit", args.value("--limit")?)?.unwrap_or(DEFAULT_HISTORY_LIMIT),
+        ),
+        ..HistoryQuery::default()
+    };
+    args.finish()?;
+    let page = history_store::query(store, &query)?;
+
+    if json {
+        print_json(&page)?;
+        return Ok(true);
+    }
+    let rows: Vec<Vec<String>> = page
+        .entries
+        .iter()
+        .map(|entry| {
+            vec![
+                format_time(&entry.recorded_at),
+                entry.action.clone(),
+                entry.target.clone(),
+                if entry.success { "成功" } else { "失败" }.to_string(),
+                entry.summary.clone(),
+            ]
+        })
+        .collect();
+    print_table(&["时间", "动作", "目标", "结果", "摘要"], &rows);
+    Ok(true)
+}
+
+fn config(store: &FileStore, mut args: Args, json: bool) -> Result<bool, Failure> {
+    match args.positional().as_deref() {
+        Some("validate") => validate_config(store, args, json),
+        Some("repair") => repair_config(store, args, json),
+        _ => Err(Failure::Usage(
+            "config 仅支持 validate 与 repair 子命令".to_string(),
+        )),
+    }
+}
+
+fn repair_config(store: &FileStore, args: Args, json: bool) -> Result<bool, Failure> {
+    args.finish()?;
+    let restored = config_store::repair_config(store)?;
+    if json {
+        print_json(&serde_json::json!({ "restored": restored }))?;
+    } else if restored {
+        println!("配置文件无法解析，已从备份恢复");
+    } else {
+        println!("配置文件可以正常解析，无需修复");
+    }
+    Ok(true)
+}
+
+fn validate_config(store: &FileStore, mut args: Args, json: bool) -> Result<bool, Failure> {
+    let path = match args.positional() {
+        Some(path) => PathBuf::from(path),
+        None => store.path(config_store::CONFIG_FILE)?,
+    };
+    args.finish()?;
+
+    let data = fs::read_to_string(&path)
+        .map_err(|error| format!("failed to read {}: {error}", path.display()))?;
+    let config: AppConfig = serde_json::from_str(&data)
+        .map_err(|error| format!("failed to parse {}: {error}", path.display()))?;
+    let issues = config_validation::validate_config(&config);
+
+    if json {
+        print_json(&issues)?;
+    } else if issues.is_empty() {
+        println!("{} 校验通过", path.display());
+    } else {
+        let rows: Vec<Vec<String>> = issues
+            .iter()
+            .map(|issue| vec![issue.field.clone(), issue.message.clone()])
+            .collect();
+        print_table(&["字段", "问题"], &rows);
+    }
+    Ok(issues.is_empty())
+}
+
+fn generate_report(store: &FileStore, mut args: Args, json: bool) -> Result<bool, Failure> {
+    let format = match args.value("--format")? {
+        Some(raw) => parse_enum::<ReportFormat>("--format", &raw)?,
+        None if json => ReportFormat::Json,
+        None => ReportFormat::Markdown,
+    };
+    let days =
+        parse_number("--days", args.value("--days")?)?.unwrap_or(report::DEFAULT_REPORT_DAYS);
+    let output = args.value("--output")?.map(PathBuf::from);
+    args.finish()?;
+
+    match output {
+        Some(path) => {
+            report::generate(store, format, &path, days)?;
+            eprintln!("报告已写入 {}", path.display());
+        }
+        None => print!("{}", report::render(&report::load(store, days)?, format)?),
+    }
+    Ok(true)
+}
+
+/// Set by the SIGINT/SIGTERM handler, acted on by `watch_interrupts`.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+const INTERRUPT_POLL: Duration = Duration::from_millis(50);
+/// Exit code of a process ended by SIGINT, as shells report it.
+const INTERRUPTED_EXIT_CODE: u8 = 130;
+
+#[cfg(unix)]
+extern "C" fn on_interrupt(signal: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+    // A second Ctrl-C ends the CLI right away.
+    // SAFETY: `signal` is async-signal-safe.
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+    }
+}
+
+/// Commands run in their own process group, so Ctrl-C does not reach them.
+/// On SIGINT/SIGTERM, cancel every active run instead; the shell runner then
+/// kills the command's process group and the CLI exits once it has returned.
+/// With nothing running, exit right away.
+fn watch_interrupts() {
+    #[cfg(unix)]
+    {
+        let handler = on_interrupt as extern "C" fn(libc::c_int);
+        // SAFETY: the handler only touches an atomic and calls `signal`.
+        unsafe {
+            libc::signal(libc::SIGINT, handler as libc::sighandler_t);
+            libc::signal(libc::SIGTERM, handler as libc::sighandler_t);
+        }
+        thread::spawn(|| loop {
+            if INTERRUPTED.load(Ordering::SeqCst) {
+                let runs = run_registry::active_runs();
+                if runs.is_empty() {
+                    eprintln!("已中断");
+                    std::process::exit(i32::from(INTERRUPTED_EXIT_CODE));
+                }
+                for run in runs {
+                    let _ = run_registry::cancel(&run.run_id);
+                }
+            }
+            thread::sleep(INTERRUPT_POLL);
+        });
+    }
+}
+
+fn run(mut args: Args) -> Result<bool, Failure> {
+    let data_dir = args.value("--data-dir")?;
+    let json = args.flag("--json");
+    if args.flag("--help") || args.flag("-h") {
+        println!("{USAGE}");
+        return Ok(true);
+    }
+    let command = args.positional();
+    let store = FileStore::new(match data_dir {
+        Some(dir) => StoragePaths::new(dir),
+        None => StoragePaths::from_default_dir()?,
+    });
+
+    match command.as_deref() {
+        Some("check") => check(&store, args, json),
+        Some("update") => update(&store, args, json),
+        Some("list") => list(&store, args, json),
+        Some("history") => history(&store, args, json),
+        Some("config") => config(&store, args, json),
+        Some("report") => generate_report(&store, args, json),
+        Some("help") => {
+            println!("{USAGE}");
+            Ok(true)
+        }
+        Some(other) => Err(Failure::Usage(format!("未知命令：{other}"))),
+        None => Err(Failure::Usage("缺少命令".to_string())),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args {
+        items: std::env::args().skip(1).collect(),
+    };
+    watch_interrupts();
+    match run(args) {
+        _ if INTERRUPTED.load(Ordering::SeqCst) => ExitCode::from(INTERRUPTED_EXIT_CODE),
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(Failure::Usage(message)) => {
+            eprintln!("{message}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+        Err(Failure::Error(message)) => {
+            eprintln!("错误：{message}");
+            ExitCode::FAILURE
+        }
+    }
+}
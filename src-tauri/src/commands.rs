@@ -1,278 +1,317 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 use tauri::AppHandle;
 
 use crate::model::{
-    AppConfig, CheckResult, CommandOutput, ExecutionHistoryEntry, LatestResultState, SoftwareItem,
-    UpdateResult,
+    ActiveRun, AppConfig, CatalogUpdate, CheckResult, CommandOutput, CommandRunFinished,
+    ConfigIssue, ExecutionHistoryEntry, FleetReport, HistoryPage, HistoryQuery, ItemTimeline,
+    LatestResultState, LoadedConfig, ReportFormat, RunLogPage, RunLogSummary, SchedulerStatus,
+    SoftwareItem,
 };
+use crate::services::operations::{
+    self, is_auto_app_item, is_auto_cli_item, is_auto_item, is_manual_item, is_runtime_item,
+    CheckRoundError,
+};
+use crate::services::config_store::ConfigSaveError;
+use crate::services::run_registry::{self, CancelToken, RunHandle};
+use crate::services::shell_runner::RunHooks;
+use crate::services::store::{FileStore, Store};
 use crate::services::{
-    check_all_guard, check_service, config_store, detect_service, history_events, history_store,
-    result_store, shell_runner,
+    catalog_sync, command_stream, config_schema, config_store, config_validation, detect_service,
+    history_store, report, result_store, run_log_store, scheduler, shell_runner, version_timeline,
 };
 
-fn default_timeout_seconds(config: &AppConfig) -> u64 {
-    config.command_timeout_seconds.max(1)
-}
-
-fn find_item<'a>(config: &'a AppConfig, item_id: &str) -> Option<&'a SoftwareItem> {
-    config.items.iter().find(|item| item.id == item_id)
-}
-
-fn is_manual_item(item: &SoftwareItem) -> bool {
-    matches!(item.id.as_str(), "brew" | "bun")
-}
-
-fn is_auto_cli_item(item: &SoftwareItem) -> bool {
-    item.enabled && !is_manual_item(item) && item.kind == "cli"
-}
-
-fn is_auto_app_item(item: &SoftwareItem) -> bool {
-    item.enabled && !is_manual_item(item) && (item.kind == "gui" || item.kind == "app")
-}
-
-fn is_runtime_item(item: &SoftwareItem) -> bool {
-    item.enabled && item.kind == "runtime"
-}
-
-fn check_item_impl(app: &AppHandle, item_id: &str) -> Result<CheckResult, String> {
-    let config = config_store::load_or_init_config(app)?;
-    let timeout_seconds = default_timeout_seconds(&config);
-    let item = find_item(&config, item_id).ok_or_else(|| format!("item not found: {item_id}"))?;
-    let mut execute = |command: &str| shell_runner::run_shell_command(command, timeout_seconds);
-    let result = check_service::check_single_item(item, &mut execute);
-    if let Err(error) = result_store::upsert_result(app, &result) {
-        eprintln!("failed to persist latest result: {error}");
-    }
-    history_events::append_entry_safe(app, history_events::check_item_entry(&result));
-    Ok(result)
-}
-
-fn check_items_impl(
-    app: &AppHandle,
-    action: &str,
-    skip_action: &str,
-    skip_message: &str,
-    filter: fn(&SoftwareItem) -> bool,
-) -> Result<Vec<CheckResult>, String> {
-    let _guard = match check_all_guard::CheckAllGuard::try_acquire() {
-        Some(guard) => guard,
-        None => {
-            history_events::append_entry_safe(
-                app,
-                history_events::check_all_entry(
-                    skip_action,
-                    false,
-                    skip_message.to_string(),
-                ),
-            );
-            return Err("check-all is already running".to_string());
-        }
-    };
-
-    let config = config_store::load_or_init_config(app)?;
-    let timeout_seconds = default_timeout_seconds(&config);
-    let results: Vec<CheckResult> = config
-        .items
-        .iter()
-        .filter(|item| filter(item))
-        .map(|item| {
-            let mut execute =
-                |command: &str| shell_runner::run_shell_command(command, timeout_seconds);
-            check_service::check_single_item(item, &mut execute)
-        })
-        .collect();
-
-    let error_count = results.iter().filter(|item| item.error.is_some()).count();
-    let update_count = results.iter().filter(|item| item.has_update).count();
-    if let Err(error) = result_store::upsert_results(app, &results) {
-        eprintln!("failed to persist latest results: {error}");
-    }
-    history_events::append_entry_safe(
-        app,
-        history_events::check_all_entry(
-            action,
-            error_count == 0,
-            format!(
-                "已检查 {} 项，发现 {} 项更新，{} 项错误",
-                results.len(),
-                update_count,
-                error_count
-            ),
-        ),
-    );
-    Ok(results)
-}
-
-fn check_all_impl(app: &AppHandle) -> Result<Vec<CheckResult>, String> {
-    check_items_impl(
-        app,
+fn check_all_impl(store: &dyn Store) -> Result<Vec<CheckResult>, String> {
+    operations::check_items(
+        store,
         "check-all",
         "check-all-skip",
         "已跳过：上一轮全量检查仍在运行",
-        |item| item.enabled && is_manual_item(item),
+        is_manual_item,
     )
+    .map_err(String::from)
 }
 
-fn check_auto_items_impl(app: &AppHandle) -> Result<Vec<CheckResult>, String> {
-    check_items_impl(
-        app,
+fn check_auto_items_impl(store: &dyn Store) -> Result<Vec<CheckResult>, String> {
+    operations::check_items(
+        store,
         "auto-check",
         "auto-check-skip",
         "已跳过：上一轮自动检查仍在运行",
-        |item| is_auto_cli_item(item) || is_auto_app_item(item),
+        is_auto_item,
     )
+    .map_err(String::from)
 }
 
-fn check_auto_cli_items_impl(app: &AppHandle) -> Result<Vec<CheckResult>, String> {
-    check_items_impl(
-        app,
+fn check_auto_cli_items_impl(store: &dyn Store) -> Result<Vec<CheckResult>, String> {
+    operations::check_items(
+        store,
         "auto-check-cli",
         "auto-check-cli-skip",
         "已跳过：上一轮 CLI 自动检查仍在运行",
         is_auto_cli_item,
     )
+    .map_err(String::from)
 }
 
-fn check_auto_app_items_impl(app: &AppHandle) -> Result<Vec<CheckResult>, String> {
-    check_items_impl(
-        app,
+fn check_auto_app_items_impl(store: &dyn Store) -> Result<Vec<CheckResult>, String> {
+    operations::check_items(
+        store,
         "auto-check-app",
         "auto-check-app-skip",
         "已跳过：上一轮 App 自动检查仍在运行",
         is_auto_app_item,
     )
+    .map_err(String::from)
 }
 
-fn check_runtime_items_impl(app: &AppHandle) -> Result<Vec<CheckResult>, String> {
-    check_items_impl(
-        app,
+fn check_runtime_items_impl(store: &dyn Store) -> Result<Vec<CheckResult>, String> {
+    operations::check_items(
+        store,
         "check-runtime",
         "check-runtime-skip",
         "已跳过：上一轮运行时检查仍在运行",
         is_runtime_item,
     )
+    .map_err(String::from)
 }
 
-fn run_item_update_impl(app: &AppHandle, item_id: &str) -> Result<UpdateResult, String> {
-    let config = config_store::load_or_init_config(app)?;
-    let timeout_seconds = default_timeout_seconds(&config);
-    let item = find_item(&config, item_id).ok_or_else(|| format!("item not found: {item_id}"))?;
-    let output = shell_runner::run_shell_command(&item.update_command, timeout_seconds)?;
-    history_events::append_entry_safe(
-        app,
-        history_events::command_entry(
-            "run-item-update",
-            &item.id,
-            &output,
-            format!("更新 {}（退出码 {}）", item.name, output.exit_code),
-        ),
-    );
-    Ok(UpdateResult {
-        item_id: item_id.to_string(),
-        updated_at: chrono::Utc::now().to_rfc3339(),
-        output,
-    })
+/// Items the background scheduler checks: every auto-policy item, plus the
+/// manual section when `auto_check_manual_enabled` is set.
+fn is_scheduled_item(config: &AppConfig, item: &SoftwareItem) -> bool {
+    is_auto_item(item) || (config.auto_check_manual_enabled && is_manual_item(item))
 }
 
-fn run_ad_hoc_command_impl(app: &AppHandle, command: &str) -> Result<CommandOutput, String> {
-    let config = config_store::load_or_init_config(app)?;
-    let timeout_seconds = default_timeout_seconds(&config);
-    let output = shell_runner::run_shell_command(command, timeout_seconds)?;
-    history_events::append_entry_safe(
-        app,
-        history_events::command_entry(
-            "run-shared-command",
-            "shared",
-            &output,
-            format!("共享命令执行完成（退出码 {}）", output.exit_code),
-        ),
+/// One scheduled round over the items the scheduler found due.
+fn run_scheduled_cycle(store: &dyn Store, item_ids: &[String]) -> scheduler::CycleOutcome {
+    let result = operations::check_items(
+        store,
+        "auto-check",
+        "auto-check-skip",
+        "已跳过：上一轮自动检查仍在运行",
+        |item| item_ids.contains(&item.id),
     );
-    Ok(output)
+    match result {
+        Ok(results) => scheduler::CycleOutcome::Ran {
+            checked_count: results.len(),
+            failure_reason: results.iter().find_map(|result| result.error.clone()),
+        },
+        Err(CheckRoundError::AlreadyRunning) => scheduler::CycleOutcome::Skipped,
+        Err(CheckRoundError::Failed(error)) => scheduler::CycleOutcome::Ran {
+            checked_count: 0,
+            failure_reason: Some(error),
+        },
+    }
+}
+
+pub fn scheduler_hooks() -> scheduler::SchedulerHooks {
+    scheduler::SchedulerHooks {
+        is_scheduled: is_scheduled_item,
+        run_cycle: run_scheduled_cycle,
+    }
 }
 
-fn get_active_node_version_impl() -> String {
+/// Runs `task` in the background and returns its run id right away; output
+/// lines and the final result are delivered through `command_stream` events.
+fn start_streaming_run<F>(app: AppHandle, action: &str, target: &str, task: F) -> String
+where
+    F: FnOnce(&AppHandle, &CancelToken) -> Result<CommandOutput, String> + Send + 'static,
+{
+    let run = RunHandle::register(action, target);
+    let run_id = run.run_id().to_string();
+    let target = target.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = task(&app, &run.token());
+        let (output, error) = match result {
+            Ok(output) => (Some(output), None),
+            Err(error) => (None, Some(error)),
+        };
+        command_stream::emit_finished(
+            &app,
+            CommandRunFinished {
+                run_id: run.run_id().to_string(),
+                target,
+                output,
+                error,
+            },
+        );
+    });
+    run_id
+}
+
+fn get_active_node_version_impl(app: &AppHandle) -> String {
     let command = "if command -v node >/dev/null 2>&1; then node --version | sed -E 's/^v//'; else echo ''; fi";
-    match shell_runner::run_shell_command(command, 20) {
+    let profile = FileStore::from_app(app)
+        .and_then(|store| config_store::load_or_init_config(&store))
+        .map(|config| config.execution_profile)
+        .unwrap_or_default();
+    match shell_runner::run_shell_command_with(&profile, command, 20, RunHooks::default()) {
         Ok(output) if output.exit_code == 0 => output.stdout.trim().to_string(),
         _ => String::new(),
     }
 }
 
 #[tauri::command]
-pub fn load_config(app: AppHandle) -> Result<AppConfig, String> {
-    config_store::load_or_init_config(&app)
+pub fn load_config(app: AppHandle) -> Result<LoadedConfig, String> {
+    let config = config_store::load_or_init_config(&FileStore::from_app(&app)?)?;
+    Ok(LoadedConfig {
+        config,
+        migration_report: config_store::take_migration_report(),
+    })
+}
+
+#[tauri::command]
+pub fn save_config(app: AppHandle, config: AppConfig) -> Result<(), ConfigSaveError> {
+    config_store::save_config(&FileStore::from_app(&app)?, &config)?;
+    scheduler::notify_config_changed();
+    Ok(())
+}
+
+fn update_config(
+    app: &AppHandle,
+    mutate: impl FnOnce(&mut AppConfig) -> Result<(), String>,
+) -> Result<(), String> {
+    let store = FileStore::from_app(app)?;
+    let mut config = config_store::load_or_init_config(&store)?;
+    mutate(&mut config)?;
+    config_store::save_config(&store, &config)?;
+    scheduler::notify_config_changed();
+    Ok(())
+}
+
+/// Catalog updates held back because the user modified the item.
+#[tauri::command]
+pub fn list_catalog_updates(app: AppHandle) -> Result<Vec<CatalogUpdate>, String> {
+    let config = config_store::load_or_init_config(&FileStore::from_app(&app)?)?;
+    Ok(catalog_sync::pending_updates(&config))
 }
 
 #[tauri::command]
-pub fn save_config(app: AppHandle, config: AppConfig) -> Result<(), String> {
-    config_store::save_config(&app, &config)
+pub fn accept_catalog_update(app: AppHandle, item_id: String) -> Result<(), String> {
+    update_config(&app, |config| catalog_sync::accept_update(config, &item_id))
+}
+
+#[tauri::command]
+pub fn dismiss_catalog_update(app: AppHandle, item_id: String) -> Result<(), String> {
+    update_config(&app, |config| catalog_sync::dismiss_update(config, &item_id))
+}
+
+#[tauri::command]
+pub fn restore_catalog_item(app: AppHandle, catalog_id: String) -> Result<(), String> {
+    update_config(&app, |config| catalog_sync::restore_item(config, &catalog_id))
+}
+
+#[tauri::command]
+pub fn get_config_schema() -> serde_json::Value {
+    config_schema::config_schema()
+}
+
+/// Field-level problems in `config`; empty when `save_config` would accept it.
+#[tauri::command]
+pub fn validate_config(config: AppConfig) -> Vec<ConfigIssue> {
+    config_validation::validate_config(&config)
 }
 
 #[tauri::command]
 pub fn load_latest_results(app: AppHandle) -> Result<LatestResultState, String> {
-    result_store::load_state(&app)
+    result_store::load_state(&FileStore::from_app(&app)?)
 }
 
 #[tauri::command]
 pub async fn check_item(app: AppHandle, item_id: String) -> Result<CheckResult, String> {
-    tauri::async_runtime::spawn_blocking(move || check_item_impl(&app, &item_id))
+    let store = FileStore::from_app(&app)?;
+    tauri::async_runtime::spawn_blocking(move || operations::check_item(&store, &item_id))
         .await
         .map_err(|error| format!("check_item task failed: {error}"))?
 }
 
 #[tauri::command]
 pub async fn check_all(app: AppHandle) -> Result<Vec<CheckResult>, String> {
-    tauri::async_runtime::spawn_blocking(move || check_all_impl(&app))
+    let store = FileStore::from_app(&app)?;
+    tauri::async_runtime::spawn_blocking(move || check_all_impl(&store))
         .await
         .map_err(|error| format!("check_all task failed: {error}"))?
 }
 
 #[tauri::command]
 pub async fn check_auto_items(app: AppHandle) -> Result<Vec<CheckResult>, String> {
-    tauri::async_runtime::spawn_blocking(move || check_auto_items_impl(&app))
+    let store = FileStore::from_app(&app)?;
+    tauri::async_runtime::spawn_blocking(move || check_auto_items_impl(&store))
         .await
         .map_err(|error| format!("check_auto_items task failed: {error}"))?
 }
 
 #[tauri::command]
 pub async fn check_auto_cli_items(app: AppHandle) -> Result<Vec<CheckResult>, String> {
-    tauri::async_runtime::spawn_blocking(move || check_auto_cli_items_impl(&app))
+    let store = FileStore::from_app(&app)?;
+    tauri::async_runtime::spawn_blocking(move || check_auto_cli_items_impl(&store))
         .await
         .map_err(|error| format!("check_auto_cli_items task failed: {error}"))?
 }
 
 #[tauri::command]
 pub async fn check_auto_app_items(app: AppHandle) -> Result<Vec<CheckResult>, String> {
-    tauri::async_runtime::spawn_blocking(move || check_auto_app_items_impl(&app))
+    let store = FileStore::from_app(&app)?;
+    tauri::async_runtime::spawn_blocking(move || check_auto_app_items_impl(&store))
         .await
         .map_err(|error| format!("check_auto_app_items task failed: {error}"))?
 }
 
 #[tauri::command]
 pub async fn check_runtime_items(app: AppHandle) -> Result<Vec<CheckResult>, String> {
-    tauri::async_runtime::spawn_blocking(move || check_runtime_items_impl(&app))
+    let store = FileStore::from_app(&app)?;
+    tauri::async_runtime::spawn_blocking(move || check_runtime_items_impl(&store))
         .await
         .map_err(|error| format!("check_runtime_items task failed: {error}"))?
 }
 
 #[tauri::command]
-pub async fn run_item_update(app: AppHandle, item_id: String) -> Result<UpdateResult, String> {
-    tauri::async_runtime::spawn_blocking(move || run_item_update_impl(&app, &item_id))
-        .await
-        .map_err(|error| format!("run_item_update task failed: {error}"))?
+pub fn run_item_update(app: AppHandle, item_id: String) -> Result<String, String> {
+    let store = FileStore::from_app(&app)?;
+    let config = config_store::load_or_init_config(&store)?;
+    let item = operations::find_item(&config, &item_id)
+        .cloned()
+        .ok_or_else(|| format!("item not found: {item_id}"))?;
+    Ok(start_streaming_run(
+        app,
+        "run-item-update",
+        &item_id,
+        move |app, token| {
+            let sink = command_stream::line_sink(app, token.run_id());
+            operations::run_item_update(&store, &config, &item, token, Some(sink))
+        },
+    ))
 }
 
 #[tauri::command]
-pub async fn run_ad_hoc_command(app: AppHandle, command: String) -> Result<CommandOutput, String> {
-    tauri::async_runtime::spawn_blocking(move || run_ad_hoc_command_impl(&app, &command))
-        .await
-        .map_err(|error| format!("run_ad_hoc_command task failed: {error}"))?
+pub fn run_ad_hoc_command(app: AppHandle, command: String) -> Result<String, String> {
+    let store = FileStore::from_app(&app)?;
+    let config = config_store::load_or_init_config(&store)?;
+    Ok(start_streaming_run(
+        app,
+        "run-shared-command",
+        "shared",
+        move |app, token| {
+            let sink = command_stream::line_sink(app, token.run_id());
+            operations::run_ad_hoc_command(&store, &config, &command, token, Some(sink))
+        },
+    ))
+}
+
+#[tauri::command]
+pub fn cancel_run(run_id: String) -> Result<(), String> {
+    run_registry::cancel(&run_id)
 }
 
 #[tauri::command]
-pub async fn get_active_node_version() -> Result<String, String> {
-    tauri::async_runtime::spawn_blocking(get_active_node_version_impl)
+pub fn list_active_runs() -> Vec<ActiveRun> {
+    run_registry::active_runs()
+}
+
+#[tauri::command]
+pub async fn get_active_node_version(app: AppHandle) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || get_active_node_version_impl(&app))
         .await
         .map_err(|error| format!("get_active_node_version task failed: {error}"))
 }
@@ -283,17 +322,86 @@ pub fn load_history(
     limit: Option<u32>,
 ) -> Result<Vec<ExecutionHistoryEntry>, String> {
     let requested = limit.unwrap_or(50).clamp(1, 200) as usize;
-    history_store::load_entries(&app, requested)
+    history_store::load_entries(&FileStore::from_app(&app)?, requested)
+}
+
+/// Every observed version change of an item, with time-behind metrics.
+#[tauri::command]
+pub fn load_item_timeline(app: AppHandle, item_id: String) -> Result<ItemTimeline, String> {
+    version_timeline::load_item_timeline(&FileStore::from_app(&app)?, &item_id)
+}
+
+/// Writes a fleet health report over the last `days` days (default 7) to
+/// `path`, which must be absolute.
+#[tauri::command]
+pub fn generate_report(
+    app: AppHandle,
+    format: ReportFormat,
+    path: String,
+    days: Option<u32>,
+) -> Result<FleetReport, String> {
+    let path = PathBuf::from(path);
+    if !path.is_absolute() {
+        return Err(format!("report path must be absolute: {}", path.display()));
+    }
+    report::generate(
+        &FileStore::from_app(&app)?,
+        format,
+        &path,
+        days.unwrap_or(report::DEFAULT_REPORT_DAYS),
+    )
+}
+
+/// Filtered, paginated history; pass `next_cursor` back to get the next page.
+#[tauri::command]
+pub fn query_history(app: AppHandle, query: HistoryQuery) -> Result<HistoryPage, String> {
+    history_store::query(&FileStore::from_app(&app)?, &query)
+}
+
+#[tauri::command]
+pub fn get_scheduler_status() -> SchedulerStatus {
+    scheduler::current_status()
+}
+
+#[tauri::command]
+pub fn list_run_logs(app: AppHandle, limit: Option<u32>) -> Result<Vec<RunLogSummary>, String> {
+    let requested = limit.unwrap_or(100).clamp(1, 1000) as usize;
+    run_log_store::list(&FileStore::from_app(&app)?, requested)
+}
+
+#[tauri::command]
+pub fn read_run_log(
+    app: AppHandle,
+    log_id: String,
+    offset: Option<u32>,
+    limit: Option<u32>,
+) -> Result<RunLogPage, String> {
+    run_log_store::read_page(
+        &FileStore::from_app(&app)?,
+        &log_id,
+        offset.unwrap_or(0) as usize,
+        limit.map(|value| value as usize),
+    )
+}
+
+#[tauri::command]
+pub fn delete_run_log(app: AppHandle, log_id: String) -> Result<(), String> {
+    run_log_store::delete(&FileStore::from_app(&app)?, &log_id)
 }
 
-fn detect_installed_items_impl(app: &AppHandle) -> Result<HashMap<String, bool>, String> {
-    let config = config_store::load_or_init_config(app)?;
-    Ok(detect_service::detect_all(&config.items))
+fn detect_installed_items_impl(store: &dyn Store) -> Result<HashMap<String, bool>, String> {
+    let config = config_store::load_or_init_config(store)?;
+    Ok(detect_service::detect_all(
+        &config.items,
+        &config.execution_profile,
+        operations::max_parallel_checks(&config),
+    ))
 }
 
 #[tauri::command]
 pub async fn detect_installed_items(app: AppHandle) -> Result<HashMap<String, bool>, String> {
-    tauri::async_runtime::spawn_blocking(move || detect_installed_items_impl(&app))
+    let store = FileStore::from_app(&app)?;
+    tauri::async_runtime::spawn_blocking(move || detect_installed_items_impl(&store))
         .await
         .map_err(|error| format!("detect_installed_items task failed: {error}"))?
 }
@@ -0,0 +1,3 @@
+pub mod model;
+pub mod services;
+pub mod software_catalog;
@@ -1,12 +1,9 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod commands;
-mod model;
-mod software_catalog;
-mod services;
 
 use std::process::Command as ProcessCommand;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 use chrono::{DateTime, Local};
 use tauri::image::Image;
@@ -14,8 +11,13 @@ use tauri::menu::{Menu, MenuItem, PredefinedMenuItem, Submenu};
 use tauri::tray::{MouseButtonState, TrayIconBuilder, TrayIconEvent};
 use tauri::{AppHandle, Emitter, Manager};
 
-use model::{AppConfig, LatestResultState};
-use services::{config_store, history_store, result_store};
+use patchpilot_lib::{model, services};
+
+use model::{AppConfig, LatestResultState, ThemeMode};
+use services::config_watcher::ConfigWatchHooks;
+use services::storage_paths::StoragePaths;
+use services::store::FileStore;
+use services::{config_store, config_watcher, result_store, run_log_store, scheduler};
 
 const TRAY_ID: &str = "patchpilot-tray";
 
@@ -101,7 +103,13 @@ fn build_interval_submenu(
 ) -> Result<Submenu<tauri::Wry>, String> {
     let submenu = Submenu::with_id(app, "menu.interval", "检查频率", true)
         .map_err(|error| error.to_string())?;
-    for (minutes, label) in [(240_u64, "4h"), (480_u64, "8h"), (720_u64, "12h")] {
+    for (minutes, label) in [
+        (60_u64, "1h"),
+        (240_u64, "4h"),
+        (480_u64, "8h"),
+        (720_u64, "12h"),
+        (1440_u64, "24h"),
+    ] {
         let title = if current_interval == minutes {
             format!("✓ {label}")
         } else {
@@ -115,30 +123,36 @@ fn build_interval_submenu(
 
 fn build_theme_submenu(
     app: &AppHandle,
-    theme_mode: &str,
+    theme_mode: ThemeMode,
     enabled: bool,
 ) -> Result<Submenu<tauri::Wry>, String> {
     let submenu =
         Submenu::with_id(app, "menu.theme", "主题", true).map_err(|error| error.to_string())?;
     for (mode, label) in [
-        ("system", "跟随系统"),
-        ("light", "浅色"),
-        ("dark", "深色"),
+        (ThemeMode::System, "跟随系统"),
+        (ThemeMode::Light, "浅色"),
+        (ThemeMode::Dark, "深色"),
     ] {
         let title = if theme_mode == mode {
             format!("✓ {label}")
         } else {
             label.to_string()
         };
-        let item = menu_item(app, &format!("menu.theme.{mode}"), title, enabled)?;
+        let item = menu_item(
+            app,
+            &format!("menu.theme.{}", mode.as_str()),
+            title,
+            enabled,
+        )?;
         submenu.append(&item).map_err(|error| error.to_string())?;
     }
     Ok(submenu)
 }
 
 fn build_tray_menu(app: &AppHandle) -> Result<Menu<tauri::Wry>, String> {
-    let config = config_store::load_or_init_config(app)?;
-    let latest = result_store::load_state(app).unwrap_or_default();
+    let store = FileStore::from_app(app)?;
+    let config = config_store::load_or_init_config(&store)?;
+    let latest = result_store::load_state(&store).unwrap_or_default();
     let state = state_snapshot(app);
     let error_count = collect_error_count(&config, &latest);
     let checked_at = latest_checked_time(&config, &latest)
@@ -170,7 +184,7 @@ fn build_tray_menu(app: &AppHandle) -> Result<Menu<tauri::Wry>, String> {
     menu.append(&interval).map_err(|error| error.to_string())?;
 
     append_separator(&menu, app)?;
-    let theme = build_theme_submenu(app, &config.theme_mode, true)?;
+    let theme = build_theme_submenu(app, config.theme_mode, true)?;
     menu.append(&theme).map_err(|error| error.to_string())?;
     let open_config = menu_item(app, "menu.open.config", "打开配置文件", true)?;
     menu.append(&open_config).map_err(|error| error.to_string())?;
@@ -192,6 +206,8 @@ fn build_fallback_menu(app: &AppHandle, status: &str) -> Result<Menu<tauri::Wry>
     menu.append(&status_item).map_err(|error| error.to_string())?;
     let open = menu_item(app, "menu.open_window", "打开主窗口", true)?;
     menu.append(&open).map_err(|error| error.to_string())?;
+    let open_config = menu_item(app, "menu.open.config", "打开配置文件", true)?;
+    menu.append(&open_config).map_err(|error| error.to_string())?;
     append_separator(&menu, app)?;
     let quit = menu_item(app, "menu.quit", "退出", true)?;
     menu.append(&quit).map_err(|error| error.to_string())?;
@@ -220,6 +236,19 @@ fn emit_config_event(app: &AppHandle) {
     let _ = app.emit("patchpilot://config-updated", ());
 }
 
+fn on_config_file_reloaded(app: &AppHandle) {
+    set_notice(app, "配置文件已重新加载");
+    scheduler::notify_config_changed();
+    refresh_tray_menu(app);
+    emit_config_event(app);
+}
+
+fn on_config_file_error(app: &AppHandle, error: &str) {
+    set_notice(app, format!("配置文件有误：{error}"));
+    refresh_tray_menu(app);
+    let _ = app.emit("patchpilot://config-error", error.to_string());
+}
+
 fn emit_theme_mode_event(app: &AppHandle, mode: &str) {
     let _ = app.emit("patchpilot://theme-mode-updated", mode.to_string());
     if let Some(window) = app.get_webview_window("main") {
@@ -243,9 +272,11 @@ fn with_config_mutation(
     app: &AppHandle,
     mutate: impl FnOnce(&mut AppConfig),
 ) -> Result<AppConfig, String> {
-    let mut config = config_store::load_or_init_config(app)?;
+    let store = FileStore::from_app(app)?;
+    let mut config = config_store::load_or_init_config(&store)?;
     mutate(&mut config);
-    config_store::save_config(app, &config)?;
+    config_store::save_config(&store, &config)?;
+    scheduler::notify_config_changed();
     Ok(config)
 }
 
@@ -283,7 +314,8 @@ fn handle_menu_event(app: &AppHandle, id: &str) {
             }
             refresh_tray_menu(app);
         }
-        "menu.interval.240" | "menu.interval.480" | "menu.interval.720" => {
+        "menu.interval.60" | "menu.interval.240" | "menu.interval.480" | "menu.interval.720"
+        | "menu.interval.1440" => {
             let next = id
                 .strip_prefix("menu.interval.")
                 .and_then(|value| value.parse::<u64>().ok())
@@ -300,21 +332,26 @@ fn handle_menu_event(app: &AppHandle, id: &str) {
             refresh_tray_menu(app);
         }
         "menu.theme.system" | "menu.theme.light" | "menu.theme.dark" => {
-            let mode = id.strip_prefix("menu.theme.").unwrap_or("system");
+            let mode = match id {
+                "menu.theme.light" => ThemeMode::Light,
+                "menu.theme.dark" => ThemeMode::Dark,
+                _ => ThemeMode::System,
+            };
             match with_config_mutation(app, |config| {
-                config.theme_mode = mode.to_string();
+                config.theme_mode = mode;
             }) {
                 Ok(_) => {
-                    set_notice(app, format!("主题已切换为 {mode}"));
+                    set_notice(app, format!("主题已切换为 {}", mode.as_str()));
                     emit_config_event(app);
-                    emit_theme_mode_event(app, mode);
+                    emit_theme_mode_event(app, mode.as_str());
                 }
                 Err(error) => set_notice(app, format!("切换主题失败：{error}")),
             }
             refresh_tray_menu(app);
         }
         "menu.open.config" => {
-            match config_store::resolve_config_path(app)
+            match StoragePaths::from_app(app)
+                .and_then(|paths| paths.config_path())
                 .and_then(|path| open_with_system(&path.to_string_lossy()))
             {
                 Ok(_) => set_notice(app, "已打开配置文件"),
@@ -323,15 +360,12 @@ fn handle_menu_event(app: &AppHandle, id: &str) {
             refresh_tray_menu(app);
         }
         "menu.open.logs" => {
-            let logs_dir = history_store::history_path(app)
-                .ok()
-                .and_then(|path| path.parent().map(|parent| parent.to_path_buf()));
-            match logs_dir {
-                Some(path) => match open_with_system(&path.to_string_lossy()) {
-                    Ok(_) => set_notice(app, "已打开日志目录"),
-                    Err(error) => set_notice(app, format!("打开日志目录失败：{error}")),
-                },
-                None => set_notice(app, "日志目录不存在"),
+            match FileStore::from_app(app)
+                .and_then(|store| run_log_store::log_dir(&store))
+                .and_then(|path| open_with_system(&path.to_string_lossy()))
+            {
+                Ok(_) => set_notice(app, "已打开日志目录"),
+                Err(error) => set_notice(app, format!("打开日志目录失败：{error}")),
             }
             refresh_tray_menu(app);
         }
@@ -355,6 +389,13 @@ fn main() {
 
             app.manage(Mutex::new(TrayRuntimeState::default()));
             let app_handle = app.handle().clone();
+            match FileStore::from_app(&app_handle)
+                .and_then(|store| config_store::repair_config(&store))
+            {
+                Ok(true) => set_notice(&app_handle, "配置文件无法解析，已从备份恢复"),
+                Ok(false) => {}
+                Err(error) => eprintln!("failed to repair config: {error}"),
+            }
             let tray_icon =
                 Image::from_bytes(include_bytes!("../icons/tray-template.png")).map_err(|error| {
                     std::io::Error::new(
@@ -402,6 +443,18 @@ fn main() {
                 let _ = window.hide();
             }
 
+            let watch_hooks = ConfigWatchHooks {
+                on_reload: on_config_file_reloaded,
+                on_error: on_config_file_error,
+            };
+            let store = FileStore::from_app(&app_handle).map_err(std::io::Error::other)?;
+            if let Err(error) =
+                config_watcher::start(app_handle.clone(), store.clone(), watch_hooks)
+            {
+                eprintln!("{error}");
+            }
+            scheduler::start(app_handle, Arc::new(store), commands::scheduler_hooks());
+
             Ok(())
         })
         .on_window_event(|window, event| {
@@ -415,7 +468,14 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             commands::load_config,
             commands::save_config,
+            commands::validate_config,
+            commands::get_config_schema,
+            commands::list_catalog_updates,
+            commands::accept_catalog_update,
+            commands::dismiss_catalog_update,
+            commands::restore_catalog_item,
             commands::load_latest_results,
+            commands::load_item_timeline,
             commands::check_item,
             commands::check_all,
             commands::check_auto_items,
@@ -424,8 +484,16 @@ fn main() {
             commands::check_runtime_items,
             commands::run_item_update,
             commands::run_ad_hoc_command,
+            commands::cancel_run,
+            commands::list_active_runs,
             commands::get_active_node_version,
+            commands::get_scheduler_status,
             commands::load_history,
+            commands::query_history,
+            commands::generate_report,
+            commands::list_run_logs,
+            commands::read_run_log,
+            commands::delete_run_log,
             commands::detect_installed_items
         ])
         .run(tauri::generate_context!())
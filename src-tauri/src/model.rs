@@ -1,13 +1,49 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+/// `AppConfig.schema_version` written by this build; see `config_migrations`.
+pub const CONFIG_SCHEMA_VERSION: u32 = 6;
+
 fn default_command_timeout_seconds() -> u64 {
     120
 }
 
-fn default_theme_mode() -> String {
-    "system".to_string()
+fn default_max_parallel_checks() -> usize {
+    4
+}
+
+fn default_host_request_interval_ms() -> u64 {
+    500
+}
+
+fn default_max_output_bytes() -> usize {
+    64 * 1024
+}
+
+fn default_keep_full_output_logs() -> bool {
+    true
+}
+
+fn default_run_log_retention_days() -> u64 {
+    14
+}
+
+fn default_run_log_max_total_mb() -> u64 {
+    200
+}
+
+fn default_history_retention_days() -> u64 {
+    180
+}
+
+fn default_shell() -> String {
+    "zsh".to_string()
+}
+
+fn default_login_shell() -> bool {
+    true
 }
 
 fn default_auto_check_enabled() -> bool {
@@ -18,32 +54,322 @@ fn default_auto_check_manual_enabled() -> bool {
     true
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionComparator {
+    #[default]
+    Auto,
+    Semver,
+    Dotted,
+    Calendar,
+    Exact,
+}
+
+/// Unknown values deserialize to `Unknown` so `validate_config` can report
+/// them instead of failing the whole parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SoftwareKind {
+    Cli,
+    Gui,
+    App,
+    Runtime,
+    #[serde(other)]
+    #[schemars(skip)]
+    Unknown,
+}
+
+/// Unknown values fall back to following the system theme.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeMode {
+    Light,
+    Dark,
+    #[default]
+    #[serde(other)]
+    System,
+}
+
+impl ThemeMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Light => "light",
+            Self::Dark => "dark",
+            Self::System => "system",
+        }
+    }
+}
+
+/// Which batch checks include an item.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckPolicy {
+    /// Checked from the manual section; scheduled only when
+    /// `auto_check_manual_enabled` is set.
+    Manual,
+    /// Checked by its group's batch check and the scheduler.
+    #[default]
+    Auto,
+    /// Skipped by batch and scheduled checks; single checks still work.
+    Disabled,
+}
+
+impl CheckPolicy {
+    /// The policy of an item that sets none: runtimes were never checked
+    /// automatically, so they stay manual.
+    pub fn default_for(group: ItemGroup) -> Self {
+        match group {
+            ItemGroup::Runtime => Self::Manual,
+            ItemGroup::Cli | ItemGroup::Gui => Self::Auto,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Auto => "auto",
+            Self::Disabled => "disabled",
+        }
+    }
+}
+
+/// The UI section an item belongs to; also the key of `group_schedules`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemGroup {
+    Cli,
+    Gui,
+    Runtime,
+}
+
+impl ItemGroup {
+    /// The group legacy configs implied through the item kind.
+    pub fn from_kind(kind: SoftwareKind) -> Self {
+        match kind {
+            SoftwareKind::Gui | SoftwareKind::App => Self::Gui,
+            SoftwareKind::Runtime => Self::Runtime,
+            SoftwareKind::Cli | SoftwareKind::Unknown => Self::Cli,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Cli => "cli",
+            Self::Gui => "gui",
+            Self::Runtime => "runtime",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionComparison {
+    Newer,
+    Same,
+    Older,
+    Incomparable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VersionSource {
+    GithubRelease { repo: String },
+    NpmPackage { package: String },
+    HomebrewCask { token: String },
+    HomebrewFormula { name: String },
+    Pypi { package: String },
+    CratesIo { name: String },
+    JsonUrl { url: String, pointer: String },
+    HtmlRegex { url: String, pattern: String },
+}
+
+fn default_plist_key() -> String {
+    "CFBundleShortVersionString".to_string()
+}
+
+fn default_rust_toolchain() -> String {
+    "stable".to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InstalledVersionProbe {
+    PlistKey {
+        path: String,
+        #[serde(default = "default_plist_key")]
+        key: String,
+    },
+    BinaryVersion {
+        binary: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        pattern: Option<String>,
+    },
+    NpmGlobalPackage {
+        package: String,
+        #[serde(default)]
+        prefix: Option<String>,
+    },
+    RustToolchain {
+        #[serde(default = "default_rust_toolchain")]
+        toolchain: String,
+    },
+}
+
+/// How shell commands are launched: `<shell> <shell_args> [-l] [-i] -c <command>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ExecutionProfile {
+    #[serde(default = "default_shell")]
+    pub shell: String,
+    #[serde(default)]
+    pub shell_args: Vec<String>,
+    #[serde(default = "default_login_shell")]
+    pub login: bool,
+    #[serde(default)]
+    pub interactive: bool,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub working_directory: Option<String>,
+}
+
+/// Per-item changes on top of `AppConfig.execution_profile`; unset fields
+/// inherit, `env` entries are merged key by key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ExecutionProfileOverride {
+    #[serde(default)]
+    pub shell: Option<String>,
+    #[serde(default)]
+    pub shell_args: Option<Vec<String>>,
+    #[serde(default)]
+    pub login: Option<bool>,
+    #[serde(default)]
+    pub interactive: Option<bool>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub working_directory: Option<String>,
+}
+
+/// When an item is due for an automatic check: a fixed interval, or a
+/// five-field cron expression in local time (the cron wins if both are set).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CheckSchedule {
+    #[serde(default)]
+    pub interval_minutes: Option<u64>,
+    #[serde(default)]
+    pub cron: Option<String>,
+}
+
+/// Daily local-time window (`HH:MM`, may wrap past midnight) in which no
+/// automatic checks start.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+/// Where a catalog-provided item came from. `fingerprint` covers the
+/// catalog-managed fields as last taken from the catalog; an item whose
+/// current fingerprint differs was modified by the user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ItemOrigin {
+    pub catalog_id: String,
+    pub revision: u32,
+    pub fingerprint: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SoftwareItem {
     pub id: String,
     pub name: String,
-    pub kind: String,
+    pub kind: SoftwareKind,
     pub enabled: bool,
     pub description: String,
     pub current_version_command: Option<String>,
+    #[serde(default)]
+    pub installed_probe: Option<InstalledVersionProbe>,
     pub latest_version_command: Option<String>,
+    #[serde(default)]
+    pub version_source: Option<VersionSource>,
     pub update_check_command: Option<String>,
     pub update_check_regex: Option<String>,
     pub update_command: String,
+    #[serde(default)]
+    pub version_comparator: VersionComparator,
+    #[serde(default)]
+    pub execution: Option<ExecutionProfileOverride>,
+    #[serde(default)]
+    pub schedule: Option<CheckSchedule>,
+    /// Filled in by `config_migrations` for configs written before it existed.
+    #[serde(default)]
+    pub check_policy: Option<CheckPolicy>,
+    #[serde(default)]
+    pub group: Option<ItemGroup>,
+    /// Set for items that came from the built-in catalog.
+    #[serde(default)]
+    pub origin: Option<ItemOrigin>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl SoftwareItem {
+    pub fn policy(&self) -> CheckPolicy {
+        self.check_policy
+            .unwrap_or_else(|| CheckPolicy::default_for(self.group()))
+    }
+
+    pub fn group(&self) -> ItemGroup {
+        self.group.unwrap_or_else(|| ItemGroup::from_kind(self.kind))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AppConfig {
+    /// Points editors at the JSON Schema written next to the config file.
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// Last migration applied to this file; missing means a pre-versioning config.
+    #[serde(default)]
+    pub schema_version: u32,
     pub check_interval_minutes: u64,
+    /// Schedules keyed by item group (`cli`, `gui`, `runtime`).
+    #[serde(default)]
+    pub group_schedules: BTreeMap<String, CheckSchedule>,
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
     #[serde(default = "default_command_timeout_seconds")]
     pub command_timeout_seconds: u64,
-    #[serde(default = "default_theme_mode")]
-    pub theme_mode: String,
+    #[serde(default = "default_max_parallel_checks")]
+    pub max_parallel_checks: usize,
+    /// Minimum spacing between `version_source` requests to one host during
+    /// a batch check; 0 disables it.
+    #[serde(default = "default_host_request_interval_ms")]
+    pub host_request_interval_ms: u64,
+    #[serde(default)]
+    pub execution_profile: ExecutionProfile,
+    #[serde(default = "default_max_output_bytes")]
+    pub max_output_bytes: usize,
+    #[serde(default = "default_keep_full_output_logs")]
+    pub keep_full_output_logs: bool,
+    #[serde(default = "default_run_log_retention_days")]
+    pub run_log_retention_days: u64,
+    #[serde(default = "default_run_log_max_total_mb")]
+    pub run_log_max_total_mb: u64,
+    #[serde(default = "default_history_retention_days")]
+    pub history_retention_days: u64,
+    #[serde(default)]
+    pub theme_mode: ThemeMode,
     #[serde(default = "default_auto_check_enabled")]
     pub auto_check_enabled: bool,
     #[serde(default = "default_auto_check_manual_enabled")]
     pub auto_check_manual_enabled: bool,
     pub shared_update_commands: Vec<String>,
+    /// Catalog revision the items were last synced against.
+    #[serde(default)]
+    pub catalog_revision: u32,
+    /// Catalog ids the user deleted; catalog syncs do not add them back.
+    #[serde(default)]
+    pub removed_catalog_items: BTreeSet<String>,
     pub items: Vec<SoftwareItem>,
 }
 
@@ -55,6 +381,12 @@ pub struct CommandOutput {
     pub stderr: String,
     pub duration_ms: u128,
     pub timed_out: bool,
+    #[serde(default)]
+    pub cancelled: bool,
+    #[serde(default)]
+    pub truncated: bool,
+    #[serde(default)]
+    pub full_output_log: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,6 +396,8 @@ pub struct CheckResult {
     pub has_update: bool,
     pub current_version: Option<String>,
     pub latest_version: Option<String>,
+    #[serde(default)]
+    pub version_comparison: Option<VersionComparison>,
     pub details: String,
     pub error: Option<String>,
 }
@@ -75,6 +409,8 @@ pub struct LatestResultSnapshot {
     pub has_update: bool,
     pub current_version: Option<String>,
     pub latest_version: Option<String>,
+    #[serde(default)]
+    pub version_comparison: Option<VersionComparison>,
     pub error: Option<String>,
 }
 
@@ -85,11 +421,176 @@ pub struct LatestResultState {
     pub items: HashMap<String, LatestResultSnapshot>,
 }
 
+/// One (current, latest) version pair of an item, from the first check that
+/// saw it until the last.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionTransition {
+    pub observed_at: String,
+    pub last_seen_at: String,
+    pub current_version: Option<String>,
+    pub latest_version: Option<String>,
+    pub has_update: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VersionTimelineState {
+    #[serde(default)]
+    pub items: HashMap<String, Vec<VersionTransition>>,
+}
+
+/// How long it took for the installed version to reach a newly seen latest
+/// version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateLag {
+    pub version: String,
+    pub first_seen_at: String,
+    pub installed_at: String,
+    pub hours: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TimelineMetrics {
+    /// Days since the item fell behind, while it still is.
+    pub days_behind_latest: Option<f64>,
+    /// Days spent behind over the whole timeline.
+    pub total_days_behind: f64,
+    pub update_lags: Vec<UpdateLag>,
+    pub average_hours_to_update: Option<f64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct UpdateResult {
+pub struct ItemTimeline {
     pub item_id: String,
-    pub updated_at: String,
-    pub output: CommandOutput,
+    pub transitions: Vec<VersionTransition>,
+    pub metrics: TimelineMetrics,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutdatedItem {
+    pub item_id: String,
+    pub name: String,
+    pub current_version: Option<String>,
+    pub latest_version: Option<String>,
+    pub checked_at: String,
+    pub days_behind: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemErrorSummary {
+    pub item_id: String,
+    pub count: usize,
+    pub last_error: String,
+    pub last_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportRun {
+    pub action: String,
+    pub target: String,
+    pub recorded_at: String,
+    pub success: bool,
+    pub duration_ms: Option<u128>,
+    pub summary: String,
+}
+
+/// Fleet health over `[since, until)`, built from history, the latest
+/// results and the version timelines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetReport {
+    pub generated_at: String,
+    pub since: String,
+    pub until: String,
+    pub outdated: Vec<OutdatedItem>,
+    pub errors: Vec<ItemErrorSummary>,
+    pub updates: Vec<ReportRun>,
+    /// Mean of the update lags whose update landed in the period.
+    pub average_hours_to_update: Option<f64>,
+    pub slowest_runs: Vec<ReportRun>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandOutputLine {
+    pub run_id: String,
+    pub stream: String,
+    pub line: String,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandRunFinished {
+    pub run_id: String,
+    pub target: String,
+    pub output: Option<CommandOutput>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveRun {
+    pub run_id: String,
+    pub action: String,
+    pub target: String,
+    pub started_at: String,
+}
+
+/// One problem found by `validate_config`; `field` is a path such as
+/// `items[3].update_check_regex`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigIssue {
+    pub field: String,
+    pub message: String,
+}
+
+/// One field that a migration step changed. Added or removed items have no
+/// `field` and carry the whole item in `after` / `before`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationChange {
+    /// `None` for top-level settings.
+    pub item_id: Option<String>,
+    pub field: Option<String>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationStepReport {
+    pub id: String,
+    pub description: String,
+    pub changes: Vec<MigrationChange>,
+}
+
+/// What loading an older config rewrote, and where the original was kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub backup_path: Option<String>,
+    pub steps: Vec<MigrationStepReport>,
+}
+
+/// A catalog change held back because the user modified the item; `changes`
+/// go from the user's value (`before`) to the catalog's (`after`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogUpdate {
+    pub item_id: String,
+    pub catalog_id: String,
+    pub from_revision: u32,
+    pub to_revision: u32,
+    pub changes: Vec<MigrationChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadedConfig {
+    pub config: AppConfig,
+    /// Set once, on the first load after a migration ran.
+    pub migration_report: Option<MigrationReport>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -106,19 +607,107 @@ pub struct ExecutionHistoryEntry {
     pub success: bool,
     pub exit_code: Option<i32>,
     pub timed_out: bool,
+    #[serde(default)]
+    pub cancelled: bool,
+    #[serde(default)]
+    pub truncated: bool,
+    #[serde(default)]
+    pub full_output_log: Option<String>,
     pub duration_ms: Option<u128>,
     pub summary: String,
 }
 
+/// Filters for `query_history`; every field is optional and they combine
+/// with AND. Results are newest first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryQuery {
+    pub target: Option<String>,
+    pub action: Option<String>,
+    pub success: Option<bool>,
+    /// RFC 3339, inclusive.
+    pub since: Option<String>,
+    /// RFC 3339, exclusive.
+    pub until: Option<String>,
+    /// Case-insensitive match against summary, stdout and stderr.
+    pub text: Option<String>,
+    /// `next_cursor` of the previous page.
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub entries: Vec<ExecutionHistoryEntry>,
+    /// Present when more entries match.
+    pub next_cursor: Option<String>,
+}
+
+/// State of the background auto-check scheduler; also persisted so the next
+/// due time survives restarts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchedulerStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub last_run_at: Option<String>,
+    pub next_run_at: Option<String>,
+    pub last_duration_ms: Option<u128>,
+    pub last_checked_count: usize,
+    pub last_failure_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunLogSummary {
+    pub id: String,
+    pub path: Option<String>,
+    pub size_bytes: u64,
+    pub modified_at: String,
+    pub entry: Option<ExecutionHistoryEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunLogPage {
+    pub id: String,
+    pub offset: usize,
+    pub next_offset: Option<usize>,
+    pub lines: Vec<String>,
+}
+
+impl Default for ExecutionProfile {
+    fn default() -> Self {
+        Self {
+            shell: default_shell(),
+            shell_args: Vec::new(),
+            login: default_login_shell(),
+            interactive: false,
+            env: BTreeMap::new(),
+            working_directory: None,
+        }
+    }
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
+            schema: None,
+            schema_version: CONFIG_SCHEMA_VERSION,
             check_interval_minutes: 480,
+            group_schedules: BTreeMap::new(),
+            quiet_hours: None,
             command_timeout_seconds: default_command_timeout_seconds(),
-            theme_mode: default_theme_mode(),
+            max_parallel_checks: default_max_parallel_checks(),
+            host_request_interval_ms: default_host_request_interval_ms(),
+            execution_profile: ExecutionProfile::default(),
+            max_output_bytes: default_max_output_bytes(),
+            keep_full_output_logs: default_keep_full_output_logs(),
+            run_log_retention_days: default_run_log_retention_days(),
+            run_log_max_total_mb: default_run_log_max_total_mb(),
+            history_retention_days: default_history_retention_days(),
+            theme_mode: ThemeMode::default(),
             auto_check_enabled: default_auto_check_enabled(),
             auto_check_manual_enabled: default_auto_check_manual_enabled(),
             shared_update_commands: crate::software_catalog::default_shared_update_commands(),
+            catalog_revision: crate::software_catalog::CATALOG_REVISION,
+            removed_catalog_items: BTreeSet::new(),
             items: crate::software_catalog::default_software_items(),
         }
     }
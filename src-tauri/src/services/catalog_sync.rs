@@ -0,0 +1,230 @@
+use crate::model::{AppConfig, CatalogUpdate, MigrationStepReport, SoftwareItem};
+use crate::software_catalog::{self, CATALOG_REVISION};
+
+use super::config_migrations;
+
+fn find_catalog_item(catalog_id: &str) -> Option<SoftwareItem> {
+    software_catalog::default_software_items()
+        .into_iter()
+        .find(|item| item.id == catalog_id)
+}
+
+fn position_of_origin(config: &AppConfig, catalog_id: &str) -> Option<usize> {
+    config.items.iter().position(|item| {
+        item.origin
+            .as_ref()
+            .is_some_and(|origin| origin.catalog_id == catalog_id)
+    })
+}
+
+/// Whether the catalog changed since `item` was last taken from it.
+fn catalog_changed(item: &SoftwareItem, catalog_item: &SoftwareItem) -> bool {
+    item.origin.as_ref().is_some_and(|origin| {
+        origin.fingerprint != software_catalog::catalog_fingerprint(catalog_item)
+    })
+}
+
+fn user_modified(item: &SoftwareItem) -> bool {
+    item.origin
+        .as_ref()
+        .is_some_and(|origin| origin.fingerprint != software_catalog::catalog_fingerprint(item))
+}
+
+/// Brings the config up to the current catalog: untouched items take the new
+/// catalog fields, new catalog items are added, and catalog items that are
+/// gone from an already synced config are recorded as deleted. Items the
+/// user modified are left alone; see `pending_updates`.
+pub fn sync(config: &mut AppConfig) -> Option<MigrationStepReport> {
+    let before = config.clone();
+    for catalog_item in software_catalog::default_software_items() {
+        let catalog_id = catalog_item.id.clone();
+        if config.removed_catalog_items.contains(&catalog_id) {
+            continue;
+        }
+        let Some(position) = position_of_origin(config, &catalog_id) else {
+            if software_catalog::catalog_item_added_in(&catalog_id) <= config.catalog_revision {
+                config.removed_catalog_items.insert(catalog_id);
+            } else if !config.items.iter().any(|item| item.id == catalog_id) {
+                config.items.push(catalog_item);
+            }
+            continue;
+        };
+        let item = &mut config.items[position];
+        if catalog_changed(item, &catalog_item) && !user_modified(item) {
+            software_catalog::apply_catalog_fields(item, &catalog_item);
+            item.origin = catalog_item.origin;
+        }
+    }
+    config.catalog_revision = config.catalog_revision.max(CATALOG_REVISION);
+
+    let changes = config_migrations::diff_config(&before, config);
+    (!changes.is_empty()).then(|| MigrationStepReport {
+        id: "catalog-sync".to_string(),
+        description: "按内置目录更新未被修改的软件项".to_string(),
+        changes,
+    })
+}
+
+/// Catalog changes held back because the user modified the item.
+pub fn pending_updates(config: &AppConfig) -> Vec<CatalogUpdate> {
+    config
+        .items
+        .iter()
+        .filter(|item| user_modified(item))
+        .filter_map(|item| {
+            let origin = item.origin.as_ref()?;
+            let catalog_item = find_catalog_item(&origin.catalog_id)?;
+            if !catalog_changed(item, &catalog_item) {
+                return None;
+            }
+            let mut updated = item.clone();
+            software_catalog::apply_catalog_fields(&mut updated, &catalog_item);
+            Some(CatalogUpdate {
+                item_id: item.id.clone(),
+                catalog_id: origin.catalog_id.clone(),
+                from_revision: origin.revision,
+                to_revision: CATALOG_REVISION,
+                changes: config_migrations::diff_item(item, &updated),
+            })
+        })
+        .collect()
+}
+
+fn catalog_item_for(config: &AppConfig, item_id: &str) -> Result<(usize, SoftwareItem), String> {
+    let position = config
+        .items
+        .iter()
+        .position(|item| item.id == item_id)
+        .ok_or_else(|| format!("item not found: {item_id}"))?;
+    let catalog_id = config.items[position]
+        .origin
+        .as_ref()
+        .map(|origin| origin.catalog_id.clone())
+        .ok_or_else(|| format!("{item_id} is not a catalog item"))?;
+    let catalog_item = find_catalog_item(&catalog_id)
+        .ok_or_else(|| format!("catalog item not found: {catalog_id}"))?;
+    Ok((position, catalog_item))
+}
+
+/// Replaces the item's catalog-managed fields with the catalog's.
+pub fn accept_update(config: &mut AppConfig, item_id: &str) -> Result<(), String> {
+    let (position, catalog_item) = catalog_item_for(config, item_id)?;
+    let item = &mut config.items[position];
+    software_catalog::apply_catalog_fields(item, &catalog_item);
+    item.origin = catalog_item.origin;
+    Ok(())
+}
+
+/// Keeps the user's fields and stops offering this catalog version.
+pub fn dismiss_update(config: &mut AppConfig, item_id: &str) -> Result<(), String> {
+    let (position, catalog_item) = catalog_item_for(config, item_id)?;
+    config.items[position].origin = catalog_item.origin;
+    Ok(())
+}
+
+/// Adds a deleted catalog item back and forgets its tombstone.
+pub fn restore_item(config: &mut AppConfig, catalog_id: &str) -> Result<(), String> {
+    let catalog_item = find_catalog_item(catalog_id)
+        .ok_or_else(|| format!("catalog item not found: {catalog_id}"))?;
+    if config.items.iter().any(|item| item.id == catalog_item.id) {
+        return Err(format!("item already exists: {catalog_id}"));
+    }
+    config.removed_catalog_items.remove(catalog_id);
+    config.items.push(catalog_item);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::config_store::{load_or_init_config, save_config};
+    use crate::services::store::MemoryStore;
+
+    /// Makes the first item look like it was taken from an older catalog
+    /// with a different update command.
+    fn from_older_catalog(config: &mut AppConfig) -> String {
+        let item = &mut config.items[0];
+        item.update_command = "brew upgrade --old".to_string();
+        item.origin = Some(software_catalog::catalog_origin(item));
+        item.id.clone()
+    }
+
+    fn catalog_command(item_id: &str) -> String {
+        find_catalog_item(item_id).unwrap().update_command
+    }
+
+    fn saved(store: &MemoryStore, config: &AppConfig) -> AppConfig {
+        save_config(store, config).unwrap();
+        load_or_init_config(store).unwrap()
+    }
+
+    #[test]
+    fn untouched_items_take_the_catalog_fields() {
+        let store = MemoryStore::new();
+        let mut config = load_or_init_config(&store).unwrap();
+        let item_id = from_older_catalog(&mut config);
+        // User settings are not part of the fingerprint.
+        config.items[0].enabled = !config.items[0].enabled;
+
+        let loaded = saved(&store, &config);
+        assert_eq!(loaded.items[0].update_command, catalog_command(&item_id));
+        assert_eq!(loaded.items[0].enabled, config.items[0].enabled);
+        assert!(pending_updates(&loaded).is_empty());
+    }
+
+    #[test]
+    fn modified_items_are_held_back_until_accepted() {
+        let store = MemoryStore::new();
+        let mut config = load_or_init_config(&store).unwrap();
+        let item_id = from_older_catalog(&mut config);
+        config.items[0].update_command = "echo custom".to_string();
+
+        let mut loaded = saved(&store, &config);
+        assert_eq!(loaded.items[0].update_command, "echo custom");
+        let pending = pending_updates(&loaded);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].item_id, item_id);
+        assert!(pending[0]
+            .changes
+            .iter()
+            .any(|change| change.field.as_deref() == Some("update_command")));
+
+        accept_update(&mut loaded, &item_id).unwrap();
+        let loaded = saved(&store, &loaded);
+        assert_eq!(loaded.items[0].update_command, catalog_command(&item_id));
+        assert!(pending_updates(&loaded).is_empty());
+    }
+
+    #[test]
+    fn dismissed_updates_keep_the_user_fields() {
+        let store = MemoryStore::new();
+        let mut config = load_or_init_config(&store).unwrap();
+        let item_id = from_older_catalog(&mut config);
+        config.items[0].update_command = "echo custom".to_string();
+
+        let mut loaded = saved(&store, &config);
+        dismiss_update(&mut loaded, &item_id).unwrap();
+        let loaded = saved(&store, &loaded);
+        assert_eq!(loaded.items[0].update_command, "echo custom");
+        assert!(pending_updates(&loaded).is_empty());
+    }
+
+    #[test]
+    fn deleted_catalog_items_stay_deleted_until_restored() {
+        let store = MemoryStore::new();
+        let mut config = load_or_init_config(&store).unwrap();
+        let item_id = config.items.remove(0).id;
+
+        saved(&store, &config);
+        let mut loaded = load_or_init_config(&store).unwrap();
+        assert!(loaded.items.iter().all(|item| item.id != item_id));
+        assert!(loaded.removed_catalog_items.contains(&item_id));
+        assert!(restore_item(&mut loaded.clone(), "missing").is_err());
+
+        restore_item(&mut loaded, &item_id).unwrap();
+        assert!(restore_item(&mut loaded.clone(), &item_id).is_err());
+        let loaded = saved(&store, &loaded);
+        assert!(loaded.items.iter().any(|item| item.id == item_id));
+        assert!(!loaded.removed_catalog_items.contains(&item_id));
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Runs `task` over `items` on at most `max_parallel` worker threads and
+/// returns the results in input order, regardless of completion order. A
+/// panic in `task` is re-raised here once the other workers finished.
+pub fn run_bounded<T, R, F>(items: &[T], max_parallel: usize, task: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let workers = max_parallel.max(1).min(items.len());
+    if workers <= 1 {
+        return items.iter().map(&task).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let mut indexed: Vec<(usize, R)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            break;
+                        };
+                        done.push((index, task(item)));
+                    }
+                    done
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|payload| panic::resume_unwind(payload))
+            })
+            .collect()
+    });
+
+    indexed.sort_by_key(|(index, _)| *index);
+    indexed.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Spaces out requests to the same host so a batch of items backed by one
+/// registry (GitHub, npm, formulae.brew.sh) does not burst it.
+pub struct HostRateLimiter {
+    min_interval: Duration,
+    next_slot: Mutex<HashMap<String, Instant>>,
+}
+
+impl HostRateLimiter {
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            next_slot: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn wait_turn(&self, host: &str) {
+        let slot = {
+            let Ok(mut slots) = self.next_slot.lock() else {
+                return;
+            };
+            let now = Instant::now();
+            let slot = slots
+                .get(host)
+                .copied()
+                .filter(|slot| *slot > now)
+                .unwrap_or(now);
+            slots.insert(host.to_string(), slot + self.min_interval);
+            slot
+        };
+
+        let wait = slot.saturating_duration_since(Instant::now());
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_keep_the_input_order() {
+        let items: Vec<u64> = (0..20).collect();
+        let results = run_bounded(&items, 4, |item| {
+            thread::sleep(Duration::from_millis(20 - item));
+            item * 2
+        });
+        assert_eq!(
+            results,
+            items.iter().map(|item| item * 2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "worker failed")]
+    fn a_panicking_task_is_not_swallowed() {
+        let items: Vec<u64> = (0..8).collect();
+        run_bounded(&items, 4, |item| {
+            if *item == 5 {
+                panic!("worker failed");
+            }
+            *item
+        });
+    }
+
+    #[test]
+    fn requests_to_one_host_are_spaced_out() {
+        let limiter = HostRateLimiter::new(Duration::from_millis(50));
+        let started = Instant::now();
+        limiter.wait_turn("api.github.com");
+        limiter.wait_turn("registry.npmjs.org");
+        assert!(started.elapsed() < Duration::from_millis(50));
+        limiter.wait_turn("api.github.com");
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+}
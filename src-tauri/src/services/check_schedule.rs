@@ -0,0 +1,362 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+
+use crate::model::{AppConfig, CheckSchedule, QuietHours, SoftwareItem};
+
+/// How far ahead `CronExpr::next_after` searches before giving up.
+const CRON_SEARCH_DAYS: i64 = 366 * 5;
+
+/// Five-field cron expression (`minute hour day-of-month month day-of-week`)
+/// evaluated in local time. Fields accept `*`, lists, ranges and `/step`;
+/// day-of-week is 0-7 with both 0 and 7 meaning Sunday.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days: Vec<bool>,
+    months: Vec<bool>,
+    weekdays: Vec<bool>,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+fn parse_number(raw: &str, field: &str) -> Result<u32, String> {
+    raw.parse::<u32>()
+        .map_err(|_| format!("invalid cron {field} value: {raw}"))
+}
+
+fn parse_field(raw: &str, min: u32, max: u32, field: &str) -> Result<Vec<bool>, String> {
+    let mut allowed = vec![false; max as usize + 1];
+    for part in raw.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, parse_number(step, field)?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(format!("invalid cron {field} step: {part}"));
+        }
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_number(start, field)?, parse_number(end, field)?)
+        } else {
+            let value = parse_number(range, field)?;
+            // `5/15` means "from 5 to the end, every 15".
+            (value, if part.contains('/') { max } else { value })
+        };
+        if start < min || end > max || start > end {
+            return Err(format!("cron {field} out of range: {part}"));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            allowed[value as usize] = true;
+        }
+    }
+    Ok(allowed)
+}
+
+impl CronExpr {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "cron expression needs 5 fields, got {}: {expression}",
+                fields.len()
+            ));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7, "day-of-week")?;
+        if weekdays[7] {
+            weekdays[0] = true;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, "minute")?,
+            hours: parse_field(hour, 0, 23, "hour")?,
+            days: parse_field(day, 1, 31, "day-of-month")?,
+            months: parse_field(month, 1, 12, "month")?,
+            weekdays,
+            // As in Vixie cron, a field starting with `*` (also `*/2`) does
+            // not restrict the day, so only the other day field applies.
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        })
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if !self.months[date.month() as usize] {
+            return false;
+        }
+        let day = self.days[date.day() as usize];
+        let weekday = self.weekdays[date.weekday().num_days_from_sunday() as usize];
+        // Standard cron: when both day fields are restricted, either may match.
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            (true, false) => day,
+            (false, true) => weekday,
+            (false, false) => true,
+        }
+    }
+
+    /// First matching minute strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let next_minute = after.naive_local() + Duration::minutes(1);
+        let start = next_minute
+            .date()
+            .and_hms_opt(next_minute.hour(), next_minute.minute(), 0)?;
+        let start_date = start.date();
+        for offset in 0..CRON_SEARCH_DAYS {
+            let date = start_date + Duration::days(offset);
+            if !self.matches_date(date) {
+                continue;
+            }
+            for hour in 0..24u32 {
+                if !self.hours[hour as usize] {
+                    continue;
+                }
+                for minute in 0..60u32 {
+                    if !self.minutes[minute as usize] {
+                        continue;
+                    }
+                    let Some(candidate) = date.and_hms_opt(hour, minute, 0) else {
+                        continue;
+                    };
+                    if candidate < start {
+                        continue;
+                    }
+                    // Skips times that do not exist because of a DST jump.
+                    if let Some(local) = Local.from_local_datetime(&candidate).earliest() {
+                        return Some(local);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+fn parse_clock(raw: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(raw.trim(), "%H:%M")
+        .map_err(|_| format!("invalid quiet hours time (expected HH:MM): {raw}"))
+}
+
+/// The end of the quiet window containing `at`, or `None` when `at` is
+/// outside quiet hours.
+pub fn quiet_hours_end(
+    quiet: &QuietHours,
+    at: DateTime<Local>,
+) -> Result<Option<DateTime<Local>>, String> {
+    let start = parse_clock(&quiet.start)?;
+    let end = parse_clock(&quiet.end)?;
+    let time = at.time();
+    let inside = if start <= end {
+        time >= start && time < end
+    } else {
+        time >= start || time < end
+    };
+    if !inside {
+        return Ok(None);
+    }
+    let mut end_date = at.date_naive();
+    if start > end && time >= start {
+        end_date += Duration::days(1);
+    }
+    let end_at = end_date.and_time(end);
+    Ok(Some(
+        Local
+            .from_local_datetime(&end_at)
+            .earliest()
+            .unwrap_or_else(|| at + Duration::hours(1)),
+    ))
+}
+
+/// The schedule that applies to `item`: its own, then its group's, then the
+/// global `check_interval_minutes`.
+pub fn effective_schedule(config: &AppConfig, item: &SoftwareItem) -> CheckSchedule {
+    let configured = item
+        .schedule
+        .as_ref()
+        .filter(|schedule| schedule.interval_minutes.is_some() || schedule.cron.is_some())
+        .or_else(|| config.group_schedules.get(item.group().as_str()));
+    match configured {
+        Some(schedule) => schedule.clone(),
+        None => CheckSchedule {
+            interval_minutes: Some(config.check_interval_minutes),
+            cron: None,
+        },
+    }
+}
+
+/// When `item` should next be checked automatically, ignoring quiet hours.
+/// Items that were never checked are due immediately.
+pub fn next_due(
+    config: &AppConfig,
+    item: &SoftwareItem,
+    last_checked: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, String> {
+    let Some(last_checked) = last_checked else {
+        return Ok(now);
+    };
+    let schedule = effective_schedule(config, item);
+    if let Some(expression) = schedule.cron.as_deref() {
+        return CronExpr::parse(expression)?
+            .next_after(last_checked.with_timezone(&Local))
+            .map(|next| next.with_timezone(&Utc))
+            .ok_or_else(|| format!("cron expression never fires: {expression}"));
+    }
+    let minutes = schedule
+        .interval_minutes
+        .unwrap_or(config.check_interval_minutes)
+        .max(1);
+    Ok(last_checked + Duration::minutes(minutes as i64))
+}
+
+/// Moves `run_at` past the configured quiet hours, if it falls inside them.
+pub fn outside_quiet_hours(
+    config: &AppConfig,
+    run_at: DateTime<Utc>,
+) -> Result<DateTime<Utc>, String> {
+    let Some(quiet) = &config.quiet_hours else {
+        return Ok(run_at);
+    };
+    Ok(quiet_hours_end(quiet, run_at.with_timezone(&Local))?
+        .map(|end| end.with_timezone(&Utc))
+        .unwrap_or(run_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ItemGroup;
+
+    fn local(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .single()
+            .expect("unambiguous local time")
+    }
+
+    fn cron(expression: &str) -> CronExpr {
+        CronExpr::parse(expression).expect("valid cron expression")
+    }
+
+    #[test]
+    fn cron_fields_accept_lists_ranges_and_steps() {
+        let expr = cron("5,20-22,*/30 1-3/2 * * *");
+        let minutes: Vec<usize> = (0..60).filter(|minute| expr.minutes[*minute]).collect();
+        assert_eq!(minutes, [0, 5, 20, 21, 22, 30]);
+        let hours: Vec<usize> = (0..24).filter(|hour| expr.hours[*hour]).collect();
+        assert_eq!(hours, [1, 3]);
+        assert_eq!(
+            expr.next_after(local(2025, 1, 6, 1, 22)),
+            Some(local(2025, 1, 6, 1, 30))
+        );
+        assert_eq!(
+            expr.next_after(local(2025, 1, 6, 3, 30)),
+            Some(local(2025, 1, 7, 1, 0))
+        );
+
+        assert!(CronExpr::parse("* * *").is_err());
+        assert!(CronExpr::parse("60 * * * *").is_err());
+        assert!(CronExpr::parse("*/0 * * * *").is_err());
+        assert!(CronExpr::parse("5-1 * * * *").is_err());
+    }
+
+    #[test]
+    fn zero_and_seven_both_mean_sunday() {
+        // 2025-01-05 is a Sunday.
+        let saturday = local(2025, 1, 4, 12, 0);
+        let sunday_nine = Some(local(2025, 1, 5, 9, 0));
+        assert_eq!(cron("0 9 * * 0").next_after(saturday), sunday_nine);
+        assert_eq!(cron("0 9 * * 7").next_after(saturday), sunday_nine);
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // Day 15 or any Monday: from Friday 2025-01-10 the Monday comes first.
+        let expr = cron("0 0 15 * 1");
+        assert_eq!(
+            expr.next_after(local(2025, 1, 10, 0, 0)),
+            Some(local(2025, 1, 13, 0, 0))
+        );
+        assert_eq!(
+            expr.next_after(local(2025, 1, 13, 0, 0)),
+            Some(local(2025, 1, 15, 0, 0))
+        );
+        // A `*/2` day-of-week does not restrict, so only day 15 matches.
+        assert_eq!(
+            cron("0 0 15 * */2").next_after(local(2025, 1, 10, 0, 0)),
+            Some(local(2025, 1, 15, 0, 0))
+        );
+    }
+
+    #[test]
+    fn quiet_hours_wrap_past_midnight() {
+        let quiet = QuietHours {
+            start: "22:00".to_string(),
+            end: "07:30".to_string(),
+        };
+        assert_eq!(
+            quiet_hours_end(&quiet, local(2025, 1, 6, 23, 0)),
+            Ok(Some(local(2025, 1, 7, 7, 30)))
+        );
+        assert_eq!(
+            quiet_hours_end(&quiet, local(2025, 1, 7, 6, 0)),
+            Ok(Some(local(2025, 1, 7, 7, 30)))
+        );
+        assert_eq!(quiet_hours_end(&quiet, local(2025, 1, 7, 7, 30)), Ok(None));
+        assert_eq!(quiet_hours_end(&quiet, local(2025, 1, 7, 12, 0)), Ok(None));
+
+        let daytime = QuietHours {
+            start: "12:00".to_string(),
+            end: "13:00".to_string(),
+        };
+        assert_eq!(
+            quiet_hours_end(&daytime, local(2025, 1, 7, 12, 15)),
+            Ok(Some(local(2025, 1, 7, 13, 0)))
+        );
+        assert_eq!(
+            quiet_hours_end(&daytime, local(2025, 1, 7, 23, 0)),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn item_schedule_beats_group_beats_global() {
+        let mut config = AppConfig {
+            check_interval_minutes: 600,
+            ..AppConfig::default()
+        };
+        let mut item = config
+            .items
+            .iter()
+            .find(|item| item.group() == ItemGroup::Cli)
+            .cloned()
+            .expect("a cli item");
+        item.schedule = None;
+        let checked_at = Utc.with_ymd_and_hms(2025, 1, 6, 8, 0, 0).unwrap();
+        let now = checked_at + Duration::minutes(90);
+        let due = |config: &AppConfig, item: &SoftwareItem| {
+            next_due(config, item, Some(checked_at), now).unwrap()
+        };
+
+        assert_eq!(due(&config, &item), checked_at + Duration::minutes(600));
+        config.group_schedules.insert(
+            "cli".to_string(),
+            CheckSchedule {
+                interval_minutes: Some(120),
+                cron: None,
+            },
+        );
+        assert_eq!(due(&config, &item), checked_at + Duration::minutes(120));
+        item.schedule = Some(CheckSchedule {
+            interval_minutes: Some(60),
+            cron: None,
+        });
+        // Due once `checked_at` is older than the item's own interval.
+        assert!(due(&config, &item) <= now);
+        assert_eq!(due(&config, &item), checked_at + Duration::minutes(60));
+        // An empty item schedule falls back to the group's.
+        item.schedule = Some(CheckSchedule::default());
+        assert_eq!(due(&config, &item), checked_at + Duration::minutes(120));
+
+        assert_eq!(next_due(&config, &item, None, now), Ok(now));
+    }
+}
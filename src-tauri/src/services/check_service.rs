@@ -1,7 +1,18 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
 use chrono::Utc;
 use regex::Regex;
 
-use crate::model::{CheckResult, CommandOutput, SoftwareItem};
+use crate::model::{
+    CheckResult, CommandOutput, ExecutionProfile, InstalledVersionProbe, SoftwareItem,
+    VersionComparison, VersionSource,
+};
+use crate::services::check_executor::HostRateLimiter;
+use crate::services::run_registry::CancelToken;
+use crate::services::shell_runner::{OutputLimits, RunHooks};
+use crate::services::version_source::Endpoints;
+use crate::services::{installed_probe, shell_runner, version_compare, version_source};
 
 fn now_rfc3339() -> String {
     Utc::now().to_rfc3339()
@@ -17,7 +28,166 @@ fn command_error_text(stderr: &str, stdout: &str) -> String {
     "no output".to_string()
 }
 
-type CommandExecutor<'a> = dyn FnMut(&str) -> Result<CommandOutput, String> + 'a;
+fn compare_pair(
+    item: &SoftwareItem,
+    current: &Option<String>,
+    latest: &Option<String>,
+) -> Option<VersionComparison> {
+    match (current, latest) {
+        (Some(current), Some(latest)) => Some(version_compare::compare_versions(
+            item.version_comparator,
+            current,
+            latest,
+        )),
+        _ => None,
+    }
+}
+
+/// Why a runner operation produced nothing. A cancellation is kept apart
+/// from failures so it ends the whole check instead of becoming its error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckError {
+    Cancelled,
+    Failed(String),
+}
+
+impl CheckError {
+    fn context(self, what: &str) -> Self {
+        match self {
+            CheckError::Failed(error) => CheckError::Failed(format!("{what} failed: {error}")),
+            cancelled => cancelled,
+        }
+    }
+}
+
+impl From<String> for CheckError {
+    fn from(error: String) -> Self {
+        CheckError::Failed(error)
+    }
+}
+
+/// What checking an item came to. A cancelled check has no result, so the
+/// previously stored one is kept.
+#[derive(Debug)]
+pub enum CheckOutcome {
+    Checked(CheckResult),
+    Cancelled,
+}
+
+/// Side-effecting operations a check needs; swapped out for fakes when
+/// exercising the check logic without a shell or network.
+pub trait CheckRunner {
+    fn run_command(&mut self, command: &str) -> Result<CommandOutput, CheckError>;
+    fn fetch_latest(&mut self, source: &VersionSource) -> Result<String, CheckError>;
+    fn probe_installed(
+        &mut self,
+        probe: &InstalledVersionProbe,
+    ) -> Result<Option<String>, CheckError>;
+}
+
+pub struct SystemCheckRunner {
+    timeout_seconds: u64,
+    profile: ExecutionProfile,
+    limits: OutputLimits,
+    rate_limiter: Option<Arc<HostRateLimiter>>,
+    cancel: Option<CancelToken>,
+    endpoints: Endpoints,
+}
+
+impl SystemCheckRunner {
+    pub fn new(timeout_seconds: u64) -> Self {
+        Self {
+            timeout_seconds,
+            profile: ExecutionProfile::default(),
+            limits: OutputLimits::default(),
+            rate_limiter: None,
+            cancel: None,
+            endpoints: Endpoints::default(),
+        }
+    }
+
+    pub fn with_profile(mut self, profile: ExecutionProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    pub fn with_max_output_bytes(mut self, max_stream_bytes: usize) -> Self {
+        self.limits.max_stream_bytes = max_stream_bytes;
+        self
+    }
+
+    /// Appends the full output of every command this runner executes to `path`.
+    pub fn with_full_output_log(mut self, path: PathBuf) -> Self {
+        self.limits.spill_path = Some(path);
+        self
+    }
+
+    pub fn with_rate_limiter(mut self, limiter: Arc<HostRateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    pub fn with_cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    pub fn with_endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    fn ensure_not_cancelled(&self) -> Result<(), CheckError> {
+        if self.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+            Err(CheckError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl CheckRunner for SystemCheckRunner {
+    fn run_command(&mut self, command: &str) -> Result<CommandOutput, CheckError> {
+        self.ensure_not_cancelled()?;
+        let hooks = RunHooks {
+            sink: None,
+            cancel: self.cancel.clone(),
+            limits: self.limits.clone(),
+        };
+        let output =
+            shell_runner::run_shell_command_with(&self.profile, command, self.timeout_seconds, hooks)?;
+        if output.cancelled {
+            return Err(CheckError::Cancelled);
+        }
+        Ok(output)
+    }
+
+    fn fetch_latest(&mut self, source: &VersionSource) -> Result<String, CheckError> {
+        self.ensure_not_cancelled()?;
+        if let (Some(limiter), Some(host)) =
+            (&self.rate_limiter, version_source::source_host(&self.endpoints, source))
+        {
+            limiter.wait_turn(&host);
+        }
+        Ok(version_source::fetch_latest_with(
+            &self.endpoints,
+            source,
+            self.timeout_seconds,
+        )?)
+    }
+
+    fn probe_installed(
+        &mut self,
+        probe: &InstalledVersionProbe,
+    ) -> Result<Option<String>, CheckError> {
+        self.ensure_not_cancelled()?;
+        Ok(installed_probe::probe_installed_version(
+            probe,
+            &self.profile,
+            self.timeout_seconds,
+        )?)
+    }
+}
 
 fn normalize_version(value: &str) -> Option<String> {
     let trimmed = value.trim();
@@ -28,42 +198,83 @@ fn normalize_version(value: &str) -> Option<String> {
     }
 }
 
-fn check_with_versions(
+fn has_current_source(item: &SoftwareItem) -> bool {
+    item.installed_probe.is_some() || item.current_version_command.is_some()
+}
+
+fn has_latest_source(item: &SoftwareItem) -> bool {
+    item.version_source.is_some() || item.latest_version_command.is_some()
+}
+
+fn resolve_current(
     item: &SoftwareItem,
-    execute: &mut CommandExecutor<'_>,
-) -> Result<CheckResult, String> {
+    runner: &mut dyn CheckRunner,
+) -> Result<Option<String>, CheckError> {
+    if let Some(probe) = &item.installed_probe {
+        let current = runner
+            .probe_installed(probe)
+            .map_err(|error| error.context("installed_probe"))?;
+        return Ok(current.as_deref().and_then(normalize_version));
+    }
+
     let current_cmd = item
         .current_version_command
         .as_deref()
         .ok_or_else(|| format!("{} has no current_version_command", item.id))?;
-    let latest_cmd = item
-        .latest_version_command
-        .as_deref()
-        .ok_or_else(|| format!("{} has no latest_version_command", item.id))?;
-
-    let current_output = execute(current_cmd)?;
-    let latest_output = execute(latest_cmd)?;
+    let current_output = runner.run_command(current_cmd)?;
     if current_output.exit_code != 0 {
-        return Err(format!(
+        return Err(CheckError::Failed(format!(
             "current_version_command failed (exit {}): {}",
             current_output.exit_code,
             command_error_text(&current_output.stderr, &current_output.stdout)
-        ));
+        )));
+    }
+    Ok(normalize_version(&current_output.stdout))
+}
+
+fn resolve_latest(
+    item: &SoftwareItem,
+    runner: &mut dyn CheckRunner,
+) -> Result<Option<String>, CheckError> {
+    if let Some(source) = &item.version_source {
+        let latest = runner
+            .fetch_latest(source)
+            .map_err(|error| error.context("version_source"))?;
+        return Ok(normalize_version(&latest));
     }
+
+    let latest_cmd = item
+        .latest_version_command
+        .as_deref()
+        .ok_or_else(|| format!("{} has no latest_version_command", item.id))?;
+    let latest_output = runner.run_command(latest_cmd)?;
     if latest_output.exit_code != 0 {
-        return Err(format!(
+        return Err(CheckError::Failed(format!(
             "latest_version_command failed (exit {}): {}",
             latest_output.exit_code,
             command_error_text(&latest_output.stderr, &latest_output.stdout)
-        ));
+        )));
     }
+    Ok(normalize_version(&latest_output.stdout))
+}
 
-    let current = normalize_version(&current_output.stdout);
-    let latest = normalize_version(&latest_output.stdout);
-    let has_update = match (&current, &latest) {
-        (Some(current), Some(latest)) => current != latest,
-        _ => false,
-    };
+fn check_with_versions(
+    item: &SoftwareItem,
+    runner: &mut dyn CheckRunner,
+) -> Result<CheckResult, CheckError> {
+    if !has_current_source(item) {
+        return Err(CheckError::Failed(format!(
+            "{} has neither installed_probe nor current_version_command",
+            item.id
+        )));
+    }
+
+    let current = resolve_current(item, runner)?;
+    let latest = resolve_latest(item, runner)?;
+    let version_comparison = compare_pair(item, &current, &latest);
+    let has_update = version_comparison
+        .map(version_compare::has_update)
+        .unwrap_or(false);
 
     Ok(CheckResult {
         item_id: item.id.clone(),
@@ -71,6 +282,7 @@ fn check_with_versions(
         has_update,
         current_version: current,
         latest_version: latest,
+        version_comparison,
         details: "version comparison".to_string(),
         error: None,
     })
@@ -78,19 +290,19 @@ fn check_with_versions(
 
 fn check_with_command(
     item: &SoftwareItem,
-    execute: &mut CommandExecutor<'_>,
-) -> Result<CheckResult, String> {
+    runner: &mut dyn CheckRunner,
+) -> Result<CheckResult, CheckError> {
     let check_cmd = item
         .update_check_command
         .as_deref()
         .ok_or_else(|| format!("{} has no update_check_command", item.id))?;
-    let output = execute(check_cmd)?;
+    let output = runner.run_command(check_cmd)?;
     if output.exit_code != 0 {
-        return Err(format!(
+        return Err(CheckError::Failed(format!(
             "update_check_command failed (exit {}): {}",
             output.exit_code,
             command_error_text(&output.stderr, &output.stdout)
-        ));
+        )));
     }
 
     let has_update = match &item.update_check_regex {
@@ -103,61 +315,224 @@ fn check_with_command(
         }
     };
 
-    let current_version = if let Some(command) = &item.current_version_command {
-        let version_output = execute(command)?;
-        if version_output.exit_code == 0 {
-            normalize_version(&version_output.stdout)
-        } else {
-            None
-        }
+    // The versions are extra detail here, so only a cancellation stops the
+    // check.
+    let current_version = if has_current_source(item) {
+        optional_version(resolve_current(item, runner))?
     } else {
         None
     };
 
-    let latest_version = if let Some(command) = &item.latest_version_command {
-        let version_output = execute(command)?;
-        if version_output.exit_code == 0 {
-            normalize_version(&version_output.stdout)
-        } else {
-            None
-        }
+    let latest_version = if has_latest_source(item) {
+        optional_version(resolve_latest(item, runner))?
     } else {
         None
     };
 
+    let version_comparison = compare_pair(item, &current_version, &latest_version);
+
     Ok(CheckResult {
         item_id: item.id.clone(),
         checked_at: now_rfc3339(),
         has_update,
         current_version,
         latest_version,
+        version_comparison,
         details: format!("check command output: {}", output.stdout),
         error: None,
     })
 }
 
-pub fn check_single_item(item: &SoftwareItem, execute: &mut CommandExecutor<'_>) -> CheckResult {
+fn optional_version(
+    version: Result<Option<String>, CheckError>,
+) -> Result<Option<String>, CheckError> {
+    match version {
+        Err(CheckError::Failed(_)) => Ok(None),
+        other => other,
+    }
+}
+
+/// The stored shape of a check that failed with `error`.
+pub fn failed_result(item: &SoftwareItem, error: String) -> CheckResult {
+    CheckResult {
+        item_id: item.id.clone(),
+        checked_at: now_rfc3339(),
+        has_update: false,
+        current_version: None,
+        latest_version: None,
+        version_comparison: None,
+        details: "check failed".to_string(),
+        error: Some(error),
+    }
+}
+
+pub fn check_single_item(item: &SoftwareItem, runner: &mut dyn CheckRunner) -> CheckOutcome {
     let result = if item.update_check_command.is_some() {
-        check_with_command(item, execute)
-    } else if item.latest_version_command.is_some() {
-        check_with_versions(item, execute)
+        check_with_command(item, runner)
+    } else if has_latest_source(item) {
+        check_with_versions(item, runner)
     } else {
-        Err(format!(
-            "{} has neither update_check_command nor latest_version_command",
+        Err(CheckError::Failed(format!(
+            "{} has neither update_check_command nor version_source/latest_version_command",
             item.id
-        ))
+        )))
     };
 
     match result {
-        Ok(value) => value,
-        Err(error) => CheckResult {
-            item_id: item.id.clone(),
-            checked_at: now_rfc3339(),
-            has_update: false,
-            current_version: None,
-            latest_version: None,
-            details: "check failed".to_string(),
-            error: Some(error),
-        },
+        Ok(value) => CheckOutcome::Checked(value),
+        Err(CheckError::Failed(error)) => CheckOutcome::Checked(failed_result(item, error)),
+        Err(CheckError::Cancelled) => CheckOutcome::Cancelled,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::model::AppConfig;
+    use crate::services::check_executor;
+
+    /// Answers `current` and `latest` from fixed versions, after `delay`.
+    struct FakeRunner {
+        current: &'static str,
+        latest: &'static str,
+        delay: Duration,
+        cancelled: bool,
+    }
+
+    impl FakeRunner {
+        fn new(current: &'static str, latest: &'static str) -> Self {
+            Self {
+                current,
+                latest,
+                delay: Duration::ZERO,
+                cancelled: false,
+            }
+        }
+    }
+
+    impl CheckRunner for FakeRunner {
+        fn run_command(&mut self, command: &str) -> Result<CommandOutput, CheckError> {
+            if self.cancelled {
+                return Err(CheckError::Cancelled);
+            }
+            thread::sleep(self.delay);
+            let (exit_code, stdout) = match command {
+                "current" => (0, self.current),
+                "latest" => (0, self.latest),
+                "has-update" => (0, "yes"),
+                _ => (1, ""),
+            };
+            Ok(CommandOutput {
+                command: command.to_string(),
+                exit_code,
+                stdout: format!("{stdout}\n"),
+                stderr: String::new(),
+                duration_ms: 0,
+                timed_out: false,
+                cancelled: false,
+                truncated: false,
+                full_output_log: None,
+            })
+        }
+
+        fn fetch_latest(&mut self, _source: &VersionSource) -> Result<String, CheckError> {
+            Err(CheckError::Failed("no network in tests".to_string()))
+        }
+
+        fn probe_installed(
+            &mut self,
+            _probe: &InstalledVersionProbe,
+        ) -> Result<Option<String>, CheckError> {
+            Err(CheckError::Failed("no probes in tests".to_string()))
+        }
+    }
+
+    fn item(id: &str) -> SoftwareItem {
+        SoftwareItem {
+            id: id.to_string(),
+            installed_probe: None,
+            current_version_command: Some("current".to_string()),
+            version_source: None,
+            latest_version_command: Some("latest".to_string()),
+            update_check_command: None,
+            update_check_regex: None,
+            ..AppConfig::default().items[0].clone()
+        }
+    }
+
+    fn checked(outcome: CheckOutcome) -> CheckResult {
+        match outcome {
+            CheckOutcome::Checked(result) => result,
+            CheckOutcome::Cancelled => panic!("check was cancelled"),
+        }
+    }
+
+    #[test]
+    fn versions_are_compared() {
+        let result = checked(check_single_item(
+            &item("node"),
+            &mut FakeRunner::new("1.2.0", "1.3.0"),
+        ));
+        assert!(result.has_update);
+        assert_eq!(result.current_version.as_deref(), Some("1.2.0"));
+        assert_eq!(result.error, None);
+    }
+
+    #[test]
+    fn failing_commands_become_the_result_error() {
+        let mut broken = item("node");
+        broken.latest_version_command = Some("missing".to_string());
+        let result = checked(check_single_item(
+            &broken,
+            &mut FakeRunner::new("1.2.0", "1.3.0"),
+        ));
+        assert!(!result.has_update);
+        assert!(result
+            .error
+            .is_some_and(|error| error.starts_with("latest_version_command failed")));
+    }
+
+    #[test]
+    fn cancelled_checks_have_no_result() {
+        let mut with_command = item("node");
+        with_command.update_check_command = Some("has-update".to_string());
+        for item in [item("node"), with_command] {
+            let mut runner = FakeRunner {
+                cancelled: true,
+                ..FakeRunner::new("1.2.0", "1.3.0")
+            };
+            assert!(matches!(
+                check_single_item(&item, &mut runner),
+                CheckOutcome::Cancelled
+            ));
+        }
+    }
+
+    #[test]
+    fn bounded_checks_come_back_in_input_order() {
+        let items: Vec<SoftwareItem> = (0..6).map(|index| item(&format!("item-{index}"))).collect();
+        let results = check_executor::run_bounded(&items, 2, |item| {
+            // Later items finish first.
+            let index: u64 = item.id.trim_start_matches("item-").parse().unwrap();
+            let mut runner = FakeRunner {
+                delay: Duration::from_millis(5 * (6 - index)),
+                ..FakeRunner::new("1.0.0", "1.0.0")
+            };
+            checked(check_single_item(item, &mut runner))
+        });
+        let ids: Vec<&str> = results
+            .iter()
+            .map(|result| result.item_id.as_str())
+            .collect();
+        assert_eq!(
+            ids,
+            ["item-0", "item-1", "item-2", "item-3", "item-4", "item-5"]
+        );
+        assert!(results
+            .iter()
+            .all(|result| result.error.is_none() && !result.has_update));
     }
 }
@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use tauri::{AppHandle, Emitter};
+
+use crate::model::{CommandOutputLine, CommandRunFinished};
+use crate::services::shell_runner::LineSink;
+
+pub const OUTPUT_EVENT: &str = "patchpilot://command-output";
+pub const FINISHED_EVENT: &str = "patchpilot://command-finished";
+
+/// Emits every line of a running command as an `OUTPUT_EVENT`.
+pub fn line_sink(app: &AppHandle, run_id: &str) -> LineSink {
+    let app = app.clone();
+    let run_id = run_id.to_string();
+    Arc::new(move |stream: &str, line: &str| {
+        let payload = CommandOutputLine {
+            run_id: run_id.clone(),
+            stream: stream.to_string(),
+            line: line.to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+        };
+        if let Err(error) = app.emit(OUTPUT_EVENT, payload) {
+            eprintln!("failed to emit command output: {error}");
+        }
+    })
+}
+
+pub fn emit_finished(app: &AppHandle, finished: CommandRunFinished) {
+    if let Err(error) = app.emit(FINISHED_EVENT, finished) {
+        eprintln!("failed to emit command completion: {error}");
+    }
+}
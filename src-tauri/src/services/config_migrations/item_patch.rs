@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use crate::model::SoftwareItem;
+use crate::model::{InstalledVersionProbe, SoftwareItem, VersionSource};
 
 const OLD_BREW_CHECK_CMD: &str = "brew outdated --quiet brew";
 const OLD_BREW_UPDATE_CMD: &str = "brew update && brew upgrade brew";
@@ -45,6 +45,82 @@ fn set_string_if_empty(target: &mut String, default: &str) -> bool {
     false
 }
 
+fn command_matches_source(command: &str, source: &VersionSource) -> bool {
+    match source {
+        VersionSource::GithubRelease { repo } => command.contains(&format!("github.com/{repo}")),
+        VersionSource::NpmPackage { package } => command.contains(&format!("npm view {package} ")),
+        VersionSource::HomebrewCask { token } => {
+            command.contains(&format!("brew info --cask {token} "))
+        }
+        VersionSource::HomebrewFormula { name } => command.contains(&format!("brew info {name} ")),
+        VersionSource::Pypi { .. } | VersionSource::CratesIo { .. } => false,
+        VersionSource::JsonUrl { url, .. } | VersionSource::HtmlRegex { url, .. } => {
+            command.contains(url.as_str())
+        }
+    }
+}
+
+fn command_matches_probe(command: &str, probe: &InstalledVersionProbe) -> bool {
+    match probe {
+        InstalledVersionProbe::PlistKey { path, .. } => command.contains(path.as_str()),
+        InstalledVersionProbe::BinaryVersion { binary, .. } => {
+            command.contains(&format!("{binary} --version"))
+                || command.contains(&format!("{binary} version"))
+        }
+        InstalledVersionProbe::NpmGlobalPackage { package, .. } => {
+            command.contains(package.as_str())
+        }
+        InstalledVersionProbe::RustToolchain { .. } => command.contains("rustc --version"),
+    }
+}
+
+fn reset_current_to_default(item: &mut SoftwareItem, default_item: &SoftwareItem) {
+    item.current_version_command = default_item.current_version_command.clone();
+    item.installed_probe = default_item.installed_probe.clone();
+}
+
+/// Same as `adopt_default_version_source`, for the installed-version side.
+fn adopt_default_installed_probe(item: &mut SoftwareItem, default_item: &SoftwareItem) -> bool {
+    let Some(probe) = &default_item.installed_probe else {
+        return false;
+    };
+    if item.installed_probe.is_some() {
+        return false;
+    }
+    let replaceable = match item.current_version_command.as_deref().map(str::trim) {
+        None | Some("") => true,
+        Some(command) => command_matches_probe(command, probe),
+    };
+    if replaceable {
+        reset_current_to_default(item, default_item);
+    }
+    replaceable
+}
+
+fn reset_latest_to_default(item: &mut SoftwareItem, default_item: &SoftwareItem) {
+    item.latest_version_command = default_item.latest_version_command.clone();
+    item.version_source = default_item.version_source.clone();
+}
+
+/// Swaps a catalog shell pipeline for the catalog's typed version source,
+/// leaving user-written latest_version_command values untouched.
+fn adopt_default_version_source(item: &mut SoftwareItem, default_item: &SoftwareItem) -> bool {
+    let Some(source) = &default_item.version_source else {
+        return false;
+    };
+    if item.version_source.is_some() {
+        return false;
+    }
+    let replaceable = match item.latest_version_command.as_deref().map(str::trim) {
+        None | Some("") => true,
+        Some(command) => command_matches_source(command, source),
+    };
+    if replaceable {
+        reset_latest_to_default(item, default_item);
+    }
+    replaceable
+}
+
 pub(super) fn patch_legacy_item_commands(
     item: &mut SoftwareItem,
     default_items: &HashMap<String, SoftwareItem>,
@@ -54,6 +130,9 @@ pub(super) fn patch_legacy_item_commands(
         return false;
     };
 
+    changed |= adopt_default_version_source(item, default_item);
+    changed |= adopt_default_installed_probe(item, default_item);
+
     if item.id == "brew" {
         changed |= set_option_if_missing(
             &mut item.current_version_command,
@@ -79,7 +158,7 @@ pub(super) fn patch_legacy_item_commands(
 
     if item.id == "bun" {
         if item.current_version_command.as_deref() == Some(OLD_BUN_CURRENT_CMD) {
-            item.current_version_command = default_item.current_version_command.clone();
+            reset_current_to_default(item, default_item);
             changed = true;
         } else {
             changed |= set_option_if_missing(
@@ -108,8 +187,8 @@ pub(super) fn patch_legacy_item_commands(
         if looks_legacy {
             item.enabled = default_item.enabled;
             item.description = default_item.description.clone();
-            item.current_version_command = default_item.current_version_command.clone();
-            item.latest_version_command = default_item.latest_version_command.clone();
+            reset_current_to_default(item, default_item);
+            reset_latest_to_default(item, default_item);
             item.update_check_command = default_item.update_check_command.clone();
             item.update_check_regex = default_item.update_check_regex.clone();
             item.update_command = default_item.update_command.clone();
@@ -120,13 +199,13 @@ pub(super) fn patch_legacy_item_commands(
     if item.id == "codex-cli"
         && item.latest_version_command.as_deref() == Some(OLD_CODEX_LATEST_CMD)
     {
-        item.latest_version_command = default_item.latest_version_command.clone();
+        reset_latest_to_default(item, default_item);
         changed = true;
     }
 
     if item.id == "lm-studio" {
         if item.current_version_command.as_deref() == Some(OLD_LMSTUDIO_CURRENT_CMD) {
-            item.current_version_command = default_item.current_version_command.clone();
+            reset_current_to_default(item, default_item);
             changed = true;
         } else {
             changed |= set_option_if_missing(
@@ -147,12 +226,8 @@ pub(super) fn patch_legacy_item_commands(
             &default_item.current_version_command,
         );
         let latest_cmd = item.latest_version_command.as_deref();
-        if latest_cmd
-            .map(|value| value.trim().is_empty())
-            .unwrap_or(true)
-            || latest_cmd == Some(OLD_CHROME_LATEST_CMD)
-        {
-            item.latest_version_command = default_item.latest_version_command.clone();
+        if item.version_source.is_none() && latest_cmd == Some(OLD_CHROME_LATEST_CMD) {
+            reset_latest_to_default(item, default_item);
             changed = true;
         }
         changed |= set_string_if_empty(&mut item.update_command, &default_item.update_command);
@@ -164,10 +239,7 @@ pub(super) fn patch_legacy_item_commands(
             &default_item.current_version_command,
         );
         let latest_cmd = item.latest_version_command.as_deref();
-        if latest_cmd
-            .map(|value| value.trim().is_empty())
-            .unwrap_or(true)
-            || latest_cmd == Some(OLD_GHOSTTY_LATEST_CMD)
+        let legacy_latest = latest_cmd == Some(OLD_GHOSTTY_LATEST_CMD)
             || latest_cmd == Some(PREV_GHOSTTY_LATEST_CMD)
             || latest_cmd == Some(PREV2_GHOSTTY_LATEST_CMD)
             || latest_cmd
@@ -175,9 +247,9 @@ pub(super) fn patch_legacy_item_commands(
                     value.contains("ghostty-org/ghostty/releases")
                         || value.contains("api.github.com/repos/ghostty-org/ghostty/releases")
                 })
-                .unwrap_or(false)
-        {
-            item.latest_version_command = default_item.latest_version_command.clone();
+                .unwrap_or(false);
+        if item.version_source.is_none() && legacy_latest {
+            reset_latest_to_default(item, default_item);
             changed = true;
         }
         changed |= set_string_if_empty(&mut item.update_command, &default_item.update_command);
@@ -255,21 +327,8 @@ pub(super) fn patch_legacy_item_commands(
             &default_item.current_version_command,
         );
         let latest_cmd = item.latest_version_command.as_deref();
-        if latest_cmd
-            .map(|value| value.trim().is_empty())
-            .unwrap_or(true)
-            || latest_cmd == Some(OLD_RUST_LATEST_CMD)
-            || latest_cmd
-                .map(|value| !value.contains("channel-rust-stable.toml"))
-                .unwrap_or(false)
-            || latest_cmd
-                .map(|value| {
-                    value.contains("channel-rust-stable.toml")
-                        && (!value.contains("[pkg\\.rust]") || value.contains("else echo ''; fi"))
-                })
-                .unwrap_or(false)
-        {
-            item.latest_version_command = default_item.latest_version_command.clone();
+        if item.version_source.is_none() && latest_cmd == Some(OLD_RUST_LATEST_CMD) {
+            reset_latest_to_default(item, default_item);
             changed = true;
         }
     }
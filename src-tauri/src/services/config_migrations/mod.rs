@@ -1,53 +1,377 @@
 use std::collections::HashMap;
 
-use crate::model::{AppConfig, SoftwareItem};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::model::{
+    AppConfig, CheckPolicy, ItemGroup, ItemOrigin, MigrationChange, MigrationReport,
+    MigrationStepReport, SoftwareItem, CONFIG_SCHEMA_VERSION,
+};
+use crate::software_catalog;
 
 mod item_patch;
 
 const OLD_DEFAULT_CHECK_INTERVAL_MINUTES: u64 = 360;
 const NEW_DEFAULT_CHECK_INTERVAL_MINUTES: u64 = 480;
 const REMOVED_ITEM_IDS: [&str; 3] = ["chatgpt-atlas", "pencil", "codexskillmanager"];
+/// Items that were hardcoded into the manual section before `check_policy`.
+const LEGACY_MANUAL_ITEM_IDS: [&str; 2] = ["brew", "bun"];
+/// Catalog revision in effect when items started recording their origin.
+const FIRST_TRACKED_CATALOG_REVISION: u32 = 1;
+/// Origin fingerprint of legacy items whose catalog base is unknown. It
+/// matches neither the item nor the catalog, so the item counts as modified
+/// and the catalog's fields are offered as a pending update.
+const UNKNOWN_BASE_FINGERPRINT: &str = "";
 
-fn append_default_items_if_missing(config: &mut AppConfig, default_items: &[SoftwareItem]) -> bool {
-    let mut changed = false;
-    for default_item in default_items {
-        if config.items.iter().any(|item| item.id == default_item.id) {
-            continue;
-        }
-        config.items.push(default_item.clone());
-        changed = true;
-    }
-    changed
+/// One migration, applied once to configs whose `schema_version` is below
+/// `version`. New steps go at the end with the next version, which then
+/// becomes `CONFIG_SCHEMA_VERSION`.
+struct MigrationStep {
+    version: u32,
+    id: &'static str,
+    description: &'static str,
+    apply: fn(&mut AppConfig),
 }
 
-fn remove_disabled_items(config: &mut AppConfig) -> bool {
-    let before = config.items.len();
+const MIGRATIONS: [MigrationStep; 6] = [
+    MigrationStep {
+        version: 1,
+        id: "remove-retired-items",
+        description: "移除已不再支持的软件项",
+        apply: remove_retired_items,
+    },
+    MigrationStep {
+        version: 2,
+        id: "raise-default-interval",
+        description: "将旧的默认检查间隔 6 小时调整为 8 小时",
+        apply: raise_default_interval,
+    },
+    MigrationStep {
+        version: 3,
+        id: "rewrite-legacy-item-commands",
+        description: "将内置软件项的过时检查/更新命令替换为当前默认值",
+        apply: rewrite_legacy_item_commands,
+    },
+    MigrationStep {
+        version: 4,
+        id: "assign-check-policy-and-group",
+        description: "按软件项 id 与 kind 补齐 check_policy 与 group",
+        apply: assign_check_policy_and_group,
+    },
+    MigrationStep {
+        version: 5,
+        id: "add-default-items",
+        description: "补充配置中缺少的内置软件项",
+        apply: add_default_items,
+    },
+    MigrationStep {
+        version: 6,
+        id: "track-catalog-origins",
+        description: "记录内置软件项的来源，之后的目录更新不再覆盖用户修改",
+        apply: track_catalog_origins,
+    },
+];
+
+fn remove_retired_items(config: &mut AppConfig) {
     config
         .items
         .retain(|item| !REMOVED_ITEM_IDS.contains(&item.id.as_str()));
-    config.items.len() != before
 }
 
-pub fn patch_legacy_config(config: &mut AppConfig) -> bool {
-    let default_items = AppConfig::default().items;
-    let default_item_map: HashMap<String, SoftwareItem> = default_items
-        .iter()
-        .cloned()
+fn raise_default_interval(config: &mut AppConfig) {
+    if config.check_interval_minutes == OLD_DEFAULT_CHECK_INTERVAL_MINUTES {
+        config.check_interval_minutes = NEW_DEFAULT_CHECK_INTERVAL_MINUTES;
+    }
+}
+
+fn rewrite_legacy_item_commands(config: &mut AppConfig) {
+    let default_item_map: HashMap<String, SoftwareItem> = AppConfig::default()
+        .items
+        .into_iter()
         .map(|item| (item.id.clone(), item))
         .collect();
+    for item in &mut config.items {
+        item_patch::patch_legacy_item_commands(item, &default_item_map);
+    }
+}
 
-    let mut changed = false;
-    changed |= remove_disabled_items(config);
+/// Fills `check_policy` and `group` the way the item ids and kinds used to
+/// decide them.
+fn assign_check_policy_and_group(config: &mut AppConfig) {
+    for item in &mut config.items {
+        let group = *item.group.get_or_insert(ItemGroup::from_kind(item.kind));
+        if item.check_policy.is_none() {
+            item.check_policy = Some(if LEGACY_MANUAL_ITEM_IDS.contains(&item.id.as_str()) {
+                CheckPolicy::Manual
+            } else {
+                CheckPolicy::default_for(group)
+            });
+        }
+    }
+}
 
-    if config.check_interval_minutes == OLD_DEFAULT_CHECK_INTERVAL_MINUTES {
-        config.check_interval_minutes = NEW_DEFAULT_CHECK_INTERVAL_MINUTES;
-        changed = true;
+fn add_default_items(config: &mut AppConfig) {
+    for default_item in AppConfig::default().items {
+        if !config.items.iter().any(|item| item.id == default_item.id) {
+            config.items.push(default_item);
+        }
     }
+}
 
+/// Existing catalog items that still match the catalog are tracked from
+/// it. For the others it is unknown whether the user or an older catalog
+/// wrote them, so they are never overwritten and the differences are left
+/// for the user to accept or dismiss.
+fn track_catalog_origins(config: &mut AppConfig) {
+    let catalog = AppConfig::default().items;
     for item in &mut config.items {
-        changed |= item_patch::patch_legacy_item_commands(item, &default_item_map);
+        if item.origin.is_some() {
+            continue;
+        }
+        if let Some(catalog_item) = catalog.iter().find(|entry| entry.id == item.id) {
+            let catalog_fingerprint = software_catalog::catalog_fingerprint(catalog_item);
+            let fingerprint = if software_catalog::catalog_fingerprint(item) == catalog_fingerprint
+            {
+                catalog_fingerprint
+            } else {
+                UNKNOWN_BASE_FINGERPRINT.to_string()
+            };
+            item.origin = Some(ItemOrigin {
+                catalog_id: catalog_item.id.clone(),
+                revision: FIRST_TRACKED_CATALOG_REVISION,
+                fingerprint,
+            });
+        }
     }
+    config.catalog_revision = config.catalog_revision.max(FIRST_TRACKED_CATALOG_REVISION);
+}
 
-    changed |= append_default_items_if_missing(config, &default_items);
-    changed
+fn to_fields<T: Serialize>(value: &T) -> Map<String, Value> {
+    match serde_json::to_value(value) {
+        Ok(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    }
+}
+
+fn diff_fields(
+    item_id: Option<&str>,
+    before: &Map<String, Value>,
+    after: &Map<String, Value>,
+    changes: &mut Vec<MigrationChange>,
+) {
+    let keys = before
+        .keys()
+        .chain(after.keys().filter(|key| !before.contains_key(*key)));
+    for key in keys {
+        let (old, new) = (before.get(key), after.get(key));
+        if old != new {
+            changes.push(MigrationChange {
+                item_id: item_id.map(str::to_string),
+                field: Some(key.clone()),
+                before: old.cloned(),
+                after: new.cloned(),
+            });
+        }
+    }
+}
+
+/// Field-level differences between two versions of one item.
+pub fn diff_item(before: &SoftwareItem, after: &SoftwareItem) -> Vec<MigrationChange> {
+    let mut changes = Vec::new();
+    diff_fields(
+        Some(&before.id),
+        &to_fields(before),
+        &to_fields(after),
+        &mut changes,
+    );
+    changes
+}
+
+/// Field-level differences between two configs, per item.
+pub fn diff_config(before: &AppConfig, after: &AppConfig) -> Vec<MigrationChange> {
+    let mut changes = Vec::new();
+    let mut before_settings = to_fields(before);
+    let mut after_settings = to_fields(after);
+    before_settings.remove("items");
+    after_settings.remove("items");
+    diff_fields(None, &before_settings, &after_settings, &mut changes);
+
+    for old in &before.items {
+        match after.items.iter().find(|item| item.id == old.id) {
+            Some(new) => changes.extend(diff_item(old, new)),
+            None => changes.push(MigrationChange {
+                item_id: Some(old.id.clone()),
+                field: None,
+                before: serde_json::to_value(old).ok(),
+                after: None,
+            }),
+        }
+    }
+    for new in &after.items {
+        if !before.items.iter().any(|item| item.id == new.id) {
+            changes.push(MigrationChange {
+                item_id: Some(new.id.clone()),
+                field: None,
+                before: None,
+                after: serde_json::to_value(new).ok(),
+            });
+        }
+    }
+    changes
+}
+
+/// Applies every step newer than `config.schema_version`. Returns `None`
+/// when the config is already current (or written by a newer build).
+pub fn migrate(config: &mut AppConfig) -> Option<MigrationReport> {
+    let from_version = config.schema_version;
+    if from_version >= CONFIG_SCHEMA_VERSION {
+        return None;
+    }
+    let mut steps = Vec::new();
+    for step in MIGRATIONS.iter().filter(|step| step.version > from_version) {
+        let before = config.clone();
+        (step.apply)(config);
+        steps.push(MigrationStepReport {
+            id: step.id.to_string(),
+            description: step.description.to_string(),
+            changes: diff_config(&before, config),
+        });
+    }
+    config.schema_version = CONFIG_SCHEMA_VERSION;
+    Some(MigrationReport {
+        from_version,
+        to_version: CONFIG_SCHEMA_VERSION,
+        backup_path: None,
+        steps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::catalog_sync;
+
+    fn legacy_config() -> AppConfig {
+        let mut config = AppConfig {
+            schema_version: 5,
+            ..AppConfig::default()
+        };
+        for item in &mut config.items {
+            item.origin = None;
+        }
+        config
+    }
+
+    #[test]
+    fn legacy_items_matching_the_catalog_are_tracked_from_it() {
+        let mut config = legacy_config();
+        track_catalog_origins(&mut config);
+
+        assert!(config.items.iter().all(|item| item.origin.is_some()));
+        assert!(catalog_sync::sync(&mut config).is_none());
+        assert!(catalog_sync::pending_updates(&config).is_empty());
+    }
+
+    #[test]
+    fn differing_legacy_items_become_pending_updates() {
+        let mut config = legacy_config();
+        let item_id = config.items[0].id.clone();
+        config.items[0].update_command = "echo custom".to_string();
+        track_catalog_origins(&mut config);
+
+        catalog_sync::sync(&mut config);
+        assert_eq!(config.items[0].update_command, "echo custom");
+        let pending = catalog_sync::pending_updates(&config);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].item_id, item_id);
+        assert!(pending[0]
+            .changes
+            .iter()
+            .any(|change| change.field.as_deref() == Some("update_command")));
+    }
+
+    fn step_ids(report: &MigrationReport) -> Vec<&str> {
+        report.steps.iter().map(|step| step.id.as_str()).collect()
+    }
+
+    #[test]
+    fn current_configs_are_not_migrated() {
+        let mut config = AppConfig::default();
+        assert!(migrate(&mut config).is_none());
+    }
+
+    #[test]
+    fn only_newer_steps_run() {
+        let mut config = AppConfig {
+            schema_version: 2,
+            check_interval_minutes: OLD_DEFAULT_CHECK_INTERVAL_MINUTES,
+            ..AppConfig::default()
+        };
+        let report = migrate(&mut config).unwrap();
+
+        assert_eq!(
+            (report.from_version, report.to_version),
+            (2, CONFIG_SCHEMA_VERSION)
+        );
+        assert_eq!(
+            step_ids(&report),
+            [
+                "rewrite-legacy-item-commands",
+                "assign-check-policy-and-group",
+                "add-default-items",
+                "track-catalog-origins",
+            ]
+        );
+        assert_eq!(config.schema_version, CONFIG_SCHEMA_VERSION);
+        // Step 2 already ran for this config, so a 6 hour interval is kept.
+        assert_eq!(
+            config.check_interval_minutes,
+            OLD_DEFAULT_CHECK_INTERVAL_MINUTES
+        );
+    }
+
+    #[test]
+    fn steps_report_their_changes_per_item() {
+        let mut config = AppConfig {
+            schema_version: 1,
+            check_interval_minutes: OLD_DEFAULT_CHECK_INTERVAL_MINUTES,
+            ..AppConfig::default()
+        };
+        let removed = config.items.remove(0);
+        config.items[0].check_policy = None;
+        let patched_id = config.items[0].id.clone();
+        let report = migrate(&mut config).unwrap();
+
+        let changes = |id: &str| {
+            &report
+                .steps
+                .iter()
+                .find(|step| step.id == id)
+                .expect("step ran")
+                .changes
+        };
+        let interval = changes("raise-default-interval");
+        assert_eq!(interval.len(), 1);
+        assert_eq!(interval[0].item_id, None);
+        assert_eq!(interval[0].field.as_deref(), Some("check_interval_minutes"));
+        assert_eq!(
+            (interval[0].before.clone(), interval[0].after.clone()),
+            (Some(360.into()), Some(480.into()))
+        );
+
+        let policy = changes("assign-check-policy-and-group");
+        assert!(policy.iter().any(|change| {
+            change.item_id.as_deref() == Some(patched_id.as_str())
+                && change.field.as_deref() == Some("check_policy")
+        }));
+
+        let added = changes("add-default-items");
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].item_id.as_deref(), Some(removed.id.as_str()));
+        assert_eq!(
+            (added[0].field.as_deref(), added[0].before.as_ref()),
+            (None, None)
+        );
+        assert!(config.items.iter().any(|item| item.id == removed.id));
+    }
 }
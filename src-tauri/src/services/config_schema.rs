@@ -0,0 +1,30 @@
+use std::fs;
+use std::path::Path;
+
+use schemars::schema_for;
+use serde_json::Value;
+
+use crate::model::AppConfig;
+use crate::services::json_file;
+
+/// Written next to `software-items.json` and referenced from its `$schema`.
+pub const SCHEMA_FILE_NAME: &str = "software-items.schema.json";
+
+/// JSON Schema of `software-items.json`, generated from the config model.
+pub fn config_schema() -> Value {
+    serde_json::to_value(schema_for!(AppConfig)).unwrap_or(Value::Null)
+}
+
+/// Writes the schema beside `config_path`, skipping the write when the file
+/// is already up to date. The write is atomic, so editors watching the file
+/// never read half a schema.
+pub fn write_schema(config_path: &Path) -> Result<(), String> {
+    let path = config_path.with_file_name(SCHEMA_FILE_NAME);
+    let payload = serde_json::to_string_pretty(&config_schema())
+        .map_err(|error| format!("failed to serialize config schema: {error}"))?;
+    if fs::read_to_string(&path).is_ok_and(|existing| existing == payload) {
+        return Ok(());
+    }
+    json_file::write(&path, &payload)
+        .map_err(|error| format!("failed to write config schema: {error}"))
+}
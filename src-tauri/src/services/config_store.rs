@@ -1,75 +1,275 @@
-use std::fs;
-use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
 
-use tauri::AppHandle;
-use tauri::Manager;
+use chrono::Local;
 
-use crate::model::AppConfig;
+use serde::Serialize;
 
-use super::config_migrations;
+use crate::model::{AppConfig, ConfigIssue, MigrationReport};
 
-fn config_path_from_current_dir() -> Option<PathBuf> {
-    let current = std::env::current_dir().ok()?;
-    let direct = current.join("software-items.json");
-    if direct.exists() {
-        return Some(direct);
-    }
+use super::store::{Store, StoreDir, StoreFile};
+use super::{catalog_sync, config_migrations, config_schema, config_validation};
 
-    let nested = current.join("config").join("software-items.json");
-    if nested.exists() {
-        return Some(nested);
-    }
+pub const CONFIG_FILE: StoreFile<'static> = StoreFile::config("software-items.json");
+const BACKUP_PREFIX: &str = "software-items.v";
+const BACKUP_SUFFIX: &str = ".bak.json";
+/// How many pre-migration backups `backup_config` keeps.
+const KEPT_CONFIG_BACKUPS: usize = 10;
 
-    let parent_nested = current.parent()?.join("config").join("software-items.json");
-    if parent_nested.exists() {
-        return Some(parent_nested);
-    }
+/// The report of the last migration, until `load_config` hands it to the UI.
+fn pending_report() -> &'static Mutex<Option<MigrationReport>> {
+    static REPORT: OnceLock<Mutex<Option<MigrationReport>>> = OnceLock::new();
+    REPORT.get_or_init(|| Mutex::new(None))
+}
 
-    None
+/// What `save_config` last wrote, so the watcher can skip the app's own saves.
+fn last_written() -> &'static Mutex<Option<String>> {
+    static LAST_WRITTEN: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+    LAST_WRITTEN.get_or_init(|| Mutex::new(None))
 }
 
-pub fn resolve_config_path(app: &AppHandle) -> Result<PathBuf, String> {
-    if let Some(path) = config_path_from_current_dir() {
-        return Ok(path);
-    }
+pub fn is_own_write(data: &str) -> bool {
+    last_written()
+        .lock()
+        .is_ok_and(|written| written.as_deref() == Some(data))
+}
 
-    let app_dir = app
-        .path()
-        .app_config_dir()
-        .map_err(|error| format!("failed to get app config directory: {error}"))?;
+pub fn take_migration_report() -> Option<MigrationReport> {
+    pending_report()
+        .lock()
+        .ok()
+        .and_then(|mut report| report.take())
+}
+
+/// Orders backups by when they were taken, then by version.
+fn backup_age(name: &str) -> Option<(&str, u32)> {
+    let rest = name
+        .strip_prefix(BACKUP_PREFIX)?
+        .strip_suffix(BACKUP_SUFFIX)?;
+    let (version, taken_at) = rest.split_once('-')?;
+    Some((taken_at, version.parse().ok()?))
+}
+
+/// Removes all but the newest `KEPT_CONFIG_BACKUPS` backups.
+fn prune_backups(store: &dyn Store) -> Result<(), String> {
+    let mut backups: Vec<String> = store
+        .list(StoreDir::Config, BACKUP_PREFIX)?
+        .into_iter()
+        .filter(|name| backup_age(name).is_some())
+        .collect();
+    backups.sort_by(|a, b| backup_age(b).cmp(&backup_age(a)));
+    for name in backups.iter().skip(KEPT_CONFIG_BACKUPS) {
+        store.remove(StoreFile::config(name))?;
+    }
+    Ok(())
+}
 
-    fs::create_dir_all(&app_dir)
-        .map_err(|error| format!("failed to create app config directory: {error}"))?;
+/// Copies the config file as it was before migrating or syncing it.
+fn backup_config(store: &dyn Store, from_version: u32) -> Result<String, String> {
+    let name = format!(
+        "{BACKUP_PREFIX}{from_version}-{}{BACKUP_SUFFIX}",
+        Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let backup = StoreFile::config(&name);
+    store
+        .copy(CONFIG_FILE, backup)
+        .map_err(|error| format!("failed to back up config: {error}"))?;
+    if let Err(error) = prune_backups(store) {
+        eprintln!("failed to prune config backups: {error}");
+    }
+    Ok(store.location(backup))
+}
 
-    Ok(app_dir.join("software-items.json"))
+/// Restores the config from its backup when it no longer parses. Only run at
+/// startup and on an explicit repair, so a hand edit in progress is never
+/// replaced behind the user's back. Returns whether it restored the backup.
+pub fn repair_config(store: &dyn Store) -> Result<bool, String> {
+    if store.read_json_strict::<AppConfig>(CONFIG_FILE).is_ok() {
+        return Ok(false);
+    }
+    store.read_json::<AppConfig>(CONFIG_FILE)?;
+    Ok(true)
 }
 
-pub fn load_or_init_config(app: &AppHandle) -> Result<AppConfig, String> {
-    let path = resolve_config_path(app)?;
-    if !path.exists() {
+/// Loads the config, failing with the parse error if it was broken by hand;
+/// see `repair_config`.
+pub fn load_or_init_config(store: &dyn Store) -> Result<AppConfig, String> {
+    let Some(mut config) = store.read_json_strict::<AppConfig>(CONFIG_FILE)? else {
         let config = AppConfig::default();
-        save_config(app, &config)?;
+        save_config(store, &config)?;
         return Ok(config);
+    };
+
+    let mut report = config_migrations::migrate(&mut config);
+    if let Some(step) = catalog_sync::sync(&mut config) {
+        report
+            .get_or_insert_with(|| MigrationReport {
+                from_version: config.schema_version,
+                to_version: config.schema_version,
+                backup_path: None,
+                steps: Vec::new(),
+            })
+            .steps
+            .push(step);
     }
 
-    let data = fs::read_to_string(&path)
-        .map_err(|error| format!("failed to read config from {}: {error}", path.display()))?;
+    if let Some(mut report) = report {
+        report.backup_path = Some(backup_config(store, report.from_version)?);
+        save_config(store, &config)?;
+        if let Ok(mut pending) = pending_report().lock() {
+            *pending = Some(report);
+        }
+    } else if config.schema.is_none() {
+        save_config(store, &config)?;
+    } else {
+        config_validation::ensure_valid(&config)?;
+    }
 
-    let mut config = serde_json::from_str::<AppConfig>(&data)
-        .map_err(|error| format!("failed to parse config from {}: {error}", path.display()))?;
+    Ok(config)
+}
 
-    if config_migrations::patch_legacy_config(&mut config) {
-        save_config(app, &config)?;
+/// Why `save_config` did not save. Sent to the UI as `{ "kind": ... }`, so
+/// an invalid config arrives with its per-field issues.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConfigSaveError {
+    Invalid { issues: Vec<ConfigIssue> },
+    Failed { message: String },
+}
+
+impl From<String> for ConfigSaveError {
+    fn from(message: String) -> Self {
+        ConfigSaveError::Failed { message }
     }
+}
 
-    Ok(config)
+impl From<ConfigSaveError> for String {
+    fn from(error: ConfigSaveError) -> Self {
+        match error {
+            ConfigSaveError::Invalid { issues } => config_validation::describe(&issues),
+            ConfigSaveError::Failed { message } => message,
+        }
+    }
 }
 
-pub fn save_config(app: &AppHandle, config: &AppConfig) -> Result<(), String> {
-    let path = resolve_config_path(app)?;
-    let payload = serde_json::to_string_pretty(config)
+pub fn save_config(store: &dyn Store, config: &AppConfig) -> Result<(), ConfigSaveError> {
+    let issues = config_validation::validate_config(config);
+    if !issues.is_empty() {
+        return Err(ConfigSaveError::Invalid { issues });
+    }
+    if let Some(paths) = store.paths() {
+        if let Err(error) = paths
+            .config_path()
+            .and_then(|path| config_schema::write_schema(&path))
+        {
+            eprintln!("{error}");
+        }
+    }
+    let config = AppConfig {
+        schema: Some(format!("./{}", config_schema::SCHEMA_FILE_NAME)),
+        ..config.clone()
+    };
+    let payload = serde_json::to_string_pretty(&config)
         .map_err(|error| format!("failed to serialize config: {error}"))?;
 
-    fs::write(&path, payload)
-        .map_err(|error| format!("failed to write config to {}: {error}", path.display()))
+    // Recorded before writing: the watcher may see the change before
+    // `write` returns.
+    if let Ok(mut written) = last_written().lock() {
+        *written = Some(payload.clone());
+    }
+    let result = store.write(CONFIG_FILE, &payload);
+    if result.is_err() {
+        if let Ok(mut written) = last_written().lock() {
+            *written = None;
+        }
+    }
+    result.map_err(ConfigSaveError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::store::MemoryStore;
+
+    #[test]
+    fn first_load_writes_the_default_config() {
+        let store = MemoryStore::new();
+        let config = load_or_init_config(&store).unwrap();
+        assert_eq!(config.items.len(), AppConfig::default().items.len());
+        assert!(store.size(CONFIG_FILE).unwrap().is_some());
+    }
+
+    #[test]
+    fn saved_changes_are_loaded_back() {
+        let store = MemoryStore::new();
+        let mut config = load_or_init_config(&store).unwrap();
+        config.check_interval_minutes = 90;
+        config.items.truncate(1);
+        save_config(&store, &config).unwrap();
+
+        let loaded = load_or_init_config(&store).unwrap();
+        assert_eq!(loaded.check_interval_minutes, 90);
+        assert_eq!(loaded.items.len(), 1);
+    }
+
+    #[test]
+    fn invalid_configs_are_not_saved() {
+        let store = MemoryStore::new();
+        let mut config = load_or_init_config(&store).unwrap();
+        let duplicate = config.items[0].clone();
+        config.items.push(duplicate);
+        let error = save_config(&store, &config).unwrap_err();
+        let ConfigSaveError::Invalid { issues } = error else {
+            panic!("expected validation issues, got {error:?}");
+        };
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("duplicate id"), "{issues:?}");
+        assert_eq!(
+            load_or_init_config(&store).unwrap().items.len(),
+            AppConfig::default().items.len()
+        );
+    }
+
+    #[test]
+    fn broken_configs_are_reported_until_repaired() {
+        let store = MemoryStore::new();
+        load_or_init_config(&store).unwrap();
+        store.write(CONFIG_FILE, "{ broken").unwrap();
+
+        let error = load_or_init_config(&store).unwrap_err();
+        assert!(error.contains("failed to parse"), "{error}");
+        // Loading must not have replaced the edit with the backup.
+        assert!(load_or_init_config(&store).is_err());
+
+        assert!(repair_config(&store).unwrap());
+        assert!(load_or_init_config(&store).is_ok());
+        assert!(!repair_config(&store).unwrap());
+    }
+
+    #[test]
+    fn only_the_newest_backups_are_kept() {
+        let store = MemoryStore::new();
+        let mut names: Vec<String> = (0..KEPT_CONFIG_BACKUPS + 2)
+            .map(|day| {
+                format!(
+                    "{BACKUP_PREFIX}5-202501{:02}-090000{BACKUP_SUFFIX}",
+                    day + 1
+                )
+            })
+            .collect();
+        // Newer than all the others despite sorting first by name.
+        names.push(format!("{BACKUP_PREFIX}10-20250201-090000{BACKUP_SUFFIX}"));
+        for name in &names {
+            store.replace(StoreFile::config(name), "{}").unwrap();
+        }
+        store
+            .replace(StoreFile::config("software-items.json.bak"), "{}")
+            .unwrap();
+
+        prune_backups(&store).unwrap();
+        let kept = store.list(StoreDir::Config, "software-items").unwrap();
+        assert_eq!(kept.len(), KEPT_CONFIG_BACKUPS + 1);
+        assert!(kept.contains(&names[names.len() - 1]));
+        assert!(!kept.contains(&names[0]) && !kept.contains(&names[1]));
+        assert!(kept.contains(&"software-items.json.bak".to_string()));
+    }
 }
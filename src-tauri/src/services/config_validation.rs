@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+
+use chrono::Local;
+use regex::Regex;
+
+use crate::model::{
+    AppConfig, CheckSchedule, ConfigIssue, InstalledVersionProbe, SoftwareKind, VersionSource,
+};
+use crate::services::check_schedule::{self, CronExpr};
+
+fn issue(field: impl Into<String>, message: impl Into<String>) -> ConfigIssue {
+    ConfigIssue {
+        field: field.into(),
+        message: message.into(),
+    }
+}
+
+fn is_blank(value: Option<&str>) -> bool {
+    value.is_none_or(|value| value.trim().is_empty())
+}
+
+fn validate_schedule(schedule: &CheckSchedule, field: &str, issues: &mut Vec<ConfigIssue>) {
+    if schedule.interval_minutes == Some(0) {
+        issues.push(issue(
+            format!("{field}.interval_minutes"),
+            "must be at least 1",
+        ));
+    }
+    if let Some(expression) = schedule.cron.as_deref() {
+        if let Err(error) = CronExpr::parse(expression) {
+            issues.push(issue(format!("{field}.cron"), error));
+        }
+    }
+}
+
+fn validate_regex(pattern: &str, field: String, issues: &mut Vec<ConfigIssue>) {
+    if let Err(error) = Regex::new(pattern) {
+        issues.push(issue(field, format!("invalid regex: {error}")));
+    }
+}
+
+/// Checks what the serde types cannot express. An empty result means the
+/// config is safe to save and run.
+pub fn validate_config(config: &AppConfig) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+    if config.check_interval_minutes == 0 {
+        issues.push(issue("check_interval_minutes", "must be at least 1"));
+    }
+    if config.history_retention_days == 0 {
+        issues.push(issue("history_retention_days", "must be at least 1"));
+    }
+    if config.execution_profile.shell.trim().is_empty() {
+        issues.push(issue("execution_profile.shell", "must not be empty"));
+    }
+    for (group, schedule) in &config.group_schedules {
+        if !matches!(group.as_str(), "cli" | "gui" | "runtime") {
+            issues.push(issue(
+                format!("group_schedules.{group}"),
+                "unknown group; expected cli, gui or runtime",
+            ));
+        }
+        validate_schedule(schedule, &format!("group_schedules.{group}"), &mut issues);
+    }
+    if let Some(quiet) = &config.quiet_hours {
+        if let Err(error) = check_schedule::quiet_hours_end(quiet, Local::now()) {
+            issues.push(issue("quiet_hours", error));
+        }
+    }
+    if config.items.is_empty() {
+        issues.push(issue("items", "must contain at least one item"));
+    }
+
+    let mut seen_ids = HashSet::new();
+    for (index, item) in config.items.iter().enumerate() {
+        let field = |name: &str| format!("items[{index}].{name}");
+        if item.id.trim().is_empty() {
+            issues.push(issue(field("id"), "must not be empty"));
+        } else if !seen_ids.insert(item.id.as_str()) {
+            issues.push(issue(field("id"), format!("duplicate id: {}", item.id)));
+        }
+        if item.kind == SoftwareKind::Unknown {
+            issues.push(issue(
+                field("kind"),
+                "unknown kind; expected cli, gui, app or runtime",
+            ));
+        }
+        if item.update_command.trim().is_empty() {
+            issues.push(issue(field("update_command"), "must not be empty"));
+        }
+        if let Some(pattern) = item.update_check_regex.as_deref() {
+            validate_regex(pattern, field("update_check_regex"), &mut issues);
+        }
+        if let Some(VersionSource::HtmlRegex { pattern, .. }) = &item.version_source {
+            validate_regex(pattern, field("version_source.pattern"), &mut issues);
+        }
+        if let Some(InstalledVersionProbe::BinaryVersion {
+            pattern: Some(pattern),
+            ..
+        }) = &item.installed_probe
+        {
+            validate_regex(pattern, field("installed_probe.pattern"), &mut issues);
+        }
+        let has_check = !is_blank(item.update_check_command.as_deref());
+        let has_latest =
+            item.version_source.is_some() || !is_blank(item.latest_version_command.as_deref());
+        if !has_check && !has_latest {
+            issues.push(issue(
+                field("update_check_command"),
+                "needs update_check_command, latest_version_command or version_source",
+            ));
+        }
+        if let Some(schedule) = &item.schedule {
+            validate_schedule(schedule, &field("schedule"), &mut issues);
+        }
+    }
+    issues
+}
+
+/// `issues` as a single error string, for callers that only report.
+pub fn describe(issues: &[ConfigIssue]) -> String {
+    let details = issues
+        .iter()
+        .map(|issue| format!("{}: {}", issue.field, issue.message))
+        .collect::<Vec<_>>()
+        .join("; ");
+    format!("invalid config: {details}")
+}
+
+/// `validate_config` as a single error string.
+pub fn ensure_valid(config: &AppConfig) -> Result<(), String> {
+    let issues = validate_config(config);
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(describe(&issues))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::ThemeMode;
+
+    fn fields(config: &AppConfig) -> Vec<String> {
+        validate_config(config)
+            .into_iter()
+            .map(|issue| issue.field)
+            .collect()
+    }
+
+    #[test]
+    fn the_default_config_is_valid() {
+        assert!(validate_config(&AppConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn duplicate_ids_and_empty_update_commands_are_reported() {
+        let mut config = AppConfig::default();
+        let mut duplicate = config.items[0].clone();
+        duplicate.update_command = "  ".to_string();
+        config.items.push(duplicate);
+        let last = config.items.len() - 1;
+
+        assert_eq!(
+            fields(&config),
+            [
+                format!("items[{last}].id"),
+                format!("items[{last}].update_command")
+            ]
+        );
+    }
+
+    #[test]
+    fn items_need_a_check_or_latest_source() {
+        let mut config = AppConfig::default();
+        let item = &mut config.items[0];
+        item.update_check_command = None;
+        item.latest_version_command = Some(String::new());
+        item.version_source = None;
+
+        assert_eq!(fields(&config), ["items[0].update_check_command"]);
+    }
+
+    #[test]
+    fn unknown_kinds_are_reported_and_unknown_themes_follow_the_system() {
+        let mut value = serde_json::to_value(AppConfig::default()).unwrap();
+        value["items"][0]["kind"] = json!("GUI");
+        value["theme_mode"] = json!("neon");
+        let config: AppConfig = serde_json::from_value(value).unwrap();
+
+        assert_eq!(config.theme_mode, ThemeMode::System);
+        assert_eq!(fields(&config), ["items[0].kind"]);
+    }
+
+    #[test]
+    fn invalid_source_and_probe_patterns_are_reported() {
+        let mut config = AppConfig::default();
+        config.items[0].version_source = Some(VersionSource::HtmlRegex {
+            url: "https://example.com/releases".to_string(),
+            pattern: "v(\\d+".to_string(),
+        });
+        config.items[0].installed_probe = Some(InstalledVersionProbe::BinaryVersion {
+            binary: "/usr/local/bin/tool".to_string(),
+            args: Vec::new(),
+            pattern: Some("[0-9".to_string()),
+        });
+
+        assert_eq!(
+            fields(&config),
+            [
+                "items[0].version_source.pattern",
+                "items[0].installed_probe.pattern"
+            ]
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use tauri::AppHandle;
+
+use super::config_store;
+use super::store::FileStore;
+
+/// Editors write a save as several events (truncate, write, rename); wait
+/// this long after the last one before reading the file.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// What to do once an external edit was picked up; supplied by the app shell.
+#[derive(Clone, Copy)]
+pub struct ConfigWatchHooks {
+    /// The edited config loaded and validated.
+    pub on_reload: fn(&AppHandle),
+    /// The edited config failed to parse or validate; the file is left as is.
+    pub on_error: fn(&AppHandle, &str),
+}
+
+fn touches_config(event: &notify::Result<Event>, path: &Path) -> bool {
+    let Ok(event) = event else {
+        return false;
+    };
+    !matches!(event.kind, EventKind::Access(_))
+        && event
+            .paths
+            .iter()
+            .any(|changed| changed.file_name() == path.file_name())
+}
+
+fn reload(app: &AppHandle, store: &FileStore, hooks: ConfigWatchHooks, path: &Path) {
+    // Missing while an editor swaps the file in; the next event covers it.
+    let Ok(data) = fs::read_to_string(path) else {
+        return;
+    };
+    if config_store::is_own_write(&data) {
+        return;
+    }
+    match config_store::load_or_init_config(store) {
+        Ok(_) => (hooks.on_reload)(app),
+        Err(error) => (hooks.on_error)(app, &error),
+    }
+}
+
+fn run_loop(
+    app: AppHandle,
+    store: FileStore,
+    hooks: ConfigWatchHooks,
+    path: PathBuf,
+    events: Receiver<notify::Result<Event>>,
+) {
+    while let Ok(event) = events.recv() {
+        if !touches_config(&event, &path) {
+            continue;
+        }
+        while events.recv_timeout(DEBOUNCE).is_ok() {}
+        reload(&app, &store, hooks, &path);
+    }
+}
+
+/// Starts watching the config file for edits made outside the app.
+pub fn start(app: AppHandle, store: FileStore, hooks: ConfigWatchHooks) -> Result<(), String> {
+    let path = store.path(config_store::CONFIG_FILE)?;
+    // Watch the directory: saving by rename replaces the file being watched.
+    let dir = path
+        .parent()
+        .ok_or_else(|| format!("config path has no parent: {}", path.display()))?
+        .to_path_buf();
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)
+        .map_err(|error| format!("failed to create config watcher: {error}"))?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|error| format!("failed to watch {}: {error}", dir.display()))?;
+    thread::spawn(move || {
+        let _watcher = watcher;
+        run_loop(app, store, hooks, path, receiver);
+    });
+    Ok(())
+}
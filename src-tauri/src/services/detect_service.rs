@@ -1,35 +1,37 @@
 use std::collections::HashMap;
-use std::thread;
 
-use crate::model::SoftwareItem;
-use crate::services::shell_runner;
+use crate::model::{ExecutionProfile, SoftwareItem};
+use crate::services::{check_executor, installed_probe, shell_runner};
 
 const DETECT_TIMEOUT_SECONDS: u64 = 10;
 
-fn is_installed(item: &SoftwareItem) -> bool {
+fn is_installed(item: &SoftwareItem, base_profile: &ExecutionProfile) -> bool {
+    let profile = shell_runner::effective_profile(base_profile, item.execution.as_ref());
+    if let Some(probe) = &item.installed_probe {
+        return matches!(
+            installed_probe::probe_installed_version(probe, &profile, DETECT_TIMEOUT_SECONDS),
+            Ok(Some(_))
+        );
+    }
     let cmd = match &item.current_version_command {
         Some(cmd) => cmd,
         None => return true,
     };
-    match shell_runner::run_shell_command(cmd, DETECT_TIMEOUT_SECONDS) {
+    let hooks = shell_runner::RunHooks::default();
+    match shell_runner::run_shell_command_with(&profile, cmd, DETECT_TIMEOUT_SECONDS, hooks) {
         Ok(output) => output.exit_code == 0 && !output.stdout.trim().is_empty(),
         Err(_) => false,
     }
 }
 
-pub fn detect_all(items: &[SoftwareItem]) -> HashMap<String, bool> {
-    thread::scope(|scope| {
-        let handles: Vec<_> = items
-            .iter()
-            .map(|item| {
-                let id = item.id.clone();
-                scope.spawn(move || (id, is_installed(item)))
-            })
-            .collect();
-
-        handles
-            .into_iter()
-            .filter_map(|handle| handle.join().ok())
-            .collect()
+pub fn detect_all(
+    items: &[SoftwareItem],
+    base_profile: &ExecutionProfile,
+    max_parallel: usize,
+) -> HashMap<String, bool> {
+    check_executor::run_bounded(items, max_parallel, |item| {
+        (item.id.clone(), is_installed(item, base_profile))
     })
+    .into_iter()
+    .collect()
 }
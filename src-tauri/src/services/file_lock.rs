@@ -0,0 +1,172 @@
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex, OnceLock};
+
+/// Paths locked by this process, so updates of one file wait for each other
+/// without holding up other files. The advisory lock on the `.lock` file
+/// does the same across app instances.
+struct LockedPaths {
+    paths: Mutex<HashSet<PathBuf>>,
+    released: Condvar,
+}
+
+fn locked_paths() -> &'static LockedPaths {
+    static LOCKED: OnceLock<LockedPaths> = OnceLock::new();
+    LOCKED.get_or_init(|| LockedPaths {
+        paths: Mutex::new(HashSet::new()),
+        released: Condvar::new(),
+    })
+}
+
+/// Held for the duration of a read-modify-write; both locks are released on
+/// drop.
+pub struct StoreLock {
+    path: PathBuf,
+    file: Option<File>,
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        // Unlocks the file before letting the next thread in.
+        self.file.take();
+        let locked = locked_paths();
+        if let Ok(mut paths) = locked.paths.lock() {
+            paths.remove(&self.path);
+        }
+        locked.released.notify_all();
+    }
+}
+
+/// Waits until no other thread holds `path`, then claims it.
+fn claim(path: &Path) -> Result<StoreLock, String> {
+    let locked = locked_paths();
+    let mut paths = locked
+        .paths
+        .lock()
+        .map_err(|_| "store lock poisoned".to_string())?;
+    while paths.contains(path) {
+        paths = locked
+            .released
+            .wait(paths)
+            .map_err(|_| "store lock poisoned".to_string())?;
+    }
+    paths.insert(path.to_path_buf());
+    Ok(StoreLock {
+        path: path.to_path_buf(),
+        file: None,
+    })
+}
+
+#[cfg(unix)]
+fn lock_file(file: &File) -> std::io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    loop {
+        // SAFETY: plain syscall on a descriptor `file` keeps open; the lock
+        // goes away when the descriptor is closed.
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } == 0 {
+            return Ok(());
+        }
+        let error = std::io::Error::last_os_error();
+        if error.kind() != std::io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn lock_file(_file: &File) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Blocks until this process and every other instance are done with `path`.
+pub fn lock(path: &Path) -> Result<StoreLock, String> {
+    let mut lock = claim(path)?;
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    let lock_path = path.with_file_name(name);
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|error| format!("failed to open {}: {error}", lock_path.display()))?;
+    lock_file(&file).map_err(|error| format!("failed to lock {}: {error}", lock_path.display()))?;
+    lock.file = Some(file);
+    Ok(lock)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use chrono::Utc;
+
+    use crate::model::{CheckResult, ExecutionHistoryEntry};
+    use crate::services::storage_paths::StoragePaths;
+    use crate::services::store::{FileStore, Store};
+    use crate::services::{history_store, result_store};
+
+    const THREADS: usize = 4;
+    const WRITES: usize = 8;
+
+    fn entry(id: String) -> ExecutionHistoryEntry {
+        ExecutionHistoryEntry {
+            id,
+            action: "check-item".to_string(),
+            target: "node".to_string(),
+            command: None,
+            stdout: None,
+            stderr: None,
+            recorded_at: Utc::now().to_rfc3339(),
+            success: true,
+            exit_code: None,
+            timed_out: false,
+            cancelled: false,
+            truncated: false,
+            full_output_log: None,
+            duration_ms: None,
+            summary: String::new(),
+        }
+    }
+
+    fn result(item_id: String) -> CheckResult {
+        CheckResult {
+            item_id,
+            checked_at: Utc::now().to_rfc3339(),
+            has_update: false,
+            current_version: Some("1.0".to_string()),
+            latest_version: Some("1.0".to_string()),
+            version_comparison: None,
+            details: String::new(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn concurrent_updates_are_not_lost() {
+        let dir = std::env::temp_dir().join(format!("patchpilot-lock-{}", std::process::id()));
+        let store = FileStore::new(StoragePaths::new(&dir));
+
+        thread::scope(|scope| {
+            for thread in 0..THREADS {
+                let store: &dyn Store = &store;
+                scope.spawn(move || {
+                    for write in 0..WRITES {
+                        history_store::append_entry(store, entry(format!("{thread}-{write}")), 30)
+                            .unwrap();
+                        result_store::upsert_results(store, &[result(format!("{thread}-{write}"))])
+                            .unwrap();
+                    }
+                });
+            }
+        });
+
+        let entries = history_store::load_entries(&store, THREADS * WRITES).unwrap();
+        let results = result_store::load_state(&store).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(entries.len(), THREADS * WRITES);
+        assert_eq!(results.items.len(), THREADS * WRITES);
+    }
+}
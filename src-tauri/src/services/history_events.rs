@@ -1,8 +1,8 @@
 use chrono::Utc;
-use tauri::AppHandle;
 
 use crate::model::{CheckResult, CommandOutput, ExecutionHistoryEntry};
 use crate::services::history_store;
+use crate::services::store::Store;
 
 fn now_rfc3339() -> String {
     Utc::now().to_rfc3339()
@@ -12,13 +12,16 @@ fn next_history_id(action: &str, target: &str) -> String {
     format!("{}-{action}-{target}", Utc::now().timestamp_micros())
 }
 
-pub fn append_entry_safe(app: &AppHandle, entry: ExecutionHistoryEntry) {
-    if let Err(error) = history_store::append_entry(app, entry) {
+/// Appends `entry`; `retention_days` is the config's
+/// `history_retention_days`.
+pub fn append_entry_safe(store: &dyn Store, retention_days: u64, entry: ExecutionHistoryEntry) {
+    if let Err(error) = history_store::append_entry(store, entry, retention_days) {
         eprintln!("failed to append history: {error}");
     }
 }
 
-pub fn check_item_entry(result: &CheckResult) -> ExecutionHistoryEntry {
+/// One item's check, from a single check or as part of a round.
+pub fn check_item_entry(result: &CheckResult, duration_ms: u128) -> ExecutionHistoryEntry {
     let success = result.error.is_none();
     let summary = match (&result.error, result.has_update) {
         (Some(error), _) => error.clone(),
@@ -37,11 +40,35 @@ pub fn check_item_entry(result: &CheckResult) -> ExecutionHistoryEntry {
         success,
         exit_code: None,
         timed_out: false,
-        duration_ms: None,
+        cancelled: false,
+        truncated: false,
+        full_output_log: None,
+        duration_ms: Some(duration_ms),
         summary,
     }
 }
 
+/// A check stopped by `cancel_run`; it has no result of its own.
+pub fn check_item_cancelled_entry(item_id: &str, duration_ms: u128) -> ExecutionHistoryEntry {
+    ExecutionHistoryEntry {
+        id: next_history_id("check-item", item_id),
+        action: "check-item".to_string(),
+        target: item_id.to_string(),
+        command: None,
+        stdout: None,
+        stderr: None,
+        recorded_at: now_rfc3339(),
+        success: false,
+        exit_code: None,
+        timed_out: false,
+        cancelled: true,
+        truncated: false,
+        full_output_log: None,
+        duration_ms: Some(duration_ms),
+        summary: "已取消检查".to_string(),
+    }
+}
+
 pub fn check_all_entry(action: &str, success: bool, summary: String) -> ExecutionHistoryEntry {
     ExecutionHistoryEntry {
         id: next_history_id(action, "enabled-items"),
@@ -54,11 +81,21 @@ pub fn check_all_entry(action: &str, success: bool, summary: String) -> Executio
         success,
         exit_code: None,
         timed_out: false,
+        cancelled: false,
+        truncated: false,
+        full_output_log: None,
         duration_ms: None,
         summary,
     }
 }
 
+pub fn check_all_cancelled_entry(action: &str, summary: String) -> ExecutionHistoryEntry {
+    ExecutionHistoryEntry {
+        cancelled: true,
+        ..check_all_entry(action, false, summary)
+    }
+}
+
 pub fn command_entry(
     action: &str,
     target: &str,
@@ -73,10 +110,41 @@ pub fn command_entry(
         stdout: Some(output.stdout.clone()),
         stderr: Some(output.stderr.clone()),
         recorded_at: now_rfc3339(),
-        success: output.exit_code == 0 && !output.timed_out,
+        success: output.exit_code == 0 && !output.timed_out && !output.cancelled,
         exit_code: Some(output.exit_code),
         timed_out: output.timed_out,
+        cancelled: output.cancelled,
+        truncated: output.truncated,
+        full_output_log: output.full_output_log.clone(),
         duration_ms: Some(output.duration_ms),
         summary,
     }
 }
+
+/// A command that could not be run at all, e.g. because the shell or the
+/// working directory does not exist.
+pub fn command_failed_entry(
+    action: &str,
+    target: &str,
+    command: &str,
+    error: &str,
+    summary: String,
+) -> ExecutionHistoryEntry {
+    ExecutionHistoryEntry {
+        id: next_history_id(action, target),
+        action: action.to_string(),
+        target: target.to_string(),
+        command: Some(command.to_string()),
+        stdout: None,
+        stderr: Some(error.to_string()),
+        recorded_at: now_rfc3339(),
+        success: false,
+        exit_code: None,
+        timed_out: false,
+        cancelled: false,
+        truncated: false,
+        full_output_log: None,
+        duration_ms: None,
+        summary,
+    }
+}
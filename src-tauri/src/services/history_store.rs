@@ -1,53 +1,539 @@
-use std::fs;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
 
-use tauri::AppHandle;
-use tauri::Manager;
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, Utc};
 
-use crate::model::ExecutionHistoryEntry;
+use crate::model::{ExecutionHistoryEntry, HistoryPage, HistoryQuery};
 
-const HISTORY_FILE: &str = "execution-history.json";
-const HISTORY_LIMIT: usize = 200;
+use super::store::{Store, StoreFile};
 
-pub fn history_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_dir = app
-        .path()
-        .app_config_dir()
-        .map_err(|error| format!("failed to get app config directory: {error}"))?;
-    fs::create_dir_all(&app_dir)
-        .map_err(|error| format!("failed to create app config directory: {error}"))?;
-    Ok(app_dir.join(HISTORY_FILE))
+/// Append-only log, one JSON entry per line, oldest first.
+const HISTORY_FILE: StoreFile<'static> = StoreFile::data("execution-history.jsonl");
+/// The capped JSON array used before the log; imported on first use.
+const LEGACY_HISTORY_FILE: StoreFile<'static> = StoreFile::data("execution-history.json");
+const IMPORTED_HISTORY_FILE: StoreFile<'static> =
+    StoreFile::data("execution-history.json.imported");
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+
+fn to_lines<'a>(entries: impl Iterator<Item = &'a ExecutionHistoryEntry>) -> String {
+    entries
+        .filter_map(|entry| serde_json::to_string(entry).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/// Converts `execution-history.json` into the log, then renames it to
+/// `.imported`. Call with the store lock held.
+fn import_legacy(store: &dyn Store) -> Result<(), String> {
+    if store.size(HISTORY_FILE)?.is_some() || store.size(LEGACY_HISTORY_FILE)?.is_none() {
+        return Ok(());
+    }
+    let entries = store
+        .read_json::<Vec<ExecutionHistoryEntry>>(LEGACY_HISTORY_FILE)?
+        .unwrap_or_default();
+    store.replace(HISTORY_FILE, &to_lines(entries.iter().rev()))?;
+    store.rename(LEGACY_HISTORY_FILE, IMPORTED_HISTORY_FILE)
+}
+
+/// Imports the legacy history first if needed.
+fn prepare(store: &dyn Store) -> Result<(), String> {
+    if store.size(HISTORY_FILE)?.is_none() && store.size(LEGACY_HISTORY_FILE)?.is_some() {
+        store.locked(HISTORY_FILE, &mut || import_legacy(store))?;
+    }
+    Ok(())
+}
+
+fn parse_time(raw: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(raw).ok()
+}
+
+/// Drops entries older than `retention_days`.
+fn compact(store: &dyn Store, retention_days: u64) -> Result<(), String> {
+    let Some(data) = store.read(HISTORY_FILE, &|_| true)? else {
+        return Ok(());
+    };
+    let cutoff = Utc::now() - Duration::days(retention_days as i64);
+    let kept = data
+        .lines()
+        .filter(|line| {
+            serde_json::from_str::<ExecutionHistoryEntry>(line)
+                .ok()
+                .and_then(|entry| parse_time(&entry.recorded_at))
+                .is_none_or(|recorded_at| recorded_at >= cutoff)
+        })
+        .map(|line| format!("{line}\n"))
+        .collect::<String>();
+    if kept.len() == data.len() {
+        return Ok(());
+    }
+    store.replace(HISTORY_FILE, &kept)
 }
 
-fn read_all(app: &AppHandle) -> Result<Vec<ExecutionHistoryEntry>, String> {
-    let path = history_path(app)?;
-    if !path.exists() {
-        return Ok(Vec::new());
+/// Runs `compact` at most once a day. Call with the store lock held.
+fn compact_if_due(store: &dyn Store, retention_days: u64) -> Result<(), String> {
+    static LAST_COMPACTED: Mutex<Option<NaiveDate>> = Mutex::new(None);
+    let today = Local::now().date_naive();
+    let mut last = LAST_COMPACTED
+        .lock()
+        .map_err(|_| "history compaction lock poisoned".to_string())?;
+    if *last == Some(today) || store.size(HISTORY_FILE)?.is_none() {
+        return Ok(());
     }
+    *last = Some(today);
+    compact(store, retention_days)
+}
+
+/// Appends `entry`, first dropping entries older than `retention_days`
+/// (the config's `history_retention_days`) once a day.
+pub fn append_entry(
+    store: &dyn Store,
+    entry: ExecutionHistoryEntry,
+    retention_days: u64,
+) -> Result<(), String> {
+    let line = to_lines(std::iter::once(&entry));
+    store.locked(HISTORY_FILE, &mut || {
+        import_legacy(store)?;
+        if let Err(error) = compact_if_due(store, retention_days) {
+            eprintln!("failed to compact history: {error}");
+        }
+        store.append(HISTORY_FILE, &line)
+    })
+}
 
-    let data = fs::read_to_string(&path)
-        .map_err(|error| format!("failed to read history from {}: {error}", path.display()))?;
-    serde_json::from_str::<Vec<ExecutionHistoryEntry>>(&data)
-        .map_err(|error| format!("failed to parse history from {}: {error}", path.display()))
+/// An entry without its `stdout`/`stderr`, which are read back from the
+/// log at `offset` when needed.
+struct IndexedEntry {
+    entry: ExecutionHistoryEntry,
+    offset: u64,
+    has_output: bool,
 }
 
-fn write_all(app: &AppHandle, entries: &[ExecutionHistoryEntry]) -> Result<(), String> {
-    let path = history_path(app)?;
-    let payload = serde_json::to_string_pretty(entries)
-        .map_err(|error| format!("failed to serialize history: {error}"))?;
-    fs::write(&path, payload)
-        .map_err(|error| format!("failed to write history to {}: {error}", path.display()))
+/// The log's metadata in file order plus lookups by id, target and action.
+/// Kept in memory and extended with whatever was appended since the last
+/// query.
+#[derive(Default)]
+struct HistoryIndex {
+    /// `Store::location` of the log indexed.
+    location: String,
+    /// Bytes of the log already indexed.
+    offset: u64,
+    entries: Vec<IndexedEntry>,
+    by_id: HashMap<String, usize>,
+    by_target: HashMap<String, Vec<usize>>,
+    by_action: HashMap<String, Vec<usize>>,
 }
 
-pub fn append_entry(app: &AppHandle, entry: ExecutionHistoryEntry) -> Result<(), String> {
-    let mut entries = read_all(app)?;
-    entries.insert(0, entry);
-    entries.truncate(HISTORY_LIMIT);
-    write_all(app, &entries)
+fn first_line(store: &dyn Store) -> Option<String> {
+    let mut line = String::new();
+    store
+        .read_from(HISTORY_FILE, 0)
+        .ok()??
+        .read_line(&mut line)
+        .ok()?;
+    Some(line)
 }
 
-pub fn load_entries(app: &AppHandle, limit: usize) -> Result<Vec<ExecutionHistoryEntry>, String> {
-    let mut entries = read_all(app)?;
-    entries.truncate(limit);
-    Ok(entries)
+/// The full entry, with its output. Call with the store lock held, so the
+/// log cannot be compacted under the offsets.
+fn load_entry(store: &dyn Store, indexed: &IndexedEntry) -> Result<ExecutionHistoryEntry, String> {
+    if !indexed.has_output {
+        return Ok(indexed.entry.clone());
+    }
+    let mut line = String::new();
+    if let Some(mut reader) = store.read_from(HISTORY_FILE, indexed.offset)? {
+        reader.read_line(&mut line).map_err(|error| {
+            format!("failed to read history entry {}: {error}", indexed.entry.id)
+        })?;
+    }
+    serde_json::from_str::<ExecutionHistoryEntry>(line.trim_end())
+        .ok()
+        .filter(|entry| entry.id == indexed.entry.id)
+        .ok_or_else(|| format!("history entry {} moved; try again", indexed.entry.id))
+}
+
+impl HistoryIndex {
+    fn push(&mut self, entry: ExecutionHistoryEntry, offset: u64) {
+        let position = self.entries.len();
+        self.by_id.insert(entry.id.clone(), position);
+        self.by_target
+            .entry(entry.target.clone())
+            .or_default()
+            .push(position);
+        self.by_action
+            .entry(entry.action.clone())
+            .or_default()
+            .push(position);
+        self.entries.push(IndexedEntry {
+            has_output: entry.stdout.is_some() || entry.stderr.is_some(),
+            entry: ExecutionHistoryEntry {
+                stdout: None,
+                stderr: None,
+                ..entry
+            },
+            offset,
+        });
+    }
+
+    /// Whether the file is still the one indexed, i.e. was only appended to
+    /// since (compaction rewrites it).
+    fn is_current(&self, store: &dyn Store, location: &str, len: u64) -> bool {
+        if self.location != location || len < self.offset {
+            return false;
+        }
+        let Some(first) = self.entries.first() else {
+            return self.offset == 0;
+        };
+        first_line(store)
+            .and_then(|line| serde_json::from_str::<ExecutionHistoryEntry>(&line).ok())
+            .is_some_and(|entry| entry.id == first.entry.id)
+    }
+
+    fn refresh(&mut self, store: &dyn Store) -> Result<(), String> {
+        let location = store.location(HISTORY_FILE);
+        let len = store.size(HISTORY_FILE)?.unwrap_or(0);
+        if !self.is_current(store, &location, len) {
+            *self = HistoryIndex {
+                location: location.clone(),
+                ..HistoryIndex::default()
+            };
+        }
+        if len == self.offset {
+            return Ok(());
+        }
+        let Some(mut reader) = store.read_from(HISTORY_FILE, self.offset)? else {
+            return Ok(());
+        };
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = reader
+                .read_line(&mut line)
+                .map_err(|error| format!("failed to read history from {location}: {error}"))?;
+            // Stop at EOF or at a line still being written.
+            if read == 0 || !line.ends_with('\n') {
+                break;
+            }
+            let offset = self.offset;
+            self.offset += read as u64;
+            match serde_json::from_str::<ExecutionHistoryEntry>(line.trim_end()) {
+                Ok(entry) => self.push(entry, offset),
+                Err(error) => eprintln!("skipping unreadable history line: {error}"),
+            }
+        }
+        Ok(())
+    }
+
+    fn query(&self, store: &dyn Store, query: &HistoryQuery) -> Result<HistoryPage, String> {
+        let limit = query.limit.map_or(DEFAULT_PAGE_SIZE, |limit| {
+            (limit as usize).clamp(1, MAX_PAGE_SIZE)
+        });
+        let end = match &query.cursor {
+            Some(cursor) => *self
+                .by_id
+                .get(cursor)
+                .ok_or_else(|| format!("history cursor is no longer valid: {cursor}"))?,
+            None => self.entries.len(),
+        };
+        let since = query
+            .since
+            .as_deref()
+            .map(|raw| parse_time(raw).ok_or_else(|| format!("invalid since: {raw}")))
+            .transpose()?;
+        let until = query
+            .until
+            .as_deref()
+            .map(|raw| parse_time(raw).ok_or_else(|| format!("invalid until: {raw}")))
+            .transpose()?;
+        let text = query.text.as_deref().map(str::to_lowercase);
+
+        let positions: Box<dyn Iterator<Item = usize> + '_> =
+            match (query.target.as_ref(), query.action.as_ref()) {
+                (Some(target), _) => Box::new(
+                    self.by_target
+                        .get(target)
+                        .into_iter()
+                        .flat_map(|positions| positions.iter().rev().copied()),
+                ),
+                (None, Some(action)) => Box::new(
+                    self.by_action
+                        .get(action)
+                        .into_iter()
+                        .flat_map(|positions| positions.iter().rev().copied()),
+                ),
+                (None, None) => Box::new((0..self.entries.len()).rev()),
+            };
+        let candidates = positions
+            .filter(|position| *position < end)
+            .map(|position| &self.entries[position])
+            .filter(|indexed| {
+                let entry = &indexed.entry;
+                query
+                    .action
+                    .as_ref()
+                    .is_none_or(|action| &entry.action == action)
+                    && query.success.is_none_or(|success| entry.success == success)
+            })
+            .filter(|indexed| {
+                if since.is_none() && until.is_none() {
+                    return true;
+                }
+                parse_time(&indexed.entry.recorded_at).is_some_and(|recorded_at| {
+                    since.is_none_or(|since| recorded_at >= since)
+                        && until.is_none_or(|until| recorded_at < until)
+                })
+            });
+
+        let mut entries: Vec<ExecutionHistoryEntry> = Vec::new();
+        let mut next_cursor = None;
+        for indexed in candidates {
+            // Output is only read for entries the summary did not match.
+            let summary_matches = text
+                .as_deref()
+                .is_none_or(|text| indexed.entry.summary.to_lowercase().contains(text));
+            if !summary_matches && !indexed.has_output {
+                continue;
+            }
+            let entry = load_entry(store, indexed)?;
+            let matches = summary_matches
+                || text.as_deref().is_some_and(|text| {
+                    [entry.stdout.as_ref(), entry.stderr.as_ref()]
+                        .into_iter()
+                        .flatten()
+                        .any(|field| field.to_lowercase().contains(text))
+                });
+            if !matches {
+                continue;
+            }
+            if entries.len() == limit {
+                next_cursor = entries.last().map(|entry| entry.id.clone());
+                break;
+            }
+            entries.push(entry);
+        }
+        Ok(HistoryPage {
+            entries,
+            next_cursor,
+        })
+    }
+}
+
+fn index() -> &'static Mutex<HistoryIndex> {
+    static INDEX: OnceLock<Mutex<HistoryIndex>> = OnceLock::new();
+    INDEX.get_or_init(|| Mutex::new(HistoryIndex::default()))
+}
+
+pub fn query(store: &dyn Store, query: &HistoryQuery) -> Result<HistoryPage, String> {
+    prepare(store)?;
+    let mut index = index()
+        .lock()
+        .map_err(|_| "history index lock poisoned".to_string())?;
+    // Outputs are read back by offset, so compaction must wait.
+    let mut page = None;
+    store.locked(HISTORY_FILE, &mut || {
+        index.refresh(store)?;
+        page = Some(index.query(store, query)?);
+        Ok(())
+    })?;
+    page.ok_or_else(|| "history query did not run".to_string())
+}
+
+/// Every entry recorded in `[since, until)`, newest first.
+pub fn entries_between(
+    store: &dyn Store,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Result<Vec<ExecutionHistoryEntry>, String> {
+    let mut entries = Vec::new();
+    let mut page_query = HistoryQuery {
+        since: Some(since.to_rfc3339()),
+        until: Some(until.to_rfc3339()),
+        limit: Some(MAX_PAGE_SIZE as u32),
+        ..HistoryQuery::default()
+    };
+    loop {
+        let page = query(store, &page_query)?;
+        entries.extend(page.entries);
+        match page.next_cursor {
+            Some(cursor) => page_query.cursor = Some(cursor),
+            None => return Ok(entries),
+        }
+    }
+}
+
+pub fn load_entries(store: &dyn Store, limit: usize) -> Result<Vec<ExecutionHistoryEntry>, String> {
+    let page = query(
+        store,
+        &HistoryQuery {
+            limit: Some(limit as u32),
+            ..HistoryQuery::default()
+        },
+    )?;
+    Ok(page.entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::store::MemoryStore;
+
+    fn entry(id: &str, action: &str, target: &str, success: bool) -> ExecutionHistoryEntry {
+        ExecutionHistoryEntry {
+            id: id.to_string(),
+            action: action.to_string(),
+            target: target.to_string(),
+            command: None,
+            stdout: None,
+            stderr: None,
+            recorded_at: Utc::now().to_rfc3339(),
+            success,
+            exit_code: None,
+            timed_out: false,
+            cancelled: false,
+            truncated: false,
+            full_output_log: None,
+            duration_ms: None,
+            summary: format!("{action} {target}"),
+        }
+    }
+
+    fn ids(entries: &[ExecutionHistoryEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.id.as_str()).collect()
+    }
+
+    fn run(store: &dyn Store, query: HistoryQuery) -> HistoryPage {
+        super::query(store, &query).unwrap()
+    }
+
+    #[test]
+    fn queries_are_newest_first_and_filtered() {
+        let store = MemoryStore::new();
+        append_entry(&store, entry("1", "check-item", "node", true), 30).unwrap();
+        append_entry(&store, entry("2", "check-item", "go", false), 30).unwrap();
+        append_entry(&store, entry("3", "run-item-update", "node", true), 30).unwrap();
+        append_entry(&store, entry("4", "check-item", "node", false), 30).unwrap();
+
+        assert_eq!(
+            ids(&load_entries(&store, 10).unwrap()),
+            ["4", "3", "2", "1"]
+        );
+        let by_target = HistoryQuery {
+            target: Some("node".to_string()),
+            ..HistoryQuery::default()
+        };
+        assert_eq!(
+            ids(&run(&store, by_target.clone()).entries),
+            ["4", "3", "1"]
+        );
+        let checks = HistoryQuery {
+            action: Some("check-item".to_string()),
+            ..by_target
+        };
+        assert_eq!(ids(&run(&store, checks).entries), ["4", "1"]);
+        let failed = HistoryQuery {
+            success: Some(false),
+            ..HistoryQuery::default()
+        };
+        assert_eq!(ids(&run(&store, failed).entries), ["4", "2"]);
+        let text = HistoryQuery {
+            text: Some("UPDATE".to_string()),
+            ..HistoryQuery::default()
+        };
+        assert_eq!(ids(&run(&store, text).entries), ["3"]);
+    }
+
+    #[test]
+    fn pages_follow_the_cursor() {
+        let store = MemoryStore::new();
+        for id in 1..=5 {
+            append_entry(
+                &store,
+                entry(&id.to_string(), "check-item", "node", true),
+                30,
+            )
+            .unwrap();
+        }
+
+        let mut query = HistoryQuery {
+            limit: Some(2),
+            ..HistoryQuery::default()
+        };
+        let mut pages = Vec::new();
+        loop {
+            let page = run(&store, query.clone());
+            pages.push(ids(&page.entries).join(","));
+            match page.next_cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(pages, ["5,4", "3,2", "1"]);
+    }
+
+    #[test]
+    fn imports_the_legacy_history() {
+        let memory = MemoryStore::new();
+        let store: &dyn Store = &memory;
+        let legacy = vec![
+            entry("new", "check-all", "enabled-items", true),
+            entry("old", "check-item", "node", true),
+        ];
+        store.write_json(LEGACY_HISTORY_FILE, &legacy).unwrap();
+
+        assert_eq!(ids(&load_entries(store, 10).unwrap()), ["new", "old"]);
+        assert!(store.size(LEGACY_HISTORY_FILE).unwrap().is_none());
+        assert!(store.size(IMPORTED_HISTORY_FILE).unwrap().is_some());
+
+        append_entry(store, entry("next", "check-item", "go", true), 30).unwrap();
+        assert_eq!(
+            ids(&load_entries(store, 10).unwrap()),
+            ["next", "new", "old"]
+        );
+    }
+
+    #[test]
+    fn outputs_are_read_back_from_the_log() {
+        let store = MemoryStore::new();
+        let update = ExecutionHistoryEntry {
+            stdout: Some("==> Upgrading node 22.1.0".to_string()),
+            stderr: Some("Warning: already installed".to_string()),
+            ..entry("1", "run-item-update", "node", true)
+        };
+        append_entry(&store, update, 30).unwrap();
+        append_entry(&store, entry("2", "check-item", "node", true), 30).unwrap();
+
+        let entries = load_entries(&store, 10).unwrap();
+        assert_eq!(entries[0].stdout, None);
+        assert_eq!(
+            entries[1].stdout.as_deref(),
+            Some("==> Upgrading node 22.1.0")
+        );
+        let text = HistoryQuery {
+            text: Some("already INSTALLED".to_string()),
+            ..HistoryQuery::default()
+        };
+        assert_eq!(ids(&run(&store, text).entries), ["1"]);
+    }
+
+    #[test]
+    fn compaction_keeps_the_retention_window() {
+        let memory = MemoryStore::new();
+        let store: &dyn Store = &memory;
+        let old = ExecutionHistoryEntry {
+            recorded_at: (Utc::now() - Duration::days(40)).to_rfc3339(),
+            ..entry("old", "check-item", "node", true)
+        };
+        let recent = ExecutionHistoryEntry {
+            recorded_at: (Utc::now() - Duration::days(20)).to_rfc3339(),
+            ..entry("recent", "check-item", "node", true)
+        };
+        store
+            .replace(HISTORY_FILE, &to_lines([&old, &recent].into_iter()))
+            .unwrap();
+
+        compact(store, 30).unwrap();
+        assert_eq!(ids(&load_entries(store, 10).unwrap()), ["recent"]);
+        compact(store, 10).unwrap();
+        assert!(load_entries(store, 10).unwrap().is_empty());
+    }
 }
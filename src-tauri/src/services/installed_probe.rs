@@ -0,0 +1,353 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::model::{ExecutionProfile, InstalledVersionProbe};
+use crate::services::shell_runner;
+
+const DEFAULT_VERSION_PATTERN: &str = r"(\d+\.\d+(?:\.\d+)*(?:[-+][0-9A-Za-z.]+)?)";
+const NPM_FALLBACK_PREFIXES: [&str; 3] = ["/opt/homebrew", "/usr/local", "~/.npm-global"];
+const RUST_MANIFEST: &str = "lib/rustlib/multirust-channel-manifest.toml";
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(PathBuf::from)
+}
+
+fn expand_home(raw: &str) -> PathBuf {
+    match (raw.strip_prefix("~/"), home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(raw),
+    }
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+fn read_plist_key(path: &str, key: &str) -> Result<Option<String>, String> {
+    let target = expand_home(path);
+    let plist_path = if target.extension().is_some_and(|ext| ext == "app") {
+        target.join("Contents").join("Info.plist")
+    } else {
+        target
+    };
+    if !plist_path.exists() {
+        return Ok(None);
+    }
+
+    let value = plist::Value::from_file(&plist_path)
+        .map_err(|error| format!("failed to read {}: {error}", plist_path.display()))?;
+    let dict = value
+        .as_dictionary()
+        .ok_or_else(|| format!("{} is not a plist dictionary", plist_path.display()))?;
+    Ok(dict
+        .get(key)
+        .and_then(plist::Value::as_string)
+        .and_then(non_empty))
+}
+
+fn default_version_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(DEFAULT_VERSION_PATTERN).expect("valid version pattern"))
+}
+
+fn extract_version(text: &str, regex: &Regex) -> Option<String> {
+    let captures = regex.captures(text)?;
+    let matched = captures.get(1).or_else(|| captures.get(0))?;
+    non_empty(matched.as_str())
+}
+
+fn run_binary_version(
+    profile: &ExecutionProfile,
+    binary: &str,
+    args: &[String],
+    pattern: Option<&str>,
+    timeout_seconds: u64,
+) -> Result<Option<String>, String> {
+    // A binary that cannot be spawned is treated as "not installed".
+    let Ok(output) = shell_runner::run_program(profile, binary, args, timeout_seconds) else {
+        return Ok(None);
+    };
+    if output.exit_code != 0 {
+        return Err(format!(
+            "{} exited with {}: {}",
+            output.command,
+            output.exit_code,
+            output.stderr.trim()
+        ));
+    }
+
+    let custom;
+    let regex = match pattern {
+        Some(pattern) => {
+            custom = Regex::new(pattern)
+                .map_err(|error| format!("invalid probe pattern for {binary}: {error}"))?;
+            &custom
+        }
+        None => default_version_regex(),
+    };
+    extract_version(&output.stdout, regex)
+        .or_else(|| extract_version(&output.stderr, regex))
+        .map(Some)
+        .ok_or_else(|| format!("no version found in output of {}", output.command))
+}
+
+fn npmrc_prefix() -> Option<String> {
+    let data = fs::read_to_string(home_dir()?.join(".npmrc")).ok()?;
+    data.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        if key.trim() == "prefix" {
+            non_empty(value)
+        } else {
+            None
+        }
+    })
+}
+
+fn npm_prefixes(explicit: Option<&str>) -> Vec<PathBuf> {
+    let mut prefixes: Vec<String> = Vec::new();
+    prefixes.extend(explicit.map(str::to_string));
+    prefixes.extend(env::var("NPM_CONFIG_PREFIX").ok());
+    prefixes.extend(env::var("npm_config_prefix").ok());
+    prefixes.extend(npmrc_prefix());
+    prefixes.extend(NPM_FALLBACK_PREFIXES.iter().map(|value| value.to_string()));
+    prefixes
+        .iter()
+        .filter_map(|value| non_empty(value))
+        .map(|value| expand_home(&value))
+        .collect()
+}
+
+fn read_npm_package_version(package: &str, prefix: Option<&str>) -> Result<Option<String>, String> {
+    let Some(manifest) = npm_prefixes(prefix)
+        .into_iter()
+        .map(|prefix| {
+            prefix
+                .join("lib")
+                .join("node_modules")
+                .join(package)
+                .join("package.json")
+        })
+        .find(|path| path.exists())
+    else {
+        return Ok(None);
+    };
+
+    let data = fs::read_to_string(&manifest)
+        .map_err(|error| format!("failed to read {}: {error}", manifest.display()))?;
+    let document = serde_json::from_str::<Value>(&data)
+        .map_err(|error| format!("failed to parse {}: {error}", manifest.display()))?;
+    Ok(document
+        .get("version")
+        .and_then(Value::as_str)
+        .and_then(non_empty))
+}
+
+fn rustup_home() -> Option<PathBuf> {
+    env::var_os("RUSTUP_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".rustup")))
+}
+
+/// The triple rustup names host toolchains after, e.g. `aarch64-apple-darwin`.
+fn host_triple() -> String {
+    let arch = env::consts::ARCH;
+    match env::consts::OS {
+        "macos" => format!("{arch}-apple-darwin"),
+        "windows" => format!("{arch}-pc-windows-msvc"),
+        os => format!("{arch}-unknown-{os}-gnu"),
+    }
+}
+
+/// Picks `toolchain` itself, else `<toolchain>-<host>`, else the first
+/// `<toolchain>-*` by name, so the choice does not depend on directory order.
+fn find_toolchain_dir(toolchains: &Path, toolchain: &str, host: &str) -> Option<PathBuf> {
+    let prefix = format!("{toolchain}-");
+    let host_name = format!("{prefix}{host}");
+    let mut names: Vec<String> = fs::read_dir(toolchains)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name == toolchain || name.starts_with(&prefix))
+        .collect();
+    names.sort();
+    let name = names
+        .iter()
+        .find(|name| *name == toolchain)
+        .or_else(|| names.iter().find(|name| **name == host_name))
+        .or_else(|| names.first())?;
+    Some(toolchains.join(name))
+}
+
+fn read_toolchain_manifest(dir: &Path) -> Result<Option<String>, String> {
+    let manifest = dir.join(RUST_MANIFEST);
+    if !manifest.exists() {
+        return Ok(None);
+    }
+
+    let data = fs::read_to_string(&manifest)
+        .map_err(|error| format!("failed to read {}: {error}", manifest.display()))?;
+    let regex = Regex::new(r#"\[pkg\.rust\]\s+version = "([^" ]+)"#)
+        .map_err(|error| format!("invalid toolchain manifest pattern: {error}"))?;
+    Ok(extract_version(&data, &regex))
+}
+
+fn read_rust_toolchain_version(toolchain: &str) -> Result<Option<String>, String> {
+    let Some(home) = rustup_home() else {
+        return Ok(None);
+    };
+    match find_toolchain_dir(&home.join("toolchains"), toolchain, &host_triple()) {
+        Some(dir) => read_toolchain_manifest(&dir),
+        None => Ok(None),
+    }
+}
+
+/// Reads the installed version without going through a login shell.
+/// `Ok(None)` means the software is not installed.
+pub fn probe_installed_version(
+    probe: &InstalledVersionProbe,
+    profile: &ExecutionProfile,
+    timeout_seconds: u64,
+) -> Result<Option<String>, String> {
+    match probe {
+        InstalledVersionProbe::PlistKey { path, key } => read_plist_key(path, key),
+        InstalledVersionProbe::BinaryVersion {
+            binary,
+            args,
+            pattern,
+        } => run_binary_version(profile, binary, args, pattern.as_deref(), timeout_seconds),
+        InstalledVersionProbe::NpmGlobalPackage { package, prefix } => {
+            read_npm_package_version(package, prefix.as_deref())
+        }
+        InstalledVersionProbe::RustToolchain { toolchain } => {
+            read_rust_toolchain_version(toolchain)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("patchpilot-probe-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn reads_plist_keys_from_apps_and_plist_files() {
+        let dir = fixture_dir("plist");
+        let plist = r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0"><dict>
+  <key>CFBundleShortVersionString</key><string> 1.95.3 </string>
+  <key>CFBundleVersion</key><string></string>
+</dict></plist>"#;
+        write(&dir.join("Code.app/Contents/Info.plist"), plist);
+        write(&dir.join("Other.plist"), plist);
+        let app = dir.join("Code.app").to_string_lossy().to_string();
+        let file = dir.join("Other.plist").to_string_lossy().to_string();
+        let missing = dir.join("Missing.app").to_string_lossy().to_string();
+
+        let version = read_plist_key(&app, "CFBundleShortVersionString").unwrap();
+        assert_eq!(version.as_deref(), Some("1.95.3"));
+        let version = read_plist_key(&file, "CFBundleShortVersionString").unwrap();
+        assert_eq!(version.as_deref(), Some("1.95.3"));
+        assert_eq!(read_plist_key(&app, "CFBundleVersion").unwrap(), None);
+        assert_eq!(read_plist_key(&missing, "CFBundleVersion").unwrap(), None);
+
+        write(&dir.join("Broken.plist"), "not a plist");
+        let broken = dir.join("Broken.plist").to_string_lossy().to_string();
+        assert!(read_plist_key(&broken, "CFBundleVersion").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reads_npm_packages_under_the_explicit_prefix() {
+        let dir = fixture_dir("npm");
+        write(
+            &dir.join("lib/node_modules/@scope/cli/package.json"),
+            r#"{ "name": "@scope/cli", "version": "2.4.1" }"#,
+        );
+        write(
+            &dir.join("lib/node_modules/broken/package.json"),
+            "{ broken",
+        );
+        let prefix = dir.to_string_lossy().to_string();
+
+        let version = read_npm_package_version("@scope/cli", Some(&prefix)).unwrap();
+        assert_eq!(version.as_deref(), Some("2.4.1"));
+        assert!(read_npm_package_version("broken", Some(&prefix)).is_err());
+        let missing = read_npm_package_version("patchpilot-missing-fixture", Some(&prefix));
+        assert_eq!(missing.unwrap(), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn picks_the_host_toolchain_deterministically() {
+        let dir = fixture_dir("toolchains");
+        for name in [
+            "stable-x86_64-unknown-linux-gnu",
+            "stable-aarch64-apple-darwin",
+            "nightly-aarch64-apple-darwin",
+            "1.80.0",
+            "1.80.0-aarch64-apple-darwin",
+        ] {
+            fs::create_dir_all(dir.join(name)).unwrap();
+        }
+
+        let pick = |toolchain: &str, host: &str| {
+            find_toolchain_dir(&dir, toolchain, host)
+                .and_then(|path| path.file_name()?.to_str().map(str::to_string))
+        };
+        assert_eq!(
+            pick("stable", "x86_64-unknown-linux-gnu").as_deref(),
+            Some("stable-x86_64-unknown-linux-gnu")
+        );
+        assert_eq!(
+            pick("stable", "aarch64-apple-darwin").as_deref(),
+            Some("stable-aarch64-apple-darwin")
+        );
+        // No toolchain for this host: the first by name, every time.
+        assert_eq!(
+            pick("stable", "riscv64gc-unknown-linux-gnu").as_deref(),
+            Some("stable-aarch64-apple-darwin")
+        );
+        assert_eq!(
+            pick("1.80.0", "aarch64-apple-darwin").as_deref(),
+            Some("1.80.0")
+        );
+        assert_eq!(pick("beta", "aarch64-apple-darwin"), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reads_the_rust_version_from_the_toolchain_manifest() {
+        let dir = fixture_dir("manifest");
+        assert_eq!(read_toolchain_manifest(&dir).unwrap(), None);
+        write(
+            &dir.join(RUST_MANIFEST),
+            "manifest-version = \"2\"\n\n[pkg.cargo]\nversion = \"0.82.0 (8f40fc59f 2024-08-21)\"\n\n[pkg.rust]\nversion = \"1.81.0 (eeb90cda1 2024-09-04)\"\n",
+        );
+        assert_eq!(
+            read_toolchain_manifest(&dir).unwrap().as_deref(),
+            Some("1.81.0")
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,107 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::Local;
+
+/// `<file>.bak`: the version replaced by the last successful write.
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, "bak")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{suffix}"));
+    path.with_file_name(name)
+}
+
+/// Unique per write, so concurrent writers never share a temp file.
+fn temp_path(path: &Path) -> PathBuf {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let sequence = COUNTER.fetch_add(1, Ordering::Relaxed);
+    with_suffix(path, &format!("{}-{sequence}.tmp", std::process::id()))
+}
+
+fn sync_dir(path: &Path) {
+    // Persists the renames; not supported (nor needed) on every platform.
+    if let Some(dir) = path.parent() {
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+}
+
+fn write_temp(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()
+}
+
+/// Replaces `path` with `contents` so a crash leaves either the old or the
+/// new file, never a truncated one.
+pub fn replace(path: &Path, contents: &str) -> Result<(), String> {
+    let temp = temp_path(path);
+    if let Err(error) = write_temp(&temp, contents) {
+        let _ = fs::remove_file(&temp);
+        return Err(format!("failed to write {}: {error}", temp.display()));
+    }
+    fs::rename(&temp, path)
+        .map_err(|error| format!("failed to replace {}: {error}", path.display()))?;
+    sync_dir(path);
+    Ok(())
+}
+
+/// Like `replace`, first copying the old file to `<file>.bak`.
+pub fn write(path: &Path, contents: &str) -> Result<(), String> {
+    if path.exists() {
+        let backup = backup_path(path);
+        if let Err(error) = fs::copy(path, &backup) {
+            eprintln!(
+                "failed to back up {} to {}: {error}",
+                path.display(),
+                backup.display()
+            );
+        }
+    }
+    replace(path, contents)
+}
+
+fn read_text(path: &Path) -> Result<Option<String>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    fs::read_to_string(path)
+        .map(Some)
+        .map_err(|error| format!("failed to read {}: {error}", path.display()))
+}
+
+/// Reads `path`, or `None` when it does not exist. When `valid` rejects the
+/// contents but accepts `<file>.bak`, the backup is restored and the broken
+/// file is kept as `<file>.corrupt-<time>`.
+pub fn read(path: &Path, valid: &dyn Fn(&str) -> bool) -> Result<Option<String>, String> {
+    let Some(data) = read_text(path)? else {
+        return Ok(None);
+    };
+    if valid(&data) {
+        return Ok(Some(data));
+    }
+    let backup = backup_path(path);
+    let restored = match read_text(&backup) {
+        Ok(Some(restored)) if valid(&restored) => restored,
+        _ => return Ok(Some(data)),
+    };
+    let aside = with_suffix(
+        path,
+        &format!("corrupt-{}", Local::now().format("%Y%m%d-%H%M%S")),
+    );
+    fs::rename(path, &aside)
+        .map_err(|error| format!("failed to move aside {}: {error}", path.display()))?;
+    write(path, &restored)?;
+    eprintln!(
+        "{} is damaged; restored from {}",
+        path.display(),
+        backup.display()
+    );
+    Ok(Some(restored))
+}
@@ -1,9 +1,33 @@
+pub mod catalog_sync;
 pub mod check_all_guard;
+pub mod check_executor;
+pub mod check_schedule;
 pub mod check_service;
+#[cfg(feature = "app")]
+pub mod command_stream;
 mod config_migrations;
+pub mod config_schema;
 pub mod config_store;
+pub mod config_validation;
+#[cfg(feature = "app")]
+pub mod config_watcher;
 pub mod detect_service;
+pub mod file_lock;
 pub mod history_events;
 pub mod history_store;
+pub mod installed_probe;
+pub mod json_file;
+pub mod operations;
+pub mod output_capture;
+pub mod report;
 pub mod result_store;
+pub mod run_log_store;
+pub mod run_registry;
+#[cfg(feature = "app")]
+pub mod scheduler;
 pub mod shell_runner;
+pub mod storage_paths;
+pub mod store;
+pub mod version_compare;
+pub mod version_source;
+pub mod version_timeline;
@@ -0,0 +1,417 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::model::{
+    AppConfig, CheckPolicy, CheckResult, CommandOutput, ExecutionHistoryEntry, ExecutionProfile,
+    ItemGroup, SoftwareItem,
+};
+use crate::services::check_service::CheckOutcome;
+use crate::services::run_registry::{CancelToken, RunHandle};
+use crate::services::shell_runner::{LineSink, OutputLimits, RunHooks};
+use crate::services::store::Store;
+use crate::services::{
+    check_all_guard, check_executor, check_service, config_store, history_events, result_store,
+    run_log_store, shell_runner,
+};
+
+pub fn default_timeout_seconds(config: &AppConfig) -> u64 {
+    config.command_timeout_seconds.max(1)
+}
+
+pub fn max_parallel_checks(config: &AppConfig) -> usize {
+    config.max_parallel_checks.max(1)
+}
+
+fn max_output_bytes(config: &AppConfig) -> usize {
+    config.max_output_bytes.max(1)
+}
+
+fn item_profile(config: &AppConfig, item: &SoftwareItem) -> ExecutionProfile {
+    shell_runner::effective_profile(&config.execution_profile, item.execution.as_ref())
+}
+
+pub fn find_item<'a>(config: &'a AppConfig, item_id: &str) -> Option<&'a SoftwareItem> {
+    config.items.iter().find(|item| item.id == item_id)
+}
+
+/// The manual section: manual-policy items outside the runtime group, which
+/// has a section of its own.
+pub fn is_manual_item(item: &SoftwareItem) -> bool {
+    item.enabled && item.policy() == CheckPolicy::Manual && item.group() != ItemGroup::Runtime
+}
+
+pub fn is_auto_item(item: &SoftwareItem) -> bool {
+    item.enabled && item.policy() == CheckPolicy::Auto
+}
+
+pub fn is_auto_cli_item(item: &SoftwareItem) -> bool {
+    is_auto_item(item) && item.group() == ItemGroup::Cli
+}
+
+pub fn is_auto_app_item(item: &SoftwareItem) -> bool {
+    is_auto_item(item) && item.group() == ItemGroup::Gui
+}
+
+pub fn is_runtime_item(item: &SoftwareItem) -> bool {
+    item.enabled && item.policy() != CheckPolicy::Disabled && item.group() == ItemGroup::Runtime
+}
+
+pub fn check_item(store: &dyn Store, item_id: &str) -> Result<CheckResult, String> {
+    let config = config_store::load_or_init_config(store)?;
+    let timeout_seconds = default_timeout_seconds(&config);
+    let item = find_item(&config, item_id).ok_or_else(|| format!("item not found: {item_id}"))?;
+    let run = RunHandle::register("check-item", &item.id);
+    let log = run_log_store::start(store, &config, "check-item", &item.id);
+    let mut runner = check_service::SystemCheckRunner::new(timeout_seconds)
+        .with_profile(item_profile(&config, item))
+        .with_max_output_bytes(max_output_bytes(&config))
+        .with_cancel_token(run.token());
+    if let Some(log) = &log {
+        runner = runner.with_full_output_log(log.path().to_path_buf());
+    }
+    let started = Instant::now();
+    let outcome = check_service::check_single_item(item, &mut runner);
+    let entry = check_log_entry(item, &outcome, started, log.as_ref());
+    // A cancelled check keeps the previously stored result, as in a round.
+    if let CheckOutcome::Checked(result) = &outcome {
+        if let Err(error) = result_store::upsert_result(store, result) {
+            eprintln!("failed to persist latest result: {error}");
+        }
+    }
+    if let Some(log) = log {
+        run_log_store::finish_safe(store, log, &entry);
+        run_log_store::prune_safe(store, &config);
+    }
+    history_events::append_entry_safe(store, config.history_retention_days, entry);
+    match outcome {
+        CheckOutcome::Checked(result) => Ok(result),
+        CheckOutcome::Cancelled => Err(format!("check of {item_id} was cancelled")),
+    }
+}
+
+fn check_log_entry(
+    item: &SoftwareItem,
+    outcome: &CheckOutcome,
+    started: Instant,
+    log: Option<&run_log_store::RunLog>,
+) -> ExecutionHistoryEntry {
+    let duration_ms = started.elapsed().as_millis();
+    let entry = match outcome {
+        CheckOutcome::Checked(result) => history_events::check_item_entry(result, duration_ms),
+        CheckOutcome::Cancelled => {
+            history_events::check_item_cancelled_entry(&item.id, duration_ms)
+        }
+    };
+    ExecutionHistoryEntry {
+        full_output_log: log.and_then(run_log_store::RunLog::existing_path),
+        ..entry
+    }
+}
+
+/// Why `check_items` checked nothing.
+#[derive(Debug)]
+pub enum CheckRoundError {
+    /// Another round is still running; try again once it finished.
+    AlreadyRunning,
+    Failed(String),
+}
+
+impl From<String> for CheckRoundError {
+    fn from(error: String) -> Self {
+        CheckRoundError::Failed(error)
+    }
+}
+
+impl From<CheckRoundError> for String {
+    fn from(error: CheckRoundError) -> Self {
+        match error {
+            CheckRoundError::AlreadyRunning => "check-all is already running".to_string(),
+            CheckRoundError::Failed(error) => error,
+        }
+    }
+}
+
+/// Checks the enabled items matching `filter` as one cancellable round and
+/// records it in history as `action`. Fails without checking anything when
+/// another round is still running.
+pub fn check_items(
+    store: &dyn Store,
+    action: &str,
+    skip_action: &str,
+    skip_message: &str,
+    filter: impl Fn(&SoftwareItem) -> bool,
+) -> Result<Vec<CheckResult>, CheckRoundError> {
+    let config = config_store::load_or_init_config(store)?;
+    let retention_days = config.history_retention_days;
+    let _guard = match check_all_guard::CheckAllGuard::try_acquire() {
+        Some(guard) => guard,
+        None => {
+            history_events::append_entry_safe(
+                store,
+                retention_days,
+                history_events::check_all_entry(skip_action, false, skip_message.to_string()),
+            );
+            return Err(CheckRoundError::AlreadyRunning);
+        }
+    };
+
+    let timeout_seconds = default_timeout_seconds(&config);
+    let round_started = Instant::now();
+    let run = RunHandle::register(action, "enabled-items");
+    let token = run.token();
+    let request_interval = Duration::from_millis(config.host_request_interval_ms);
+    let limiter = Arc::new(check_executor::HostRateLimiter::new(request_interval));
+    let selected: Vec<&SoftwareItem> = config.items.iter().filter(|item| filter(item)).collect();
+    // Items skipped or interrupted by a cancellation yield no result, so the
+    // previously stored result for them is kept.
+    let checked: Vec<(CheckResult, ExecutionHistoryEntry)> =
+        check_executor::run_bounded(&selected, max_parallel_checks(&config), |item| {
+            if token.is_cancelled() {
+                return None;
+            }
+            let log = run_log_store::start(store, &config, action, &item.id);
+            let mut runner = check_service::SystemCheckRunner::new(timeout_seconds)
+                .with_profile(item_profile(&config, item))
+                .with_max_output_bytes(max_output_bytes(&config))
+                .with_rate_limiter(Arc::clone(&limiter))
+                .with_cancel_token(token.clone());
+            if let Some(log) = &log {
+                runner = runner.with_full_output_log(log.path().to_path_buf());
+            }
+            let started = Instant::now();
+            let outcome = check_service::check_single_item(item, &mut runner);
+            let entry = check_log_entry(item, &outcome, started, log.as_ref());
+            if let Some(log) = log {
+                run_log_store::finish_safe(store, log, &entry);
+            }
+            match outcome {
+                CheckOutcome::Checked(result) if !token.is_cancelled() => Some((result, entry)),
+                _ => None,
+            }
+        })
+        .into_iter()
+        .flatten()
+        .collect();
+    run_log_store::prune_safe(store, &config);
+    // Each item gets its own entry, so reports see every check and its
+    // duration, not just the round's summary.
+    let mut results = Vec::with_capacity(checked.len());
+    for (result, entry) in checked {
+        history_events::append_entry_safe(store, retention_days, entry);
+        results.push(result);
+    }
+    let round_entry = |entry: ExecutionHistoryEntry| ExecutionHistoryEntry {
+        duration_ms: Some(round_started.elapsed().as_millis()),
+        ..entry
+    };
+
+    if run.is_cancelled() {
+        if let Err(error) = result_store::upsert_results(store, &results) {
+            eprintln!("failed to persist latest results: {error}");
+        }
+        history_events::append_entry_safe(
+            store,
+            retention_days,
+            round_entry(history_events::check_all_cancelled_entry(
+                action,
+                format!("已取消：完成 {} / {} 项", results.len(), selected.len()),
+            )),
+        );
+        return Ok(results);
+    }
+
+    let error_count = results.iter().filter(|item| item.error.is_some()).count();
+    let update_count = results.iter().filter(|item| item.has_update).count();
+    if let Err(error) = result_store::upsert_results(store, &results) {
+        eprintln!("failed to persist latest results: {error}");
+    }
+    history_events::append_entry_safe(
+        store,
+        retention_days,
+        round_entry(history_events::check_all_entry(
+            action,
+            error_count == 0,
+            format!(
+                "已检查 {} 项，发现 {} 项更新，{} 项错误",
+                results.len(),
+                update_count,
+                error_count
+            ),
+        )),
+    );
+    Ok(results)
+}
+
+fn run_hooks(
+    config: &AppConfig,
+    token: &CancelToken,
+    sink: Option<LineSink>,
+    log: Option<&run_log_store::RunLog>,
+) -> RunHooks {
+    RunHooks {
+        sink,
+        cancel: Some(token.clone()),
+        limits: OutputLimits {
+            max_stream_bytes: max_output_bytes(config),
+            spill_path: log.map(|log| log.path().to_path_buf()),
+        },
+    }
+}
+
+fn record_command(
+    store: &dyn Store,
+    config: &AppConfig,
+    log: Option<run_log_store::RunLog>,
+    entry: ExecutionHistoryEntry,
+) {
+    if let Some(log) = log {
+        run_log_store::finish_safe(store, log, &entry);
+        run_log_store::prune_safe(store, config);
+    }
+    history_events::append_entry_safe(store, config.history_retention_days, entry);
+}
+
+/// Runs the item's update command, passing each output line to `sink`.
+pub fn run_item_update(
+    store: &dyn Store,
+    config: &AppConfig,
+    item: &SoftwareItem,
+    token: &CancelToken,
+    sink: Option<LineSink>,
+) -> Result<CommandOutput, String> {
+    let log = run_log_store::start(store, config, "run-item-update", &item.id);
+    let output = match shell_runner::run_shell_command_with(
+        &item_profile(config, item),
+        &item.update_command,
+        default_timeout_seconds(config),
+        run_hooks(config, token, sink, log.as_ref()),
+    ) {
+        Ok(output) => output,
+        Err(error) => {
+            let summary = format!("更新 {} 失败：{error}", item.name);
+            record_command(
+                store,
+                config,
+                log,
+                history_events::command_failed_entry(
+                    "run-item-update",
+                    &item.id,
+                    &item.update_command,
+                    &error,
+                    summary,
+                ),
+            );
+            return Err(error);
+        }
+    };
+    let summary = if output.cancelled {
+        format!("已取消更新 {}", item.name)
+    } else {
+        format!("更新 {}（退出码 {}）", item.name, output.exit_code)
+    };
+    record_command(
+        store,
+        config,
+        log,
+        history_events::command_entry("run-item-update", &item.id, &output, summary),
+    );
+    Ok(output)
+}
+
+pub fn run_ad_hoc_command(
+    store: &dyn Store,
+    config: &AppConfig,
+    command: &str,
+    token: &CancelToken,
+    sink: Option<LineSink>,
+) -> Result<CommandOutput, String> {
+    let log = run_log_store::start(store, config, "run-shared-command", "shared");
+    let output = match shell_runner::run_shell_command_with(
+        &config.execution_profile,
+        command,
+        default_timeout_seconds(config),
+        run_hooks(config, token, sink, log.as_ref()),
+    ) {
+        Ok(output) => output,
+        Err(error) => {
+            let summary = format!("共享命令执行失败：{error}");
+            record_command(
+                store,
+                config,
+                log,
+                history_events::command_failed_entry(
+                    "run-shared-command",
+                    "shared",
+                    command,
+                    &error,
+                    summary,
+                ),
+            );
+            return Err(error);
+        }
+    };
+    let summary = if output.cancelled {
+        "共享命令已取消".to_string()
+    } else {
+        format!("共享命令执行完成（退出码 {}）", output.exit_code)
+    };
+    record_command(
+        store,
+        config,
+        log,
+        history_events::command_entry("run-shared-command", "shared", &output, summary),
+    );
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::HistoryQuery;
+    use crate::services::history_store;
+    use crate::services::store::MemoryStore;
+
+    #[test]
+    fn runtimes_without_a_policy_are_not_auto_checked() {
+        let mut item = AppConfig::default().items[0].clone();
+        item.enabled = true;
+        item.check_policy = None;
+        item.group = Some(ItemGroup::Runtime);
+        assert!(!is_auto_item(&item));
+        assert!(is_runtime_item(&item));
+
+        item.group = Some(ItemGroup::Cli);
+        assert!(is_auto_cli_item(&item));
+        item.check_policy = Some(CheckPolicy::Auto);
+        item.group = Some(ItemGroup::Runtime);
+        assert!(is_auto_item(&item));
+    }
+
+    #[test]
+    fn commands_that_fail_to_start_are_recorded() {
+        let store = MemoryStore::new();
+        let mut config = AppConfig::default();
+        config.execution_profile.shell = "/nonexistent/shell".to_string();
+        config.items[0].execution = None;
+        let item = config.items[0].clone();
+        let run = RunHandle::register("run-item-update", &item.id);
+
+        assert!(run_item_update(&store, &config, &item, &run.token(), None).is_err());
+        assert!(run_ad_hoc_command(&store, &config, "true", &run.token(), None).is_err());
+
+        let page = history_store::query(&store, &HistoryQuery::default()).unwrap();
+        let recorded: Vec<(&str, bool)> = page
+            .entries
+            .iter()
+            .map(|entry| (entry.action.as_str(), entry.success))
+            .collect();
+        assert_eq!(
+            recorded,
+            [("run-shared-command", false), ("run-item-update", false)]
+        );
+        assert!(page.entries[1]
+            .stderr
+            .as_deref()
+            .is_some_and(|stderr| !stderr.is_empty()));
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+pub const DEFAULT_MAX_STREAM_BYTES: usize = 64 * 1024;
+
+fn clip_to_boundary(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Line buffer that keeps the first and the last `max_bytes / 2` of a
+/// stream and drops (but counts) everything in between.
+pub struct CappedBuffer {
+    half: usize,
+    head: String,
+    head_full: bool,
+    tail: VecDeque<String>,
+    tail_bytes: usize,
+    dropped_bytes: usize,
+}
+
+impl CappedBuffer {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            half: (max_bytes / 2).max(1),
+            head: String::new(),
+            head_full: false,
+            tail: VecDeque::new(),
+            tail_bytes: 0,
+            dropped_bytes: 0,
+        }
+    }
+
+    pub fn push_line(&mut self, line: &str) {
+        // Leaves room for the newline, so a clipped line always fits the tail.
+        let clipped = clip_to_boundary(line, self.half.saturating_sub(1));
+        self.dropped_bytes += line.len() - clipped.len();
+
+        let cost = clipped.len() + 1;
+        if !self.head_full && self.head.len() + cost <= self.half {
+            if !self.head.is_empty() {
+                self.head.push('\n');
+            }
+            self.head.push_str(clipped);
+            return;
+        }
+        self.head_full = true;
+
+        self.tail.push_back(clipped.to_string());
+        self.tail_bytes += cost;
+        while self.tail_bytes > self.half {
+            let Some(oldest) = self.tail.pop_front() else {
+                break;
+            };
+            self.tail_bytes -= oldest.len() + 1;
+            self.dropped_bytes += oldest.len() + 1;
+        }
+    }
+
+    pub fn truncated(&self) -> bool {
+        self.dropped_bytes > 0
+    }
+
+    pub fn render(&self) -> String {
+        let mut text = self.head.clone();
+        if self.truncated() {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&format!("... [truncated {} bytes] ...", self.dropped_bytes));
+        }
+        for line in &self.tail {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(line);
+        }
+        text
+    }
+}
+
+/// Full, untruncated copy of both streams, one `[stream] line` per line.
+/// Opened for appending so several commands of one run share a file.
+pub struct SpillFile {
+    path: PathBuf,
+    writer: Mutex<Option<BufWriter<File>>>,
+}
+
+impl SpillFile {
+    pub fn create(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|error| format!("failed to create {}: {error}", parent.display()))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|error| format!("failed to open {}: {error}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: Mutex::new(Some(BufWriter::new(file))),
+        })
+    }
+
+    pub fn write_line(&self, stream: &str, line: &str) {
+        if let Ok(mut writer) = self.writer.lock() {
+            if let Some(file) = writer.as_mut() {
+                if writeln!(file, "[{stream}] {line}").is_err() {
+                    *writer = None;
+                }
+            }
+        }
+    }
+
+    /// Flushes and returns the path, or `None` if writing failed.
+    pub fn finish(&self) -> Option<String> {
+        let flushed = self
+            .writer
+            .lock()
+            .ok()
+            .and_then(|mut writer| writer.take())
+            .is_some_and(|mut file| file.flush().is_ok());
+        flushed.then(|| self.path.to_string_lossy().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture(max_bytes: usize, lines: &[&str]) -> CappedBuffer {
+        let mut buffer = CappedBuffer::new(max_bytes);
+        for line in lines {
+            buffer.push_line(line);
+        }
+        buffer
+    }
+
+    #[test]
+    fn short_output_is_kept_whole() {
+        let buffer = capture(64, &["one", "two", "three"]);
+        assert!(!buffer.truncated());
+        assert_eq!(buffer.render(), "one\ntwo\nthree");
+    }
+
+    #[test]
+    fn keeps_head_and_tail_and_counts_the_gap() {
+        let buffer = capture(20, &["aaaa", "bbbb", "cccc", "dddd", "eeee"]);
+        assert!(buffer.truncated());
+        assert_eq!(
+            buffer.render(),
+            "aaaa\nbbbb\n... [truncated 5 bytes] ...\ndddd\neeee"
+        );
+    }
+
+    #[test]
+    fn long_lines_are_cut_on_char_boundaries() {
+        // A line gets 3 of the 4 bytes in each half, which falls inside the
+        // second "é".
+        let buffer = capture(8, &["aéé"]);
+        assert_eq!(buffer.render(), "aé\n... [truncated 2 bytes] ...");
+
+        let buffer = capture(8, &["ok", "0123456789"]);
+        assert_eq!(buffer.render(), "ok\n... [truncated 7 bytes] ...\n012");
+    }
+
+    #[test]
+    fn spill_file_keeps_every_line_of_both_streams() {
+        let dir = std::env::temp_dir().join(format!("patchpilot-spill-{}", std::process::id()));
+        let path = dir.join("run.log");
+        let spill = SpillFile::create(&path).unwrap();
+        spill.write_line("stdout", "==> Upgrading node");
+        spill.write_line("stderr", "Warning: already installed");
+        assert_eq!(spill.finish(), Some(path.to_string_lossy().to_string()));
+        // Finished files take no more lines.
+        spill.write_line("stdout", "late");
+        assert_eq!(spill.finish(), None);
+
+        let next = SpillFile::create(&path).unwrap();
+        next.write_line("stdout", "second command");
+        next.finish();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(
+            contents,
+            "[stdout] ==> Upgrading node\n[stderr] Warning: already installed\n[stdout] second command\n"
+        );
+    }
+}
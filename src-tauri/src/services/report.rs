@@ -0,0 +1,548 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Duration, Local, Utc};
+
+use crate::model::{
+    AppConfig, ExecutionHistoryEntry, FleetReport, ItemErrorSummary, LatestResultState,
+    OutdatedItem, ReportFormat, ReportRun, VersionTimelineState,
+};
+
+use super::store::Store;
+use super::{config_store, history_store, result_store, version_timeline};
+
+pub const DEFAULT_REPORT_DAYS: u32 = 7;
+const SLOWEST_CHECK_COUNT: usize = 10;
+
+/// Everything a report is built from; `history` holds the period's entries.
+pub struct ReportInput<'a> {
+    pub config: &'a AppConfig,
+    pub latest: &'a LatestResultState,
+    pub history: &'a [ExecutionHistoryEntry],
+    pub timelines: &'a VersionTimelineState,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+}
+
+fn to_run(entry: &ExecutionHistoryEntry) -> ReportRun {
+    ReportRun {
+        action: entry.action.clone(),
+        target: entry.target.clone(),
+        recorded_at: entry.recorded_at.clone(),
+        success: entry.success,
+        duration_ms: entry.duration_ms,
+        summary: entry.summary.clone(),
+    }
+}
+
+fn outdated_items(input: &ReportInput) -> Vec<OutdatedItem> {
+    input
+        .config
+        .items
+        .iter()
+        .filter(|item| item.enabled)
+        .filter_map(|item| {
+            let snapshot = input.latest.items.get(&item.id)?;
+            if !snapshot.has_update || snapshot.error.is_some() {
+                return None;
+            }
+            let days_behind = input.timelines.items.get(&item.id).and_then(|transitions| {
+                version_timeline::metrics(transitions, input.until).days_behind_latest
+            });
+            Some(OutdatedItem {
+                item_id: item.id.clone(),
+                name: item.name.clone(),
+                current_version: snapshot.current_version.clone(),
+                latest_version: snapshot.latest_version.clone(),
+                checked_at: snapshot.checked_at.clone(),
+                days_behind,
+            })
+        })
+        .collect()
+}
+
+fn errors_by_item(history: &[ExecutionHistoryEntry]) -> Vec<ItemErrorSummary> {
+    let mut errors: BTreeMap<&str, ItemErrorSummary> = BTreeMap::new();
+    // History is newest first, so the first failure seen is the last one.
+    for entry in history
+        .iter()
+        .filter(|entry| entry.action == "check-item" && !entry.success)
+    {
+        errors
+            .entry(&entry.target)
+            .or_insert_with(|| ItemErrorSummary {
+                item_id: entry.target.clone(),
+                count: 0,
+                last_error: entry.summary.clone(),
+                last_at: entry.recorded_at.clone(),
+            })
+            .count += 1;
+    }
+    let mut errors: Vec<ItemErrorSummary> = errors.into_values().collect();
+    errors.sort_by_key(|error| Reverse(error.count));
+    errors
+}
+
+fn average_hours_to_update(input: &ReportInput) -> Option<f64> {
+    let hours: Vec<f64> = input
+        .timelines
+        .items
+        .values()
+        .flat_map(|transitions| version_timeline::metrics(transitions, input.until).update_lags)
+        .filter(|lag| {
+            DateTime::parse_from_rfc3339(&lag.installed_at)
+                .is_ok_and(|installed_at| installed_at >= input.since && installed_at < input.until)
+        })
+        .map(|lag| lag.hours)
+        .collect();
+    (!hours.is_empty()).then(|| hours.iter().sum::<f64>() / hours.len() as f64)
+}
+
+pub fn build(input: &ReportInput) -> FleetReport {
+    // Single item checks only: updates and whole rounds would crowd them out.
+    let mut slowest_runs: Vec<ReportRun> = input
+        .history
+        .iter()
+        .filter(|entry| entry.action == "check-item" && entry.duration_ms.is_some())
+        .map(to_run)
+        .collect();
+    slowest_runs.sort_by_key(|run| Reverse(run.duration_ms));
+    slowest_runs.truncate(SLOWEST_CHECK_COUNT);
+
+    FleetReport {
+        generated_at: Utc::now().to_rfc3339(),
+        since: input.since.to_rfc3339(),
+        until: input.until.to_rfc3339(),
+        outdated: outdated_items(input),
+        errors: errors_by_item(input.history),
+        updates: input
+            .history
+            .iter()
+            .filter(|entry| entry.action == "run-item-update" && entry.success)
+            .map(to_run)
+            .collect(),
+        average_hours_to_update: average_hours_to_update(input),
+        slowest_runs,
+    }
+}
+
+fn format_time(raw: &str) -> String {
+    DateTime::parse_from_rfc3339(raw)
+        .map(|value| {
+            value
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|_| raw.to_string())
+}
+
+fn format_duration(duration_ms: Option<u128>) -> String {
+    match duration_ms {
+        Some(ms) if ms >= 1000 => format!("{:.1} s", ms as f64 / 1000.0),
+        Some(ms) => format!("{ms} ms"),
+        None => "-".to_string(),
+    }
+}
+
+fn format_version(version: &Option<String>) -> String {
+    version.clone().unwrap_or_else(|| "-".to_string())
+}
+
+/// One report section, rendered as a table in every text format.
+struct Table {
+    title: String,
+    headers: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+}
+
+fn tables(report: &FleetReport) -> Vec<Table> {
+    vec![
+        Table {
+            title: format!("当前待更新（{}）", report.outdated.len()),
+            headers: &["项目", "当前版本", "最新版本", "落后天数", "检查时间"],
+            rows: report
+                .outdated
+                .iter()
+                .map(|item| {
+                    vec![
+                        format!("{} ({})", item.name, item.item_id),
+                        format_version(&item.current_version),
+                        format_version(&item.latest_version),
+                        item.days_behind
+                            .map_or_else(|| "-".to_string(), |days| format!("{days:.1}")),
+                        format_time(&item.checked_at),
+                    ]
+                })
+                .collect(),
+        },
+        Table {
+            title: format!("检查错误（{} 个项目）", report.errors.len()),
+            headers: &["项目", "次数", "最近错误", "最近时间"],
+            rows: report
+                .errors
+                .iter()
+                .map(|error| {
+                    vec![
+                        error.item_id.clone(),
+                        error.count.to_string(),
+                        error.last_error.clone(),
+                        format_time(&error.last_at),
+                    ]
+                })
+                .collect(),
+        },
+        Table {
+            title: format!("已执行更新（{}）", report.updates.len()),
+            headers: &["时间", "项目", "耗时", "摘要"],
+            rows: report
+                .updates
+                .iter()
+                .map(|run| {
+                    vec![
+                        format_time(&run.recorded_at),
+                        run.target.clone(),
+                        format_duration(run.duration_ms),
+                        run.summary.clone(),
+                    ]
+                })
+                .collect(),
+        },
+        Table {
+            title: "最慢的检查".to_string(),
+            headers: &["项目", "时间", "耗时", "结果"],
+            rows: report
+                .slowest_runs
+                .iter()
+                .map(|run| {
+                    vec![
+                        run.target.clone(),
+                        format_time(&run.recorded_at),
+                        format_duration(run.duration_ms),
+                        if run.success { "成功" } else { "失败" }.to_string(),
+                    ]
+                })
+                .collect(),
+        },
+    ]
+}
+
+fn summary_lines(report: &FleetReport) -> [String; 2] {
+    let average = report
+        .average_hours_to_update
+        .map_or_else(|| "无数据".to_string(), |hours| format!("{hours:.1} 小时"));
+    [
+        format!(
+            "统计区间：{} 至 {}（生成于 {}）",
+            format_time(&report.since),
+            format_time(&report.until),
+            format_time(&report.generated_at)
+        ),
+        format!("平均升级耗时：{average}"),
+    ]
+}
+
+fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+fn render_markdown(report: &FleetReport) -> String {
+    let mut out = String::from("# PatchPilot 巡检报告\n\n");
+    for line in summary_lines(report) {
+        out.push_str(&format!("- {line}\n"));
+    }
+    for table in tables(report) {
+        out.push_str(&format!("\n## {}\n\n", table.title));
+        if table.rows.is_empty() {
+            out.push_str("无\n");
+            continue;
+        }
+        out.push_str(&format!("| {} |\n", table.headers.join(" | ")));
+        out.push_str(&format!("|{}\n", " --- |".repeat(table.headers.len())));
+        for row in &table.rows {
+            let cells: Vec<String> = row.iter().map(|cell| markdown_cell(cell)).collect();
+            out.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+    }
+    out
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_html(report: &FleetReport) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>PatchPilot 巡检报告</title>\n<style>\
+         body{font-family:-apple-system,sans-serif;margin:2em;}\
+         table{border-collapse:collapse;margin-bottom:1em;}\
+         th,td{border:1px solid #ccc;padding:4px 8px;text-align:left;}\
+         </style>\n</head>\n<body>\n<h1>PatchPilot 巡检报告</h1>\n",
+    );
+    for line in summary_lines(report) {
+        out.push_str(&format!("<p>{}</p>\n", escape_html(&line)));
+    }
+    for table in tables(report) {
+        out.push_str(&format!("<h2>{}</h2>\n", escape_html(&table.title)));
+        if table.rows.is_empty() {
+            out.push_str("<p>无</p>\n");
+            continue;
+        }
+        out.push_str("<table>\n<tr>");
+        for header in table.headers {
+            out.push_str(&format!("<th>{}</th>", escape_html(header)));
+        }
+        out.push_str("</tr>\n");
+        for row in &table.rows {
+            out.push_str("<tr>");
+            for cell in row {
+                out.push_str(&format!("<td>{}</td>", escape_html(cell)));
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+pub fn render(report: &FleetReport, format: ReportFormat) -> Result<String, String> {
+    match format {
+        ReportFormat::Markdown => Ok(render_markdown(report)),
+        ReportFormat::Html => Ok(render_html(report)),
+        ReportFormat::Json => serde_json::to_string_pretty(report)
+            .map_err(|error| format!("failed to serialize report: {error}")),
+    }
+}
+
+pub fn write(report: &FleetReport, format: ReportFormat, path: &Path) -> Result<(), String> {
+    let contents = render(report, format)?;
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("failed to create {}: {error}", parent.display()))?;
+    }
+    fs::write(path, contents)
+        .map_err(|error| format!("failed to write report to {}: {error}", path.display()))
+}
+
+/// Builds the report for the last `days` days from the app's stores.
+pub fn load(store: &dyn Store, days: u32) -> Result<FleetReport, String> {
+    let until = Utc::now();
+    let since = until - Duration::days(i64::from(days.max(1)));
+    let config = config_store::load_or_init_config(store)?;
+    let latest = result_store::load_state(store)?;
+    let history = history_store::entries_between(store, since, until)?;
+    let timelines = version_timeline::load_state(store)?;
+    Ok(build(&ReportInput {
+        config: &config,
+        latest: &latest,
+        history: &history,
+        timelines: &timelines,
+        since,
+        until,
+    }))
+}
+
+/// Like `load`, also writing the report to `path`.
+pub fn generate(
+    store: &dyn Store,
+    format: ReportFormat,
+    path: &Path,
+    days: u32,
+) -> Result<FleetReport, String> {
+    let report = load(store, days)?;
+    write(&report, format, path)?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::model::{LatestResultSnapshot, VersionTransition};
+
+    fn at(raw: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(raw)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn entry(
+        action: &str,
+        target: &str,
+        recorded_at: &str,
+        success: bool,
+        duration_ms: u128,
+    ) -> ExecutionHistoryEntry {
+        ExecutionHistoryEntry {
+            id: format!("{recorded_at}-{action}-{target}"),
+            action: action.to_string(),
+            target: target.to_string(),
+            command: None,
+            stdout: None,
+            stderr: None,
+            recorded_at: recorded_at.to_string(),
+            success,
+            exit_code: None,
+            timed_out: false,
+            cancelled: false,
+            truncated: false,
+            full_output_log: None,
+            duration_ms: Some(duration_ms),
+            summary: format!("{action} {target}"),
+        }
+    }
+
+    fn snapshot(item_id: &str, has_update: bool, error: Option<&str>) -> LatestResultSnapshot {
+        LatestResultSnapshot {
+            item_id: item_id.to_string(),
+            checked_at: "2025-01-06T00:00:00Z".to_string(),
+            has_update,
+            current_version: Some("1.0".to_string()),
+            latest_version: Some("1.1".to_string()),
+            version_comparison: None,
+            error: error.map(str::to_string),
+        }
+    }
+
+    fn transition(observed_at: &str, current: &str, latest: &str) -> VersionTransition {
+        VersionTransition {
+            observed_at: observed_at.to_string(),
+            last_seen_at: observed_at.to_string(),
+            current_version: Some(current.to_string()),
+            latest_version: Some(latest.to_string()),
+            has_update: current != latest,
+        }
+    }
+
+    #[test]
+    fn build_summarizes_the_period() {
+        let config = AppConfig::default();
+        let ids: Vec<&str> = config.items.iter().map(|item| item.id.as_str()).collect();
+        let (outdated, failing, current) = (ids[0], ids[1], ids[2]);
+        let latest = LatestResultState {
+            updated_at: "2025-01-06T00:00:00Z".to_string(),
+            items: HashMap::from([
+                (outdated.to_string(), snapshot(outdated, true, None)),
+                (
+                    failing.to_string(),
+                    snapshot(failing, true, Some("timed out")),
+                ),
+                (current.to_string(), snapshot(current, false, None)),
+            ]),
+        };
+        // Newest first, as history_store returns it.
+        let history = [
+            entry(
+                "check-all",
+                "enabled-items",
+                "2025-01-06T00:00:00Z",
+                false,
+                90_000,
+            ),
+            entry("check-item", failing, "2025-01-05T23:59:00Z", false, 30_000),
+            entry(
+                "run-item-update",
+                current,
+                "2025-01-05T12:00:00Z",
+                true,
+                60_000,
+            ),
+            entry(
+                "run-item-update",
+                outdated,
+                "2025-01-05T11:00:00Z",
+                false,
+                5_000,
+            ),
+            entry("check-item", outdated, "2025-01-05T10:00:00Z", true, 2_000),
+            entry("check-item", failing, "2025-01-04T00:00:00Z", false, 10_000),
+        ];
+        let timelines = VersionTimelineState {
+            items: HashMap::from([
+                (
+                    outdated.to_string(),
+                    vec![transition("2025-01-05T00:00:00Z", "1.0", "1.1")],
+                ),
+                (
+                    current.to_string(),
+                    vec![
+                        transition("2025-01-03T00:00:00Z", "0.9", "1.0"),
+                        transition("2025-01-05T12:00:00Z", "1.0", "1.0"),
+                    ],
+                ),
+            ]),
+        };
+
+        let report = build(&ReportInput {
+            config: &config,
+            latest: &latest,
+            history: &history,
+            timelines: &timelines,
+            since: at("2024-12-30T00:00:00Z"),
+            until: at("2025-01-06T12:00:00Z"),
+        });
+
+        assert_eq!(report.outdated.len(), 1);
+        assert_eq!(report.outdated[0].item_id, outdated);
+        assert_eq!(report.outdated[0].days_behind, Some(1.5));
+
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].item_id, failing);
+        assert_eq!(report.errors[0].count, 2);
+        assert_eq!(report.errors[0].last_at, "2025-01-05T23:59:00Z");
+
+        let updated: Vec<&str> = report
+            .updates
+            .iter()
+            .map(|run| run.target.as_str())
+            .collect();
+        assert_eq!(updated, [current]);
+        assert_eq!(report.average_hours_to_update, Some(60.0));
+
+        let slowest: Vec<(&str, Option<u128>)> = report
+            .slowest_runs
+            .iter()
+            .map(|run| (run.action.as_str(), run.duration_ms))
+            .collect();
+        assert_eq!(
+            slowest,
+            [
+                ("check-item", Some(30_000)),
+                ("check-item", Some(10_000)),
+                ("check-item", Some(2_000)),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_periods_have_no_average() {
+        let config = AppConfig::default();
+        let report = build(&ReportInput {
+            config: &config,
+            latest: &LatestResultState {
+                updated_at: String::new(),
+                items: HashMap::new(),
+            },
+            history: &[],
+            timelines: &VersionTimelineState::default(),
+            since: at("2024-12-30T00:00:00Z"),
+            until: at("2025-01-06T00:00:00Z"),
+        });
+
+        assert!(report.outdated.is_empty() && report.errors.is_empty());
+        assert!(report.updates.is_empty() && report.slowest_runs.is_empty());
+        assert_eq!(report.average_hours_to_update, None);
+    }
+}
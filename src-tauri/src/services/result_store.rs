@@ -1,34 +1,10 @@
-use std::fs;
-use std::path::PathBuf;
-
 use chrono::Utc;
-use tauri::AppHandle;
 
 use crate::model::{CheckResult, LatestResultSnapshot, LatestResultState};
-use crate::services::config_store;
-
-const RESULT_FILE: &str = "latest-check-results.json";
-
-fn result_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let config_path = config_store::resolve_config_path(app)?;
-    let base_dir = config_path.parent().ok_or_else(|| {
-        format!(
-            "failed to resolve latest results directory from {}",
-            config_path.display()
-        )
-    })?;
-    fs::create_dir_all(base_dir)
-        .map_err(|error| format!("failed to create latest results directory: {error}"))?;
-    Ok(base_dir.join(RESULT_FILE))
-}
+use crate::services::store::{Store, StoreFile};
+use crate::services::version_timeline;
 
-fn write_state(app: &AppHandle, state: &LatestResultState) -> Result<(), String> {
-    let path = result_path(app)?;
-    let payload = serde_json::to_string_pretty(state)
-        .map_err(|error| format!("failed to serialize latest results: {error}"))?;
-    fs::write(&path, payload)
-        .map_err(|error| format!("failed to write latest results to {}: {error}", path.display()))
-}
+const RESULT_FILE: StoreFile<'static> = StoreFile::config("latest-check-results.json");
 
 fn to_snapshot(result: &CheckResult) -> LatestResultSnapshot {
     LatestResultSnapshot {
@@ -37,46 +13,82 @@ fn to_snapshot(result: &CheckResult) -> LatestResultSnapshot {
         has_update: result.has_update,
         current_version: result.current_version.clone(),
         latest_version: result.latest_version.clone(),
+        version_comparison: result.version_comparison,
         error: result.error.clone(),
     }
 }
 
-pub fn load_state(app: &AppHandle) -> Result<LatestResultState, String> {
-    let path = result_path(app)?;
-    if !path.exists() {
-        return Ok(LatestResultState::default());
-    }
-
-    let data = fs::read_to_string(&path).map_err(|error| {
-        format!(
-            "failed to read latest results from {}: {error}",
-            path.display()
-        )
-    })?;
-
-    serde_json::from_str::<LatestResultState>(&data).map_err(|error| {
-        format!(
-            "failed to parse latest results from {}: {error}",
-            path.display()
-        )
-    })
+pub fn load_state(store: &dyn Store) -> Result<LatestResultState, String> {
+    Ok(store.read_json(RESULT_FILE)?.unwrap_or_default())
 }
 
-pub fn upsert_result(app: &AppHandle, result: &CheckResult) -> Result<(), String> {
-    upsert_results(app, std::slice::from_ref(result))
+pub fn upsert_result(store: &dyn Store, result: &CheckResult) -> Result<(), String> {
+    upsert_results(store, std::slice::from_ref(result))
 }
 
-pub fn upsert_results(app: &AppHandle, results: &[CheckResult]) -> Result<(), String> {
+pub fn upsert_results(store: &dyn Store, results: &[CheckResult]) -> Result<(), String> {
     if results.is_empty() {
         return Ok(());
     }
 
-    let mut state = load_state(app)?;
-    for result in results {
-        state
-            .items
-            .insert(result.item_id.clone(), to_snapshot(result));
+    store.update_json(RESULT_FILE, |state: &mut LatestResultState| {
+        for result in results {
+            state
+                .items
+                .insert(result.item_id.clone(), to_snapshot(result));
+        }
+        state.updated_at = Utc::now().to_rfc3339();
+    })?;
+    if let Err(error) = version_timeline::record(store, results) {
+        eprintln!("failed to record version timeline: {error}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::store::MemoryStore;
+
+    fn result(item_id: &str, current: &str, latest: &str) -> CheckResult {
+        CheckResult {
+            item_id: item_id.to_string(),
+            checked_at: Utc::now().to_rfc3339(),
+            has_update: current != latest,
+            current_version: Some(current.to_string()),
+            latest_version: Some(latest.to_string()),
+            version_comparison: None,
+            details: String::new(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn starts_empty() {
+        let store = MemoryStore::new();
+        upsert_results(&store, &[]).unwrap();
+        assert!(load_state(&store).unwrap().items.is_empty());
+        assert!(store.size(RESULT_FILE).unwrap().is_none());
+    }
+
+    #[test]
+    fn upserts_replace_only_the_items_checked() {
+        let store = MemoryStore::new();
+        upsert_results(
+            &store,
+            &[
+                result("node", "20.0.0", "22.0.0"),
+                result("go", "1.22", "1.22"),
+            ],
+        )
+        .unwrap();
+        upsert_result(&store, &result("node", "22.0.0", "22.0.0")).unwrap();
+
+        let state = load_state(&store).unwrap();
+        assert_eq!(state.items.len(), 2);
+        let node = &state.items["node"];
+        assert_eq!(node.current_version.as_deref(), Some("22.0.0"));
+        assert!(!node.has_update);
+        assert_eq!(state.items["go"].latest_version.as_deref(), Some("1.22"));
     }
-    state.updated_at = Utc::now().to_rfc3339();
-    write_state(app, &state)
 }
@@ -0,0 +1,329 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local, Utc};
+
+use crate::model::{AppConfig, ExecutionHistoryEntry, RunLogPage, RunLogSummary};
+use crate::services::json_file;
+use crate::services::store::Store;
+
+const RUN_LOG_DIR: &str = "run-logs";
+const DEFAULT_PAGE_LINES: usize = 500;
+const MAX_PAGE_LINES: usize = 5000;
+
+/// Ids of the logs of runs still in progress, which pruning must not touch.
+fn open_logs() -> &'static Mutex<HashSet<String>> {
+    static OPEN: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+    OPEN.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// One run's log: `<id>.log` holds the full command output, `<id>.json` the
+/// history entry describing the run. Open until dropped.
+pub struct RunLog {
+    id: String,
+    path: PathBuf,
+}
+
+impl Drop for RunLog {
+    fn drop(&mut self) {
+        if let Ok(mut open) = open_logs().lock() {
+            open.remove(&self.id);
+        }
+    }
+}
+
+impl RunLog {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The output file, if any command of the run wrote to it.
+    pub fn existing_path(&self) -> Option<String> {
+        self.path
+            .exists()
+            .then(|| self.path.to_string_lossy().to_string())
+    }
+}
+
+/// Run logs are files, so only stores backed by a directory keep them.
+pub fn log_dir(store: &dyn Store) -> Result<PathBuf, String> {
+    let paths = store
+        .paths()
+        .ok_or_else(|| "run logs need a data directory".to_string())?;
+    let dir = paths.data_path(RUN_LOG_DIR)?;
+    fs::create_dir_all(&dir)
+        .map_err(|error| format!("failed to create run log directory: {error}"))?;
+    Ok(dir)
+}
+
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn new_log_id(action: &str, target: &str) -> String {
+    format!(
+        "{}-{}-{}",
+        Local::now().format("%Y%m%d-%H%M%S-%3f"),
+        sanitize(action),
+        sanitize(target)
+    )
+}
+
+fn validate_id(id: &str) -> Result<(), String> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("invalid run log id: {id}"))
+    }
+}
+
+fn output_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.log"))
+}
+
+fn meta_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.json"))
+}
+
+/// Opens a log for a new run, or `None` when run logs are disabled or the
+/// store keeps no files.
+pub fn start(store: &dyn Store, config: &AppConfig, action: &str, target: &str) -> Option<RunLog> {
+    if !config.keep_full_output_logs || store.paths().is_none() {
+        return None;
+    }
+    match log_dir(store) {
+        Ok(dir) => {
+            let id = new_log_id(action, target);
+            let path = output_path(&dir, &id);
+            if let Ok(mut open) = open_logs().lock() {
+                open.insert(id.clone());
+            }
+            Some(RunLog { id, path })
+        }
+        Err(error) => {
+            eprintln!("failed to open run log: {error}");
+            None
+        }
+    }
+}
+
+/// Stores the run's metadata next to its output and closes the log.
+pub fn finish_safe(store: &dyn Store, log: RunLog, entry: &ExecutionHistoryEntry) {
+    let result = log_dir(store).and_then(|dir| {
+        let payload = serde_json::to_string_pretty(entry)
+            .map_err(|error| format!("failed to serialize run log metadata: {error}"))?;
+        json_file::write(&meta_path(&dir, &log.id), &payload)
+    });
+    if let Err(error) = result {
+        eprintln!("failed to record run log: {error}");
+    }
+}
+
+/// Applies retention to the log directory, sparing logs still open. Run once
+/// per command or check round rather than per log, since runs finish while
+/// others are still writing.
+pub fn prune_safe(store: &dyn Store, config: &AppConfig) {
+    if !config.keep_full_output_logs || store.paths().is_none() {
+        return;
+    }
+    let open = open_logs()
+        .lock()
+        .map(|open| open.clone())
+        .unwrap_or_default();
+    match log_dir(store) {
+        Ok(dir) => prune_dir(
+            &dir,
+            config.run_log_retention_days,
+            config.run_log_max_total_mb,
+            &open,
+        ),
+        Err(error) => eprintln!("failed to prune run logs: {error}"),
+    }
+}
+
+struct LogFiles {
+    id: String,
+    size_bytes: u64,
+    modified: SystemTime,
+}
+
+/// Groups the files in `dir` by log id, newest first.
+fn scan_dir(dir: &Path) -> Vec<LogFiles> {
+    let mut logs: Vec<LogFiles> = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return logs;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_log_file = matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("log") | Some("json")
+        );
+        let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        if !is_log_file || validate_id(id).is_err() {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        match logs.iter_mut().find(|log| log.id == id) {
+            Some(log) => {
+                log.size_bytes += metadata.len();
+                log.modified = log.modified.max(modified);
+            }
+            None => logs.push(LogFiles {
+                id: id.to_string(),
+                size_bytes: metadata.len(),
+                modified,
+            }),
+        }
+    }
+    logs.sort_by_key(|log| std::cmp::Reverse(log.modified));
+    logs
+}
+
+fn remove_log(dir: &Path, id: &str) -> bool {
+    let removed_output = fs::remove_file(output_path(dir, id)).is_ok();
+    let removed_meta = fs::remove_file(meta_path(dir, id)).is_ok();
+    removed_output || removed_meta
+}
+
+/// Deletes logs older than `max_age_days`, then the oldest logs until the
+/// directory fits in `max_total_mb`. The newest log and those in `open` are
+/// always kept.
+fn prune_dir(dir: &Path, max_age_days: u64, max_total_mb: u64, open: &HashSet<String>) {
+    let max_age = Duration::from_secs(max_age_days.saturating_mul(24 * 60 * 60));
+    let max_total_bytes = max_total_mb.saturating_mul(1024 * 1024);
+    let now = SystemTime::now();
+    let mut total_bytes = 0u64;
+    for (index, log) in scan_dir(dir).iter().enumerate() {
+        let expired = now
+            .duration_since(log.modified)
+            .is_ok_and(|age| age > max_age);
+        total_bytes = total_bytes.saturating_add(log.size_bytes);
+        if index > 0 && !open.contains(&log.id) && (expired || total_bytes > max_total_bytes) {
+            remove_log(dir, &log.id);
+        }
+    }
+}
+
+fn read_meta(dir: &Path, id: &str) -> Option<ExecutionHistoryEntry> {
+    let data = fs::read_to_string(meta_path(dir, id)).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+pub fn list(store: &dyn Store, limit: usize) -> Result<Vec<RunLogSummary>, String> {
+    if store.paths().is_none() {
+        return Ok(Vec::new());
+    }
+    let dir = log_dir(store)?;
+    Ok(scan_dir(&dir)
+        .into_iter()
+        .take(limit)
+        .map(|log| {
+            let path = output_path(&dir, &log.id);
+            RunLogSummary {
+                path: path.exists().then(|| path.to_string_lossy().to_string()),
+                entry: read_meta(&dir, &log.id),
+                size_bytes: log.size_bytes,
+                modified_at: DateTime::<Utc>::from(log.modified).to_rfc3339(),
+                id: log.id,
+            }
+        })
+        .collect())
+}
+
+/// Returns up to `limit` output lines starting at line `offset`.
+pub fn read_page(
+    store: &dyn Store,
+    id: &str,
+    offset: usize,
+    limit: Option<usize>,
+) -> Result<RunLogPage, String> {
+    validate_id(id)?;
+    let path = output_path(&log_dir(store)?, id);
+    let file = fs::File::open(&path)
+        .map_err(|error| format!("failed to open {}: {error}", path.display()))?;
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LINES).clamp(1, MAX_PAGE_LINES);
+
+    let mut lines = Vec::new();
+    let mut has_more = false;
+    for line in BufReader::new(file).split(b'\n').skip(offset) {
+        let line = line.map_err(|error| format!("failed to read {}: {error}", path.display()))?;
+        if lines.len() == limit {
+            has_more = true;
+            break;
+        }
+        lines.push(String::from_utf8_lossy(&line).to_string());
+    }
+    Ok(RunLogPage {
+        id: id.to_string(),
+        offset,
+        next_offset: has_more.then_some(offset + lines.len()),
+        lines,
+    })
+}
+
+pub fn delete(store: &dyn Store, id: &str) -> Result<(), String> {
+    validate_id(id)?;
+    if remove_log(&log_dir(store)?, id) {
+        Ok(())
+    } else {
+        Err(format!("run log not found: {id}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_log(dir: &Path, id: &str, age_secs: u64) {
+        let path = output_path(dir, id);
+        fs::write(&path, "output\n").unwrap();
+        let modified = SystemTime::now() - Duration::from_secs(age_secs);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[test]
+    fn pruning_keeps_the_newest_and_open_logs() {
+        let dir = std::env::temp_dir().join(format!("patchpilot-run-logs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        write_log(&dir, "newest", 0);
+        write_log(&dir, "open", 60);
+        write_log(&dir, "closed", 120);
+
+        let open = HashSet::from(["open".to_string()]);
+        prune_dir(&dir, 30, 0, &open);
+
+        assert!(output_path(&dir, "newest").exists());
+        assert!(output_path(&dir, "open").exists());
+        assert!(!output_path(&dir, "closed").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use chrono::Utc;
+
+use crate::model::ActiveRun;
+use crate::services::shell_runner;
+
+/// Shared cancellation flag for one run; handed to everything the run spawns.
+#[derive(Debug, Clone)]
+pub struct CancelToken {
+    run_id: Arc<str>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+}
+
+struct RunState {
+    info: ActiveRun,
+    token: CancelToken,
+    pids: Vec<u32>,
+}
+
+fn runs() -> &'static Mutex<HashMap<String, RunState>> {
+    static RUNS: OnceLock<Mutex<HashMap<String, RunState>>> = OnceLock::new();
+    RUNS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Registration of an in-flight run; the run is removed from the registry
+/// when the handle is dropped.
+pub struct RunHandle {
+    token: CancelToken,
+}
+
+impl RunHandle {
+    pub fn register(action: &str, target: &str) -> Self {
+        let now = Utc::now();
+        let run_id = format!("run-{}-{action}-{target}", now.timestamp_micros());
+        let token = CancelToken {
+            run_id: Arc::from(run_id.as_str()),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        };
+        let state = RunState {
+            info: ActiveRun {
+                run_id: run_id.clone(),
+                action: action.to_string(),
+                target: target.to_string(),
+                started_at: now.to_rfc3339(),
+            },
+            token: token.clone(),
+            pids: Vec::new(),
+        };
+        if let Ok(mut runs) = runs().lock() {
+            runs.insert(run_id, state);
+        }
+        Self { token }
+    }
+
+    pub fn run_id(&self) -> &str {
+        self.token.run_id()
+    }
+
+    pub fn token(&self) -> CancelToken {
+        self.token.clone()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+impl Drop for RunHandle {
+    fn drop(&mut self) {
+        if let Ok(mut runs) = runs().lock() {
+            runs.remove(self.token.run_id());
+        }
+    }
+}
+
+/// Records a child process (and its process group) as belonging to the run.
+/// Returns false when the run was already cancelled, so the caller can kill
+/// the child it just spawned.
+pub fn track_child(token: &CancelToken, pid: u32) -> bool {
+    if let Ok(mut runs) = runs().lock() {
+        if let Some(state) = runs.get_mut(token.run_id()) {
+            state.pids.push(pid);
+        }
+    }
+    !token.is_cancelled()
+}
+
+/// Call before reaping the child; see `cancel`.
+pub fn untrack_child(token: &CancelToken, pid: u32) {
+    if let Ok(mut runs) = runs().lock() {
+        if let Some(state) = runs.get_mut(token.run_id()) {
+            state.pids.retain(|value| *value != pid);
+        }
+    }
+}
+
+/// Flags the run as cancelled and kills the process groups of every child
+/// it currently has running. Kills under the lock: children are untracked
+/// before they are reaped, so a tracked pid still belongs to the run.
+pub fn cancel(run_id: &str) -> Result<(), String> {
+    let runs = runs()
+        .lock()
+        .map_err(|_| "run registry is unavailable".to_string())?;
+    let state = runs
+        .get(run_id)
+        .ok_or_else(|| format!("run not found: {run_id}"))?;
+    state.token.cancelled.store(true, Ordering::Release);
+    for pid in &state.pids {
+        shell_runner::kill_process_group(*pid);
+    }
+    Ok(())
+}
+
+pub fn active_runs() -> Vec<ActiveRun> {
+    let mut active: Vec<ActiveRun> = runs()
+        .lock()
+        .map(|runs| runs.values().map(|state| state.info.clone()).collect())
+        .unwrap_or_default();
+    active.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    active
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use tauri::{AppHandle, Emitter};
+
+use crate::model::{AppConfig, SchedulerStatus, SoftwareItem};
+use crate::services::store::{Store, StoreFile};
+use crate::services::{check_schedule, config_store, result_store};
+
+pub const STATUS_EVENT: &str = "patchpilot://scheduler-status";
+pub const LATEST_RESULTS_EVENT: &str = "patchpilot://latest-results-updated";
+pub const HISTORY_EVENT: &str = "patchpilot://history-updated";
+
+const STATE_FILE: StoreFile<'static> = StoreFile::data("scheduler-state.json");
+/// Upper bound on how late a due check can start. Ticks compare wall-clock
+/// time, so a check that fell due while the machine slept runs on the first
+/// tick after wake. Also the granularity of cron schedules in practice.
+const TICK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// What one scheduled cycle did, for the status shown in the UI.
+pub enum CycleOutcome {
+    Ran {
+        checked_count: usize,
+        failure_reason: Option<String>,
+    },
+    /// Another check round was running, so nothing was checked and the
+    /// items stay due.
+    Skipped,
+}
+
+/// How the scheduler picks and checks items; supplied by the command layer.
+#[derive(Clone, Copy)]
+pub struct SchedulerHooks {
+    /// Whether an item takes part in automatic checks at all.
+    pub is_scheduled: fn(&AppConfig, &SoftwareItem) -> bool,
+    /// Checks the given items (by id) as one round.
+    pub run_cycle: fn(&dyn Store, &[String]) -> CycleOutcome,
+}
+
+fn status() -> &'static Mutex<SchedulerStatus> {
+    static STATUS: OnceLock<Mutex<SchedulerStatus>> = OnceLock::new();
+    STATUS.get_or_init(|| Mutex::new(SchedulerStatus::default()))
+}
+
+/// When each item was last checked by a cycle that ran. A cancelled or
+/// failed check stores no result, so without this the item would be retried
+/// every tick.
+fn attempts() -> &'static Mutex<HashMap<String, DateTime<Utc>>> {
+    static ATTEMPTS: OnceLock<Mutex<HashMap<String, DateTime<Utc>>>> = OnceLock::new();
+    ATTEMPTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn waker() -> &'static Mutex<Option<Sender<()>>> {
+    static WAKER: OnceLock<Mutex<Option<Sender<()>>>> = OnceLock::new();
+    WAKER.get_or_init(|| Mutex::new(None))
+}
+
+fn load_state(store: &dyn Store) -> SchedulerStatus {
+    store
+        .read_json::<SchedulerStatus>(STATE_FILE)
+        .ok()
+        .flatten()
+        .map(|state| SchedulerStatus {
+            running: false,
+            ..state
+        })
+        .unwrap_or_default()
+}
+
+fn write_state(store: &dyn Store, state: &SchedulerStatus) -> Result<(), String> {
+    store.write_json(STATE_FILE, state)
+}
+
+pub fn current_status() -> SchedulerStatus {
+    status()
+        .lock()
+        .map(|state| state.clone())
+        .unwrap_or_default()
+}
+
+/// Stores, persists and broadcasts `next` if it differs from the current status.
+fn publish(app: &AppHandle, store: &dyn Store, next: SchedulerStatus) {
+    let changed = match status().lock() {
+        Ok(mut state) if *state != next => {
+            *state = next.clone();
+            true
+        }
+        _ => false,
+    };
+    if !changed {
+        return;
+    }
+    if let Err(error) = write_state(store, &next) {
+        eprintln!("failed to persist scheduler state: {error}");
+    }
+    if let Err(error) = app.emit(STATUS_EVENT, next) {
+        eprintln!("failed to emit scheduler status: {error}");
+    }
+}
+
+fn parse_time(raw: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(raw)
+        .ok()
+        .map(|value| value.with_timezone(&Utc))
+}
+
+struct DuePlan {
+    due_ids: Vec<String>,
+    next_run_at: Option<DateTime<Utc>>,
+}
+
+/// Splits scheduled items into those due now and the earliest future due
+/// time, based on each item's last `checked_at` and quiet hours.
+fn plan(
+    store: &dyn Store,
+    config: &AppConfig,
+    hooks: SchedulerHooks,
+    now: DateTime<Utc>,
+) -> DuePlan {
+    let latest = result_store::load_state(store).unwrap_or_default();
+    let attempted = attempts()
+        .lock()
+        .map(|attempts| attempts.clone())
+        .unwrap_or_default();
+    let mut due_ids = Vec::new();
+    let mut next_run_at: Option<DateTime<Utc>> = None;
+    for item in config
+        .items
+        .iter()
+        .filter(|item| (hooks.is_scheduled)(config, item))
+    {
+        let checked_at = latest
+            .items
+            .get(&item.id)
+            .and_then(|snapshot| parse_time(&snapshot.checked_at));
+        let last_checked = checked_at.max(attempted.get(&item.id).copied());
+        let run_at = check_schedule::next_due(config, item, last_checked, now)
+            .and_then(|due| check_schedule::outside_quiet_hours(config, due.max(now)));
+        match run_at {
+            Ok(run_at) if run_at <= now => due_ids.push(item.id.clone()),
+            Ok(run_at) => {
+                next_run_at = Some(next_run_at.map_or(run_at, |next| next.min(run_at)));
+            }
+            Err(error) => eprintln!("skipping schedule for {}: {error}", item.id),
+        }
+    }
+    DuePlan {
+        due_ids,
+        next_run_at,
+    }
+}
+
+fn tick(app: &AppHandle, store: &dyn Store, hooks: SchedulerHooks) {
+    let config = match config_store::load_or_init_config(store) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("scheduler failed to load config: {error}");
+            return;
+        }
+    };
+    let mut state = current_status();
+    state.enabled = config.auto_check_enabled;
+    if !state.enabled {
+        state.next_run_at = None;
+        publish(app, store, state);
+        return;
+    }
+
+    let now = Utc::now();
+    let plan = plan(store, &config, hooks, now);
+    if plan.due_ids.is_empty() {
+        state.next_run_at = plan.next_run_at.map(|next| next.to_rfc3339());
+        publish(app, store, state);
+        return;
+    }
+
+    state.running = true;
+    state.next_run_at = Some(now.to_rfc3339());
+    publish(app, store, state.clone());
+    let started = Instant::now();
+    let (checked_count, failure_reason) = match (hooks.run_cycle)(store, &plan.due_ids) {
+        CycleOutcome::Ran {
+            checked_count,
+            failure_reason,
+        } => (checked_count, failure_reason),
+        CycleOutcome::Skipped => {
+            state.running = false;
+            publish(app, store, state);
+            return;
+        }
+    };
+    if let Ok(mut attempts) = attempts().lock() {
+        for id in &plan.due_ids {
+            attempts.insert(id.clone(), now);
+        }
+    }
+    let finished_at = Utc::now();
+    let next_run_at = plan_after_cycle(store, &config, hooks, finished_at);
+    publish(
+        app,
+        store,
+        SchedulerStatus {
+            enabled: true,
+            running: false,
+            last_run_at: Some(finished_at.to_rfc3339()),
+            next_run_at: next_run_at.map(|next| next.to_rfc3339()),
+            last_duration_ms: Some(started.elapsed().as_millis()),
+            last_checked_count: checked_count,
+            last_failure_reason: failure_reason,
+        },
+    );
+    let _ = app.emit(LATEST_RESULTS_EVENT, ());
+    let _ = app.emit(HISTORY_EVENT, ());
+}
+
+/// Next due time once a cycle finished; items that became due during the
+/// cycle are picked up by the following tick.
+fn plan_after_cycle(
+    store: &dyn Store,
+    config: &AppConfig,
+    hooks: SchedulerHooks,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let plan = plan(store, config, hooks, now);
+    if plan.due_ids.is_empty() {
+        plan.next_run_at
+    } else {
+        Some(now)
+    }
+}
+
+fn run_loop(app: AppHandle, store: Arc<dyn Store>, hooks: SchedulerHooks, wake: Receiver<()>) {
+    loop {
+        tick(&app, store.as_ref(), hooks);
+        match wake.recv_timeout(TICK_INTERVAL) {
+            Ok(()) | Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+}
+
+/// Starts the background scheduler thread.
+pub fn start(app: AppHandle, store: Arc<dyn Store>, hooks: SchedulerHooks) {
+    if let Ok(mut state) = status().lock() {
+        *state = load_state(store.as_ref());
+    }
+    let (sender, receiver) = mpsc::channel();
+    if let Ok(mut waker) = waker().lock() {
+        *waker = Some(sender);
+    }
+    thread::spawn(move || run_loop(app, store, hooks, receiver));
+}
+
+/// Re-evaluates the schedule right away, e.g. after the interval or the
+/// auto-check toggle changed.
+pub fn notify_config_changed() {
+    if let Ok(waker) = waker().lock() {
+        if let Some(sender) = waker.as_ref() {
+            let _ = sender.send(());
+        }
+    }
+}
@@ -1,21 +1,68 @@
+use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Read};
-use std::process::{Command, Stdio};
-use std::sync::OnceLock;
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use crate::model::CommandOutput;
+use crate::model::{CommandOutput, ExecutionProfile, ExecutionProfileOverride};
+use crate::services::output_capture::{CappedBuffer, SpillFile, DEFAULT_MAX_STREAM_BYTES};
+use crate::services::run_registry::{self, CancelToken};
+
+/// Receives each output line as soon as it is read, with the stream name
+/// (`stdout` / `stderr`) as the first argument.
+pub type LineSink = Arc<dyn Fn(&str, &str) + Send + Sync>;
+
+/// Per-stream capture cap; when `spill_path` is set, the full output is
+/// also appended to that file.
+#[derive(Debug, Clone)]
+pub struct OutputLimits {
+    pub max_stream_bytes: usize,
+    pub spill_path: Option<PathBuf>,
+}
+
+impl Default for OutputLimits {
+    fn default() -> Self {
+        Self {
+            max_stream_bytes: DEFAULT_MAX_STREAM_BYTES,
+            spill_path: None,
+        }
+    }
+}
+
+/// Optional observers for a command: live output, cancellation and capture limits.
+#[derive(Clone, Default)]
+pub struct RunHooks {
+    pub sink: Option<LineSink>,
+    pub cancel: Option<CancelToken>,
+    pub limits: OutputLimits,
+}
+
+const CANCELLED_EXIT_CODE: i32 = -130;
+const TIMED_OUT_EXIT_CODE: i32 = -124;
+const POLL_INTERVAL: Duration = Duration::from_millis(40);
+/// Time between SIGTERM and SIGKILL when a command times out.
+const TERMINATE_GRACE: Duration = Duration::from_secs(3);
+/// How long to keep reading pipes after the command itself has exited.
+const PIPE_DRAIN_GRACE: Duration = Duration::from_secs(1);
 
 const PATH_RESOLVE_TIMEOUT: Duration = Duration::from_secs(5);
 
-fn resolve_interactive_path() -> Option<String> {
-    let mut child = Command::new("zsh")
-        .args(["-ilc", "printf '%s' \"$PATH\""])
-        .stdin(Stdio::null())
+/// Asks the configured shell, started the way commands are, for its PATH;
+/// GUI apps start with a minimal PATH that lacks Homebrew, nvm, etc.
+fn resolve_interactive_path(profile: &ExecutionProfile) -> Option<String> {
+    let mut cmd = shell_command(profile, "env");
+    cmd.stdin(Stdio::null())
         .stdout(Stdio::piped())
-        .stderr(Stdio::null())
-        .spawn()
-        .ok()?;
+        .stderr(Stdio::null());
+    // Own process group, so a timeout also stops what the rc files started.
+    #[cfg(unix)]
+    cmd.process_group(0);
+    let mut child = cmd.spawn().ok()?;
 
     let stdout_pipe = child.stdout.take()?;
     let reader = thread::spawn(move || {
@@ -29,6 +76,7 @@ fn resolve_interactive_path() -> Option<String> {
         match child.try_wait() {
             Ok(Some(_)) => break,
             Ok(None) if start.elapsed() >= PATH_RESOLVE_TIMEOUT => {
+                kill_process_group(child.id());
                 let _ = child.kill();
                 let _ = child.wait();
                 return None;
@@ -39,71 +87,212 @@ fn resolve_interactive_path() -> Option<String> {
     }
     let _ = child.wait();
 
-    let path = reader.join().ok()?.unwrap_or_default();
-    let trimmed = path.trim().to_string();
-    if trimmed.is_empty() { None } else { Some(trimmed) }
+    let env = reader.join().ok()?.unwrap_or_default();
+    env.lines()
+        .filter_map(|line| line.strip_prefix("PATH="))
+        .map(str::trim)
+        .rfind(|path| !path.is_empty())
+        .map(str::to_string)
+}
+
+/// Resolved once per shell invocation (shell, arguments and flags) for the
+/// process lifetime. Resolution runs outside the lock, so a slow shell does
+/// not hold up commands using another one.
+fn interactive_path(profile: &ExecutionProfile) -> Option<String> {
+    static PATHS: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+    let paths = PATHS.get_or_init(|| Mutex::new(HashMap::new()));
+    let key = format!(
+        "{} {} login={} interactive={}",
+        profile.shell,
+        profile.shell_args.join(" "),
+        profile.login,
+        profile.interactive
+    );
+    if let Some(path) = paths.lock().ok()?.get(&key) {
+        return path.clone();
+    }
+    let path = resolve_interactive_path(profile);
+    paths.lock().ok()?.entry(key).or_insert(path).clone()
+}
+
+/// Applies an item's overrides on top of the app-wide profile.
+pub fn effective_profile(
+    base: &ExecutionProfile,
+    item_override: Option<&ExecutionProfileOverride>,
+) -> ExecutionProfile {
+    let Some(item) = item_override else {
+        return base.clone();
+    };
+    let mut env = base.env.clone();
+    env.extend(item.env.clone());
+    ExecutionProfile {
+        shell: item.shell.clone().unwrap_or_else(|| base.shell.clone()),
+        shell_args: item
+            .shell_args
+            .clone()
+            .unwrap_or_else(|| base.shell_args.clone()),
+        login: item.login.unwrap_or(base.login),
+        interactive: item.interactive.unwrap_or(base.interactive),
+        env,
+        working_directory: item
+            .working_directory
+            .clone()
+            .or_else(|| base.working_directory.clone()),
+    }
+}
+
+fn expand_home(raw: &str) -> PathBuf {
+    match (raw.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(raw),
+    }
+}
+
+/// `shell_args` may end in `-c`; it is added back after the login and
+/// interactive flags, right before `command`.
+fn shell_command(profile: &ExecutionProfile, command: &str) -> Command {
+    let args = match profile.shell_args.split_last() {
+        Some((last, rest)) if last == "-c" => rest,
+        _ => &profile.shell_args[..],
+    };
+    let mut cmd = Command::new(&profile.shell);
+    cmd.args(args);
+    if profile.login {
+        cmd.arg("-l");
+    }
+    if profile.interactive {
+        cmd.arg("-i");
+    }
+    cmd.arg("-c").arg(command);
+    cmd
 }
 
-fn interactive_path() -> Option<&'static String> {
-    static PATH: OnceLock<Option<String>> = OnceLock::new();
-    PATH.get_or_init(resolve_interactive_path).as_ref()
+struct StreamReader {
+    collected: Arc<Mutex<CappedBuffer>>,
+    finished: Receiver<Result<(), String>>,
 }
 
-fn append_line(buffer: &mut String, text: &str) {
-    if !buffer.is_empty() {
-        buffer.push('\n');
+impl StreamReader {
+    /// Waits for EOF until `deadline`. A descendant that escaped the process
+    /// group can hold the pipe open indefinitely, so past the deadline the
+    /// reader thread is left behind and the output read so far is returned.
+    /// Returns the captured text and whether it was truncated.
+    fn finish(self, deadline: Instant) -> Result<(String, bool), String> {
+        let wait = deadline.saturating_duration_since(Instant::now());
+        if let Ok(Err(error)) = self.finished.recv_timeout(wait) {
+            return Err(error);
+        }
+        Ok(self
+            .collected
+            .lock()
+            .map(|collected| (collected.render(), collected.truncated()))
+            .unwrap_or_default())
     }
-    buffer.push_str(text);
 }
 
 fn spawn_reader<R>(
     reader: R,
     label: &str,
-) -> thread::JoinHandle<Result<String, String>>
+    sink: Option<LineSink>,
+    max_bytes: usize,
+    spill: Option<Arc<SpillFile>>,
+) -> StreamReader
 where
     R: Read + Send + 'static,
 {
     let stream = label.to_string();
+    let collected = Arc::new(Mutex::new(CappedBuffer::new(max_bytes)));
+    let (done_tx, finished) = mpsc::channel();
+    let buffer = Arc::clone(&collected);
     thread::spawn(move || {
         let mut buffered = BufReader::new(reader);
         let mut line = String::new();
-        let mut collected = String::new();
-        loop {
+        let result = loop {
             line.clear();
             match buffered.read_line(&mut line) {
-                Ok(0) => break,
+                Ok(0) => break Ok(()),
                 Ok(_) => {
-                    let text = line
-                        .trim_end_matches('\n')
-                        .trim_end_matches('\r')
-                        .to_string();
-                    append_line(&mut collected, &text);
-                }
-                Err(error) => {
-                    return Err(format!("failed to read {stream}: {error}"));
+                    let text = line.trim_end_matches('\n').trim_end_matches('\r');
+                    if let Some(sink) = &sink {
+                        sink(&stream, text);
+                    }
+                    if let Some(spill) = &spill {
+                        spill.write_line(&stream, text);
+                    }
+                    if let Ok(mut buffer) = buffer.lock() {
+                        buffer.push_line(text);
+                    }
                 }
+                Err(error) => break Err(format!("failed to read {stream}: {error}")),
             }
-        }
-        Ok(collected)
-    })
+        };
+        let _ = done_tx.send(result);
+    });
+    StreamReader {
+        collected,
+        finished,
+    }
 }
 
-pub fn run_shell_command(
+/// Runs `command` through the profile's shell, forwarding every line to
+/// `hooks.sink` while it is still running and stopping early when
+/// `hooks.cancel` fires.
+pub fn run_shell_command_with(
+    profile: &ExecutionProfile,
     command: &str,
     timeout_seconds: u64,
+    hooks: RunHooks,
+) -> Result<CommandOutput, String> {
+    let cmd = shell_command(profile, command);
+    run_command(cmd, profile, command, timeout_seconds, hooks)
+}
+
+/// Runs a program directly (no shell) with the same PATH, environment,
+/// capture and timeout handling as `run_shell_command_with`.
+pub fn run_program(
+    profile: &ExecutionProfile,
+    program: &str,
+    args: &[String],
+    timeout_seconds: u64,
+) -> Result<CommandOutput, String> {
+    let mut cmd = Command::new(program);
+    cmd.args(args);
+    let display = std::iter::once(program)
+        .chain(args.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ");
+    run_command(cmd, profile, &display, timeout_seconds, RunHooks::default())
+}
+
+fn run_command(
+    mut cmd: Command,
+    profile: &ExecutionProfile,
+    command: &str,
+    timeout_seconds: u64,
+    hooks: RunHooks,
 ) -> Result<CommandOutput, String> {
     let started = Instant::now();
-    let mut cmd = Command::new("zsh");
-    cmd.arg("-lc")
-        .arg(command)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
-    if let Some(path) = interactive_path() {
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    if let Some(path) = interactive_path(profile) {
         cmd.env("PATH", path);
     }
+    cmd.envs(&profile.env);
+    if let Some(dir) = &profile.working_directory {
+        cmd.current_dir(expand_home(dir));
+    }
+    // Own process group, so timeouts and cancellation also reach what the
+    // shell spawned (brew, npm, curl, ...).
+    #[cfg(unix)]
+    cmd.process_group(0);
     let mut child = cmd
         .spawn()
         .map_err(|error| format!("failed to execute command: {error}"))?;
+    let pid = child.id();
+    if let Some(token) = &hooks.cancel {
+        if !run_registry::track_child(token, pid) {
+            kill_process_group(pid);
+        }
+    }
 
     let stdout = child
         .stdout
@@ -114,36 +303,62 @@ pub fn run_shell_command(
         .take()
         .ok_or_else(|| "failed to capture command stderr".to_string())?;
 
-    let stdout_handle = spawn_reader(stdout, "stdout");
-    let stderr_handle = spawn_reader(stderr, "stderr");
+    let spill = match &hooks.limits.spill_path {
+        Some(path) => match SpillFile::create(path) {
+            Ok(file) => {
+                file.write_line("command", command);
+                Some(Arc::new(file))
+            }
+            Err(error) => {
+                eprintln!("failed to create output spill file: {error}");
+                None
+            }
+        },
+        None => None,
+    };
+    let max_bytes = hooks.limits.max_stream_bytes;
+    let stdout_reader =
+        spawn_reader(stdout, "stdout", hooks.sink.clone(), max_bytes, spill.clone());
+    let stderr_reader = spawn_reader(stderr, "stderr", hooks.sink, max_bytes, spill.clone());
 
     let timeout = Duration::from_secs(timeout_seconds.max(1));
     let mut timed_out = false;
+    let mut cancelled = false;
     loop {
-        if child
-            .try_wait()
-            .map_err(|error| format!("failed to wait command: {error}"))?
-            .is_some()
-        {
+        if has_exited(&mut child)? {
+            break;
+        }
+        if hooks.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+            cancelled = true;
+            kill_process_group(pid);
+            let _ = child.kill();
             break;
         }
         if started.elapsed() >= timeout {
             timed_out = true;
-            let _ = child.kill();
+            terminate_process_group(&mut child, pid);
             break;
         }
-        thread::sleep(Duration::from_millis(40));
+        thread::sleep(POLL_INTERVAL);
     }
 
+    // Untracked before reaping: once reaped, the pid may be reused and a
+    // cancel must not signal whatever process gets it.
+    if let Some(token) = &hooks.cancel {
+        run_registry::untrack_child(token, pid);
+    }
     let status = child
         .wait()
         .map_err(|error| format!("failed to collect command output: {error}"))?;
-    let stdout_buffer = stdout_handle
-        .join()
-        .map_err(|_| "failed to collect command stdout".to_string())??;
-    let stderr_buffer = stderr_handle
-        .join()
-        .map_err(|_| "failed to collect command stderr".to_string())??;
+    // `run_registry::cancel` may kill the group before the loop above notices.
+    if !timed_out && !status.success() {
+        cancelled |= hooks.cancel.as_ref().is_some_and(CancelToken::is_cancelled);
+    }
+    let drain_deadline = Instant::now() + PIPE_DRAIN_GRACE;
+    let (stdout_buffer, stdout_truncated) = stdout_reader.finish(drain_deadline)?;
+    let (stderr_buffer, stderr_truncated) = stderr_reader.finish(drain_deadline)?;
+    let truncated = stdout_truncated || stderr_truncated;
+    let full_output_log = spill.and_then(|spill| spill.finish());
 
     let duration_ms = started.elapsed().as_millis();
     let mut stderr = stderr_buffer.trim().to_string();
@@ -154,12 +369,20 @@ pub fn run_shell_command(
         } else {
             format!("{stderr}\n{timeout_message}")
         };
+    } else if cancelled {
+        stderr = if stderr.is_empty() {
+            "command cancelled".to_string()
+        } else {
+            format!("{stderr}\ncommand cancelled")
+        };
     }
 
     Ok(CommandOutput {
         command: command.to_string(),
         exit_code: if timed_out {
-            -124
+            TIMED_OUT_EXIT_CODE
+        } else if cancelled {
+            CANCELLED_EXIT_CODE
         } else {
             status.code().unwrap_or(-1)
         },
@@ -167,5 +390,145 @@ pub fn run_shell_command(
         stderr,
         duration_ms,
         timed_out,
+        cancelled,
+        truncated,
+        full_output_log,
     })
 }
+
+/// Whether the child exited, without reaping it: until reaped, its pid and
+/// process group cannot be reused, so signalling them stays safe.
+#[cfg(unix)]
+fn has_exited(child: &mut Child) -> Result<bool, String> {
+    let pid = libc::id_t::from(child.id());
+    // SAFETY: `siginfo_t` is plain data. It is zeroed so `si_signo` stays 0
+    // when the child has not changed state.
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    let options = libc::WEXITED | libc::WNOHANG | libc::WNOWAIT;
+    // SAFETY: `info` is a valid out pointer; WNOWAIT leaves the child unreaped.
+    if unsafe { libc::waitid(libc::P_PID, pid, &mut info, options) } != 0 {
+        let error = std::io::Error::last_os_error();
+        return Err(format!("failed to wait command: {error}"));
+    }
+    Ok(info.si_signo != 0)
+}
+
+#[cfg(not(unix))]
+fn has_exited(child: &mut Child) -> Result<bool, String> {
+    child
+        .try_wait()
+        .map(|status| status.is_some())
+        .map_err(|error| format!("failed to wait command: {error}"))
+}
+
+#[cfg(unix)]
+fn signal_process_group(pid: u32, signal: libc::c_int) {
+    if let Ok(pgid) = i32::try_from(pid) {
+        // SAFETY: plain syscall; a stale pgid only yields ESRCH.
+        unsafe {
+            libc::killpg(pgid, signal);
+        }
+    }
+}
+
+/// Sends SIGKILL to the process group led by `pid`.
+pub fn kill_process_group(pid: u32) {
+    #[cfg(unix)]
+    signal_process_group(pid, libc::SIGKILL);
+    #[cfg(not(unix))]
+    let _ = pid;
+}
+
+/// SIGTERM to the whole group, up to `TERMINATE_GRACE` for it to exit, then
+/// SIGKILL for anything still alive.
+fn terminate_process_group(child: &mut Child, pid: u32) {
+    #[cfg(unix)]
+    signal_process_group(pid, libc::SIGTERM);
+    let deadline = Instant::now() + TERMINATE_GRACE;
+    while Instant::now() < deadline {
+        if !matches!(has_exited(child), Ok(false)) {
+            break;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+    // Descendants may ignore SIGTERM even after the shell itself has exited.
+    kill_process_group(pid);
+    let _ = child.kill();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::run_registry::RunHandle;
+
+    fn sh() -> ExecutionProfile {
+        ExecutionProfile {
+            shell: "/bin/sh".to_string(),
+            login: false,
+            ..ExecutionProfile::default()
+        }
+    }
+
+    #[test]
+    fn a_trailing_dash_c_is_not_repeated() {
+        let profile = ExecutionProfile {
+            shell_args: vec!["-e".to_string(), "-c".to_string()],
+            login: true,
+            interactive: true,
+            ..sh()
+        };
+        let cmd = shell_command(&profile, "env");
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(args, ["-e", "-l", "-i", "-c", "env"]);
+    }
+
+    #[test]
+    fn the_path_probe_starts_the_shell_like_commands() {
+        let profile = ExecutionProfile {
+            shell_args: vec!["-c".to_string()],
+            ..sh()
+        };
+        let path = resolve_interactive_path(&profile).unwrap();
+        assert!(!path.is_empty());
+    }
+
+    #[test]
+    fn reports_the_exit_code_and_output() {
+        let command = "echo out; echo err >&2; exit 3";
+        let output = run_shell_command_with(&sh(), command, 10, RunHooks::default()).unwrap();
+        assert_eq!(output.exit_code, 3);
+        assert_eq!(output.stdout, "out");
+        assert_eq!(output.stderr, "err");
+        assert!(!output.cancelled && !output.timed_out);
+    }
+
+    #[test]
+    fn timed_out_commands_are_terminated() {
+        let started = Instant::now();
+        let output = run_shell_command_with(&sh(), "sleep 30", 1, RunHooks::default()).unwrap();
+        assert!(output.timed_out);
+        assert_eq!(output.exit_code, TIMED_OUT_EXIT_CODE);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn cancelling_a_run_kills_its_process_group() {
+        let run = RunHandle::register("test", "cancel");
+        let run_id = run.run_id().to_string();
+        let hooks = RunHooks {
+            cancel: Some(run.token()),
+            ..RunHooks::default()
+        };
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(300));
+            run_registry::cancel(&run_id)
+        });
+
+        let started = Instant::now();
+        let output = run_shell_command_with(&sh(), "sleep 30 & sleep 30; wait", 60, hooks).unwrap();
+        canceller.join().unwrap().unwrap();
+        assert!(output.cancelled);
+        assert_eq!(output.exit_code, CANCELLED_EXIT_CODE);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,119 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "app")]
+use tauri::{AppHandle, Manager};
+
+const CONFIG_FILE: &str = "software-items.json";
+/// `identifier` in `tauri.conf.json`; names the app's config directory.
+const APP_IDENTIFIER: &str = "com.firegnu.patchpilot";
+
+/// Where the stores live: the config file, the stores kept next to it, and
+/// the data directory holding history, run logs and scheduler state.
+#[derive(Debug, Clone)]
+pub struct StoragePaths {
+    data_dir: PathBuf,
+    config_path: PathBuf,
+}
+
+fn config_path_from_current_dir() -> Option<PathBuf> {
+    let current = std::env::current_dir().ok()?;
+    let direct = current.join(CONFIG_FILE);
+    if direct.exists() {
+        return Some(direct);
+    }
+
+    let nested = current.join("config").join(CONFIG_FILE);
+    if nested.exists() {
+        return Some(nested);
+    }
+
+    let parent_nested = current.parent()?.join("config").join(CONFIG_FILE);
+    if parent_nested.exists() {
+        return Some(parent_nested);
+    }
+
+    None
+}
+
+fn env_dir(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+}
+
+/// The platform config directory, as Tauri resolves it.
+fn platform_config_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else if cfg!(windows) {
+        env_dir("APPDATA")
+    } else {
+        env_dir("XDG_CONFIG_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".config")))
+    }
+}
+
+fn ensure_dir(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|error| format!("failed to create {}: {error}", dir.display()))
+}
+
+impl StoragePaths {
+    /// Everything under `data_dir`, e.g. a `--data-dir` given to the CLI.
+    pub fn new(data_dir: impl Into<PathBuf>) -> Self {
+        let data_dir = data_dir.into();
+        Self {
+            config_path: data_dir.join(CONFIG_FILE),
+            data_dir,
+        }
+    }
+
+    /// Like `new`, except that a `software-items.json` in (or in `config/`
+    /// under) the current directory takes precedence for the config and the
+    /// stores next to it.
+    fn with_default_config(data_dir: PathBuf) -> Self {
+        Self {
+            config_path: config_path_from_current_dir()
+                .unwrap_or_else(|| data_dir.join(CONFIG_FILE)),
+            data_dir,
+        }
+    }
+
+    /// The app's config directory.
+    #[cfg(feature = "app")]
+    pub fn from_app(app: &AppHandle) -> Result<Self, String> {
+        let data_dir = app
+            .path()
+            .app_config_dir()
+            .map_err(|error| format!("failed to get app config directory: {error}"))?;
+        Ok(Self::with_default_config(data_dir))
+    }
+
+    /// The directory the app uses, resolved without a running app.
+    pub fn from_default_dir() -> Result<Self, String> {
+        let config_dir = platform_config_dir()
+            .ok_or_else(|| "failed to get the config directory: HOME is not set".to_string())?;
+        Ok(Self::with_default_config(config_dir.join(APP_IDENTIFIER)))
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    pub fn config_path(&self) -> Result<PathBuf, String> {
+        if let Some(dir) = self.config_path.parent() {
+            ensure_dir(dir)?;
+        }
+        Ok(self.config_path.clone())
+    }
+
+    /// A store file next to the config file.
+    pub fn store_path(&self, file_name: &str) -> Result<PathBuf, String> {
+        Ok(self.config_path()?.with_file_name(file_name))
+    }
+
+    /// A file or directory in the data directory.
+    pub fn data_path(&self, name: &str) -> Result<PathBuf, String> {
+        ensure_dir(&self.data_dir)?;
+        Ok(self.data_dir.join(name))
+    }
+}
@@ -0,0 +1,498 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Cursor, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+#[cfg(feature = "app")]
+use tauri::AppHandle;
+
+use super::storage_paths::StoragePaths;
+use super::{file_lock, json_file};
+
+/// Which directory a store file belongs in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StoreDir {
+    /// Next to the config file: the config, its backups, latest results and
+    /// the version timeline.
+    Config,
+    /// The data directory: history, run logs and scheduler state.
+    Data,
+}
+
+impl StoreDir {
+    fn as_str(self) -> &'static str {
+        match self {
+            StoreDir::Config => "config",
+            StoreDir::Data => "data",
+        }
+    }
+}
+
+/// A file in a store, named relative to its directory.
+#[derive(Debug, Clone, Copy)]
+pub struct StoreFile<'a> {
+    pub dir: StoreDir,
+    pub name: &'a str,
+}
+
+impl<'a> StoreFile<'a> {
+    pub const fn config(name: &'a str) -> Self {
+        Self {
+            dir: StoreDir::Config,
+            name,
+        }
+    }
+
+    pub const fn data(name: &'a str) -> Self {
+        Self {
+            dir: StoreDir::Data,
+            name,
+        }
+    }
+}
+
+/// Where the services keep their files. `FileStore` is the one the app and
+/// the CLI use; `MemoryStore` keeps everything in memory, so a service can be
+/// exercised without touching disk.
+pub trait Store: Send + Sync {
+    /// The directories behind the store, if it keeps files on disk. Run logs
+    /// and the config schema are only written when it does.
+    fn paths(&self) -> Option<&StoragePaths>;
+
+    /// Where `file` lives, for messages and as a cache key.
+    fn location(&self, file: StoreFile) -> String;
+
+    /// The size of `file` in bytes, or `None` when it does not exist.
+    fn size(&self, file: StoreFile) -> Result<Option<u64>, String>;
+
+    /// Reads `file`, or `None` when it does not exist. When `valid` rejects
+    /// the contents but accepts the backup kept by `write`, the backup is
+    /// restored.
+    fn read(&self, file: StoreFile, valid: &dyn Fn(&str) -> bool)
+        -> Result<Option<String>, String>;
+
+    /// Reads `file` from byte `offset` on, or `None` when it does not exist.
+    fn read_from(
+        &self,
+        file: StoreFile,
+        offset: u64,
+    ) -> Result<Option<Box<dyn BufRead + '_>>, String>;
+
+    /// Replaces `file` in one step, keeping the old version as its backup.
+    fn write(&self, file: StoreFile, contents: &str) -> Result<(), String>;
+
+    /// Like `write`, without a backup; for logs rewritten in full.
+    fn replace(&self, file: StoreFile, contents: &str) -> Result<(), String>;
+
+    /// Appends `line`, first ending a partial last line a crash left behind.
+    fn append(&self, file: StoreFile, line: &str) -> Result<(), String>;
+
+    fn copy(&self, from: StoreFile, to: StoreFile) -> Result<(), String>;
+
+    fn rename(&self, from: StoreFile, to: StoreFile) -> Result<(), String>;
+
+    /// Removes `file`; a file that does not exist is not an error.
+    fn remove(&self, file: StoreFile) -> Result<(), String>;
+
+    /// Names of the files in `dir` starting with `prefix`, sorted.
+    fn list(&self, dir: StoreDir, prefix: &str) -> Result<Vec<String>, String>;
+
+    /// Runs `f` holding the lock on `file`, which every read-modify-write of
+    /// it takes. Not reentrant: `f` must not lock again.
+    fn locked(
+        &self,
+        file: StoreFile,
+        f: &mut dyn FnMut() -> Result<(), String>,
+    ) -> Result<(), String>;
+}
+
+impl dyn Store + '_ {
+    /// Reads and parses `file`, restoring its backup if it no longer parses.
+    pub fn read_json<T: DeserializeOwned>(&self, file: StoreFile) -> Result<Option<T>, String> {
+        let valid = |data: &str| serde_json::from_str::<T>(data).is_ok();
+        let Some(data) = self.read(file, &valid)? else {
+            return Ok(None);
+        };
+        serde_json::from_str(&data)
+            .map(Some)
+            .map_err(|error| format!("failed to parse {}: {error}", self.location(file)))
+    }
+
+    /// Like `read_json`, but fails with the parse error instead of falling
+    /// back to the backup; for files people edit by hand.
+    pub fn read_json_strict<T: DeserializeOwned>(
+        &self,
+        file: StoreFile,
+    ) -> Result<Option<T>, String> {
+        let Some(data) = self.read(file, &|_| true)? else {
+            return Ok(None);
+        };
+        serde_json::from_str(&data)
+            .map(Some)
+            .map_err(|error| format!("failed to parse {}: {error}", self.location(file)))
+    }
+
+    pub fn write_json<T: Serialize>(&self, file: StoreFile, value: &T) -> Result<(), String> {
+        let payload = serde_json::to_string_pretty(value)
+            .map_err(|error| format!("failed to serialize {}: {error}", self.location(file)))?;
+        self.write(file, &payload)
+    }
+
+    /// Read-modify-write of `file` under its lock, so concurrent commands and
+    /// other app instances never drop each other's changes.
+    pub fn update_json<T, R>(
+        &self,
+        file: StoreFile,
+        mutate: impl FnOnce(&mut T) -> R,
+    ) -> Result<R, String>
+    where
+        T: Serialize + DeserializeOwned + Default,
+    {
+        let mut mutate = Some(mutate);
+        let mut result = None;
+        self.locked(file, &mut || {
+            let mut value = self.read_json::<T>(file)?.unwrap_or_default();
+            if let Some(mutate) = mutate.take() {
+                result = Some(mutate(&mut value));
+            }
+            self.write_json(file, &value)
+        })?;
+        result.ok_or_else(|| format!("failed to update {}", self.location(file)))
+    }
+}
+
+/// Files under `StoragePaths`, written atomically and locked across
+/// processes.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    paths: StoragePaths,
+}
+
+impl FileStore {
+    pub fn new(paths: StoragePaths) -> Self {
+        Self { paths }
+    }
+
+    #[cfg(feature = "app")]
+    pub fn from_app(app: &AppHandle) -> Result<Self, String> {
+        Ok(Self::new(StoragePaths::from_app(app)?))
+    }
+
+    pub fn path(&self, file: StoreFile) -> Result<PathBuf, String> {
+        match file.dir {
+            StoreDir::Config => self.paths.store_path(file.name),
+            StoreDir::Data => self.paths.data_path(file.name),
+        }
+    }
+}
+
+fn ends_with_newline(file: &mut File) -> std::io::Result<bool> {
+    if file.seek(SeekFrom::End(0))? == 0 {
+        return Ok(true);
+    }
+    file.seek(SeekFrom::End(-1))?;
+    let mut last = [0u8];
+    file.read_exact(&mut last)?;
+    Ok(last[0] == b'\n')
+}
+
+fn append_line(path: &Path, line: &str) -> std::io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)?;
+    // A crash mid-append leaves a partial line; don't glue onto it.
+    if !ends_with_newline(&mut file)? {
+        file.write_all(b"\n")?;
+    }
+    file.write_all(line.as_bytes())?;
+    file.sync_data()
+}
+
+impl Store for FileStore {
+    fn paths(&self) -> Option<&StoragePaths> {
+        Some(&self.paths)
+    }
+
+    fn location(&self, file: StoreFile) -> String {
+        self.path(file)
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|_| file.name.to_string())
+    }
+
+    fn size(&self, file: StoreFile) -> Result<Option<u64>, String> {
+        let path = self.path(file)?;
+        match fs::metadata(&path) {
+            Ok(metadata) => Ok(Some(metadata.len())),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(format!("failed to read {}: {error}", path.display())),
+        }
+    }
+
+    fn read(
+        &self,
+        file: StoreFile,
+        valid: &dyn Fn(&str) -> bool,
+    ) -> Result<Option<String>, String> {
+        json_file::read(&self.path(file)?, valid)
+    }
+
+    fn read_from(
+        &self,
+        file: StoreFile,
+        offset: u64,
+    ) -> Result<Option<Box<dyn BufRead + '_>>, String> {
+        let path = self.path(file)?;
+        let mut handle = match File::open(&path) {
+            Ok(handle) => handle,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(format!("failed to read {}: {error}", path.display())),
+        };
+        handle
+            .seek(SeekFrom::Start(offset))
+            .map_err(|error| format!("failed to read {}: {error}", path.display()))?;
+        Ok(Some(Box::new(BufReader::new(handle))))
+    }
+
+    fn write(&self, file: StoreFile, contents: &str) -> Result<(), String> {
+        json_file::write(&self.path(file)?, contents)
+    }
+
+    fn replace(&self, file: StoreFile, contents: &str) -> Result<(), String> {
+        json_file::replace(&self.path(file)?, contents)
+    }
+
+    fn append(&self, file: StoreFile, line: &str) -> Result<(), String> {
+        let path = self.path(file)?;
+        append_line(&path, line)
+            .map_err(|error| format!("failed to append to {}: {error}", path.display()))
+    }
+
+    fn copy(&self, from: StoreFile, to: StoreFile) -> Result<(), String> {
+        let (from, to) = (self.path(from)?, self.path(to)?);
+        fs::copy(&from, &to).map_err(|error| {
+            format!(
+                "failed to copy {} to {}: {error}",
+                from.display(),
+                to.display()
+            )
+        })?;
+        Ok(())
+    }
+
+    fn rename(&self, from: StoreFile, to: StoreFile) -> Result<(), String> {
+        let (from, to) = (self.path(from)?, self.path(to)?);
+        fs::rename(&from, &to)
+            .map_err(|error| format!("failed to rename {}: {error}", from.display()))
+    }
+
+    fn remove(&self, file: StoreFile) -> Result<(), String> {
+        let path = self.path(file)?;
+        match fs::remove_file(&path) {
+            Err(error) if error.kind() != ErrorKind::NotFound => {
+                Err(format!("failed to remove {}: {error}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn list(&self, dir: StoreDir, prefix: &str) -> Result<Vec<String>, String> {
+        let dir_path = match dir {
+            StoreDir::Config => self
+                .paths
+                .config_path()?
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .map_or_else(|| PathBuf::from("."), Path::to_path_buf),
+            StoreDir::Data => self.paths.data_dir().to_path_buf(),
+        };
+        let entries = match fs::read_dir(&dir_path) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(format!("failed to list {}: {error}", dir_path.display())),
+        };
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| name.starts_with(prefix))
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn locked(
+        &self,
+        file: StoreFile,
+        f: &mut dyn FnMut() -> Result<(), String>,
+    ) -> Result<(), String> {
+        let _lock = file_lock::lock(&self.path(file)?)?;
+        f()
+    }
+}
+
+type MemoryKey = (StoreDir, String);
+
+/// Everything in memory; each instance starts empty.
+#[derive(Debug)]
+pub struct MemoryStore {
+    id: u64,
+    files: Mutex<HashMap<MemoryKey, String>>,
+    lock: Mutex<()>,
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        Self {
+            id: COUNTER.fetch_add(1, Ordering::Relaxed),
+            files: Mutex::new(HashMap::new()),
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+fn key(file: StoreFile) -> MemoryKey {
+    (file.dir, file.name.to_string())
+}
+
+fn backup_key(file: StoreFile) -> MemoryKey {
+    (file.dir, format!("{}.bak", file.name))
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn files(&self) -> Result<std::sync::MutexGuard<'_, HashMap<MemoryKey, String>>, String> {
+        self.files
+            .lock()
+            .map_err(|_| "memory store lock poisoned".to_string())
+    }
+
+    fn missing(&self, file: StoreFile) -> String {
+        format!("{} does not exist", self.location(file))
+    }
+}
+
+impl Store for MemoryStore {
+    fn paths(&self) -> Option<&StoragePaths> {
+        None
+    }
+
+    fn location(&self, file: StoreFile) -> String {
+        format!("memory:{}/{}/{}", self.id, file.dir.as_str(), file.name)
+    }
+
+    fn size(&self, file: StoreFile) -> Result<Option<u64>, String> {
+        Ok(self.files()?.get(&key(file)).map(|data| data.len() as u64))
+    }
+
+    fn read(
+        &self,
+        file: StoreFile,
+        valid: &dyn Fn(&str) -> bool,
+    ) -> Result<Option<String>, String> {
+        let mut files = self.files()?;
+        let Some(data) = files.get(&key(file)).cloned() else {
+            return Ok(None);
+        };
+        if valid(&data) {
+            return Ok(Some(data));
+        }
+        match files.get(&backup_key(file)).cloned() {
+            Some(backup) if valid(&backup) => {
+                files.insert(key(file), backup.clone());
+                Ok(Some(backup))
+            }
+            _ => Ok(Some(data)),
+        }
+    }
+
+    fn read_from(
+        &self,
+        file: StoreFile,
+        offset: u64,
+    ) -> Result<Option<Box<dyn BufRead + '_>>, String> {
+        let files = self.files()?;
+        let Some(data) = files.get(&key(file)) else {
+            return Ok(None);
+        };
+        let start = (offset as usize).min(data.len());
+        Ok(Some(Box::new(Cursor::new(
+            data.as_bytes()[start..].to_vec(),
+        ))))
+    }
+
+    fn write(&self, file: StoreFile, contents: &str) -> Result<(), String> {
+        let mut files = self.files()?;
+        if let Some(old) = files.insert(key(file), contents.to_string()) {
+            files.insert(backup_key(file), old);
+        }
+        Ok(())
+    }
+
+    fn replace(&self, file: StoreFile, contents: &str) -> Result<(), String> {
+        self.files()?.insert(key(file), contents.to_string());
+        Ok(())
+    }
+
+    fn append(&self, file: StoreFile, line: &str) -> Result<(), String> {
+        let mut files = self.files()?;
+        let data = files.entry(key(file)).or_default();
+        if !data.is_empty() && !data.ends_with('\n') {
+            data.push('\n');
+        }
+        data.push_str(line);
+        Ok(())
+    }
+
+    fn copy(&self, from: StoreFile, to: StoreFile) -> Result<(), String> {
+        let mut files = self.files()?;
+        let data = files
+            .get(&key(from))
+            .cloned()
+            .ok_or_else(|| self.missing(from))?;
+        files.insert(key(to), data);
+        Ok(())
+    }
+
+    fn rename(&self, from: StoreFile, to: StoreFile) -> Result<(), String> {
+        let mut files = self.files()?;
+        let data = files.remove(&key(from)).ok_or_else(|| self.missing(from))?;
+        files.insert(key(to), data);
+        Ok(())
+    }
+
+    fn remove(&self, file: StoreFile) -> Result<(), String> {
+        self.files()?.remove(&key(file));
+        Ok(())
+    }
+
+    fn list(&self, dir: StoreDir, prefix: &str) -> Result<Vec<String>, String> {
+        let mut names: Vec<String> = self
+            .files()?
+            .keys()
+            .filter(|(file_dir, name)| *file_dir == dir && name.starts_with(prefix))
+            .map(|(_, name)| name.clone())
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn locked(
+        &self,
+        _file: StoreFile,
+        f: &mut dyn FnMut() -> Result<(), String>,
+    ) -> Result<(), String> {
+        let _guard = self
+            .lock
+            .lock()
+            .map_err(|_| "memory store lock poisoned".to_string())?;
+        f()
+    }
+}
@@ -0,0 +1,310 @@
+use std::cmp::Ordering;
+
+use crate::model::{VersionComparator, VersionComparison};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Identifier {
+    Numeric(u64),
+    Alpha(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ParsedVersion {
+    release: Vec<u64>,
+    pre: Vec<Identifier>,
+    build: Option<u64>,
+}
+
+fn strip_prefix(value: &str) -> &str {
+    let trimmed = value.trim();
+    let trimmed = trimmed
+        .strip_prefix("version ")
+        .or_else(|| trimmed.strip_prefix("Version "))
+        .unwrap_or(trimmed)
+        .trim_start();
+    match trimmed.strip_prefix(['v', 'V']) {
+        Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()) => rest,
+        _ => trimmed,
+    }
+}
+
+/// Splits off build metadata: semver `+meta`, Homebrew cask `1.2,345`,
+/// Homebrew formula revision `1.2.3_1`, or a trailing `(build 5)` annotation.
+fn split_build(value: &str) -> (&str, Option<&str>) {
+    let index = value.find(['+', ',', '(', ' ']);
+    let (core, build) = match index {
+        Some(index) => (value[..index].trim(), Some(value[index..].trim())),
+        None => (value, None),
+    };
+    match core.rsplit_once('_') {
+        Some((version, revision))
+            if !version.contains('_')
+                && !revision.is_empty()
+                && revision.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            (version, Some(&core[version.len()..]))
+        }
+        _ => (core, build),
+    }
+}
+
+fn parse_build_number(raw: &str) -> Option<u64> {
+    let digits: String = raw
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+fn parse_identifiers(raw: &str) -> Option<Vec<Identifier>> {
+    let mut identifiers = Vec::new();
+    for part in raw.split(['.', '-', '_']).filter(|part| !part.is_empty()) {
+        let mut rest = part;
+        while !rest.is_empty() {
+            let numeric = rest.starts_with(|c: char| c.is_ascii_digit());
+            let end = rest
+                .find(|c: char| c.is_ascii_digit() != numeric)
+                .unwrap_or(rest.len());
+            let (head, tail) = rest.split_at(end);
+            if numeric {
+                identifiers.push(Identifier::Numeric(head.parse().ok()?));
+            } else if head.chars().all(|c| c.is_ascii_alphanumeric()) {
+                identifiers.push(Identifier::Alpha(head.to_ascii_lowercase()));
+            } else {
+                return None;
+            }
+            rest = tail;
+        }
+    }
+    Some(identifiers)
+}
+
+fn parse_release(core: &str) -> Option<(Vec<u64>, &str)> {
+    let mut release = Vec::new();
+    let mut rest = core;
+    loop {
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return None;
+        }
+        release.push(rest[..end].parse().ok()?);
+        rest = &rest[end..];
+        match rest.strip_prefix('.') {
+            Some(next) if next.starts_with(|c: char| c.is_ascii_digit()) => rest = next,
+            _ => break,
+        }
+    }
+    Some((release, rest))
+}
+
+fn parse_general(value: &str) -> Option<ParsedVersion> {
+    let (core, build) = split_build(strip_prefix(value));
+    let (release, rest) = parse_release(core)?;
+    // "1a2b3c" is far more likely a short commit hash than version 1 pre "a2b3c".
+    if release.len() == 1 && rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let pre = parse_identifiers(rest.trim_start_matches(['-', '.', '_']))?;
+    Some(ParsedVersion {
+        release,
+        pre,
+        build: build.and_then(parse_build_number),
+    })
+}
+
+/// Build metadata after `+` is dropped: SemVer gives it no precedence.
+fn parse_semver(value: &str) -> Option<ParsedVersion> {
+    let core = match strip_prefix(value).split_once('+') {
+        Some((core, _)) => core,
+        None => strip_prefix(value),
+    };
+    let (release, rest) = parse_release(core)?;
+    if release.len() != 3 {
+        return None;
+    }
+    let pre = match rest {
+        "" => Vec::new(),
+        _ => parse_identifiers(rest.strip_prefix('-')?)?,
+    };
+    Some(ParsedVersion {
+        release,
+        pre,
+        build: None,
+    })
+}
+
+fn parse_dotted(value: &str) -> Option<ParsedVersion> {
+    let (core, build) = split_build(strip_prefix(value));
+    let (release, _) = parse_release(core)?;
+    Some(ParsedVersion {
+        release,
+        pre: Vec::new(),
+        build: build.and_then(parse_build_number),
+    })
+}
+
+fn parse_calendar(value: &str) -> Option<ParsedVersion> {
+    let normalized = strip_prefix(value).replace(['-', '_'], ".");
+    let mut parsed = parse_dotted(&normalized)?;
+    if let Some(year) = parsed.release.first_mut() {
+        if *year < 100 {
+            *year += 2000;
+        }
+    }
+    Some(parsed)
+}
+
+fn compare_release(left: &[u64], right: &[u64]) -> Ordering {
+    let len = left.len().max(right.len());
+    (0..len)
+        .map(|index| {
+            let a = left.get(index).copied().unwrap_or(0);
+            let b = right.get(index).copied().unwrap_or(0);
+            a.cmp(&b)
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+fn compare_identifier(left: &Identifier, right: &Identifier) -> Ordering {
+    match (left, right) {
+        (Identifier::Numeric(a), Identifier::Numeric(b)) => a.cmp(b),
+        (Identifier::Numeric(_), Identifier::Alpha(_)) => Ordering::Less,
+        (Identifier::Alpha(_), Identifier::Numeric(_)) => Ordering::Greater,
+        (Identifier::Alpha(a), Identifier::Alpha(b)) => a.cmp(b),
+    }
+}
+
+fn compare_pre(left: &[Identifier], right: &[Identifier]) -> Ordering {
+    match (left.is_empty(), right.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Greater,
+        (false, true) => return Ordering::Less,
+        (false, false) => {}
+    }
+    left.iter()
+        .zip(right)
+        .map(|(a, b)| compare_identifier(a, b))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or_else(|| left.len().cmp(&right.len()))
+}
+
+fn compare_parsed(current: &ParsedVersion, latest: &ParsedVersion) -> Ordering {
+    compare_release(&latest.release, &current.release)
+        .then_with(|| compare_pre(&latest.pre, &current.pre))
+        .then_with(|| match (latest.build, current.build) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            _ => Ordering::Equal,
+        })
+}
+
+fn to_comparison(ordering: Ordering) -> VersionComparison {
+    match ordering {
+        Ordering::Greater => VersionComparison::Newer,
+        Ordering::Equal => VersionComparison::Same,
+        Ordering::Less => VersionComparison::Older,
+    }
+}
+
+/// Compares `latest` against `current`: `Newer` means the published version
+/// is ahead of the installed one.
+pub fn compare_versions(
+    comparator: VersionComparator,
+    current: &str,
+    latest: &str,
+) -> VersionComparison {
+    if current.trim() == latest.trim() {
+        return VersionComparison::Same;
+    }
+
+    let parse: fn(&str) -> Option<ParsedVersion> = match comparator {
+        VersionComparator::Exact => return VersionComparison::Newer,
+        VersionComparator::Auto => parse_general,
+        VersionComparator::Semver => parse_semver,
+        VersionComparator::Dotted => parse_dotted,
+        VersionComparator::Calendar => parse_calendar,
+    };
+
+    match (parse(current), parse(latest)) {
+        (Some(current), Some(latest)) => to_comparison(compare_parsed(&current, &latest)),
+        _ => VersionComparison::Incomparable,
+    }
+}
+
+/// An update is reported when the published version is ahead, or when the
+/// two strings differ and cannot be ordered (keeps the old `!=` behaviour).
+pub fn has_update(comparison: VersionComparison) -> bool {
+    matches!(
+        comparison,
+        VersionComparison::Newer | VersionComparison::Incomparable
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn semver_ignores_build_metadata() {
+        let compare =
+            |current, latest| compare_versions(VersionComparator::Semver, current, latest);
+        assert_eq!(
+            compare("1.2.3+build.5", "1.2.3+build.9"),
+            VersionComparison::Same
+        );
+        assert_eq!(compare("1.2.3+9", "1.2.3"), VersionComparison::Same);
+        assert_eq!(compare("1.2.3+9", "1.2.4+1"), VersionComparison::Newer);
+        assert_eq!(compare("1.2.3", "1.2.3-rc.1+7"), VersionComparison::Older);
+    }
+
+    #[test]
+    fn auto_handles_prefixes_builds_and_prereleases() {
+        let compare = |current, latest| compare_versions(VersionComparator::Auto, current, latest);
+        assert_eq!(compare("1.2.10", "v1.2.10"), VersionComparison::Same);
+        assert_eq!(compare("1.2.9", "1.2.10"), VersionComparison::Newer);
+        assert_eq!(compare("2.0.0 (build 5)", "2.0.0"), VersionComparison::Same);
+        assert_eq!(compare("2.0.0-beta.1", "2.0.0"), VersionComparison::Newer);
+        assert_eq!(
+            compare("2.0.0-beta.2", "2.0.0-beta.10"),
+            VersionComparison::Newer
+        );
+        // The installed version is ahead of the published one.
+        assert_eq!(compare("1.3.0", "1.2.9"), VersionComparison::Older);
+        assert!(!has_update(compare("1.3.0", "1.2.9")));
+    }
+
+    #[test]
+    fn homebrew_revisions_are_build_numbers() {
+        let compare = |current, latest| compare_versions(VersionComparator::Auto, current, latest);
+        assert_eq!(compare("1.2.3", "1.2.3_1"), VersionComparison::Same);
+        assert_eq!(compare("1.2.3_1", "1.2.3_2"), VersionComparison::Newer);
+        assert_eq!(compare("1.2.3_1", "1.2.4"), VersionComparison::Newer);
+        assert_eq!(
+            compare_versions(VersionComparator::Dotted, "1.2.3_2", "1.2.3_1"),
+            VersionComparison::Older
+        );
+    }
+
+    #[test]
+    fn calendar_versions_expand_short_years() {
+        let compare =
+            |current, latest| compare_versions(VersionComparator::Calendar, current, latest);
+        assert_eq!(compare("24.1.20", "2024.01.20"), VersionComparison::Same);
+        assert_eq!(
+            compare("2024.01.20", "2024-02-01"),
+            VersionComparison::Newer
+        );
+        assert_eq!(compare("2025.1", "24.12"), VersionComparison::Older);
+    }
+
+    #[test]
+    fn auto_still_orders_cask_build_numbers() {
+        assert_eq!(
+            compare_versions(VersionComparator::Auto, "1.2,345", "1.2,346"),
+            VersionComparison::Newer
+        );
+    }
+}
@@ -0,0 +1,408 @@
+use std::time::Duration;
+
+use regex::Regex;
+use serde_json::Value;
+use url::Url;
+
+use crate::model::VersionSource;
+
+const USER_AGENT: &str = concat!("PatchPilot/", env!("CARGO_PKG_VERSION"));
+
+/// Base URLs for the registries behind each `VersionSource` variant.
+/// Overridable so providers can be pointed at a local stand-in server.
+#[derive(Debug, Clone)]
+pub struct Endpoints {
+    pub github_api: String,
+    pub npm_registry: String,
+    pub homebrew_api: String,
+    pub pypi: String,
+    pub crates_io: String,
+    /// Sent as a bearer token with requests to `github_api`, and only there.
+    pub github_token: Option<String>,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            github_api: "https://api.github.com".to_string(),
+            npm_registry: "https://registry.npmjs.org".to_string(),
+            homebrew_api: "https://formulae.brew.sh/api".to_string(),
+            pypi: "https://pypi.org/pypi".to_string(),
+            crates_io: "https://crates.io/api/v1".to_string(),
+            github_token: std::env::var("GITHUB_TOKEN")
+                .ok()
+                .map(|token| token.trim().to_string())
+                .filter(|token| !token.is_empty()),
+        }
+    }
+}
+
+/// Whether `url` points at the same host and port as `base`.
+fn same_host(url: &str, base: &str) -> bool {
+    let (Ok(url), Ok(base)) = (Url::parse(url), Url::parse(base)) else {
+        return false;
+    };
+    url.host_str().is_some()
+        && url.host_str() == base.host_str()
+        && url.port_or_known_default() == base.port_or_known_default()
+}
+
+fn fetch_text(endpoints: &Endpoints, url: &str, timeout_seconds: u64) -> Result<String, String> {
+    let mut request = ureq::get(url)
+        .timeout(Duration::from_secs(timeout_seconds.max(1)))
+        .set("User-Agent", USER_AGENT);
+    if same_host(url, &endpoints.github_api) {
+        request = request.set("Accept", "application/vnd.github+json");
+        if let Some(token) = &endpoints.github_token {
+            request = request.set("Authorization", &format!("Bearer {token}"));
+        }
+    }
+
+    let response = request.call().map_err(|error| match error {
+        ureq::Error::Status(code, _) => format!("request to {url} failed with HTTP {code}"),
+        ureq::Error::Transport(transport) => format!("request to {url} failed: {transport}"),
+    })?;
+    response
+        .into_string()
+        .map_err(|error| format!("failed to read response from {url}: {error}"))
+}
+
+fn fetch_json(endpoints: &Endpoints, url: &str, timeout_seconds: u64) -> Result<Value, String> {
+    let body = fetch_text(endpoints, url, timeout_seconds)?;
+    serde_json::from_str(&body).map_err(|error| format!("invalid JSON from {url}: {error}"))
+}
+
+fn value_at(document: &Value, pointer: &str, url: &str) -> Result<String, String> {
+    match document.pointer(pointer) {
+        Some(Value::String(value)) => Ok(value.clone()),
+        Some(Value::Number(value)) => Ok(value.to_string()),
+        Some(other) => Err(format!("{pointer} in {url} is not a string: {other}")),
+        None => Err(format!("{pointer} not found in {url}")),
+    }
+}
+
+fn strip_tag_prefix(tag: &str) -> String {
+    match tag.strip_prefix(['v', 'V']) {
+        Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()) => rest.to_string(),
+        _ => tag.to_string(),
+    }
+}
+
+fn match_pattern(body: &str, pattern: &str, url: &str) -> Result<String, String> {
+    let regex =
+        Regex::new(pattern).map_err(|error| format!("invalid pattern for {url}: {error}"))?;
+    let captures = regex
+        .captures(body)
+        .ok_or_else(|| format!("pattern {pattern} did not match {url}"))?;
+    let matched = captures.get(1).or_else(|| captures.get(0));
+    Ok(matched
+        .map(|value| value.as_str().to_string())
+        .unwrap_or_default())
+}
+
+pub fn fetch_latest_with(
+    endpoints: &Endpoints,
+    source: &VersionSource,
+    timeout_seconds: u64,
+) -> Result<String, String> {
+    let version = match source {
+        VersionSource::GithubRelease { repo } => {
+            let url = format!("{}/repos/{repo}/releases/latest", endpoints.github_api);
+            let document = fetch_json(endpoints, &url, timeout_seconds)?;
+            strip_tag_prefix(&value_at(&document, "/tag_name", &url)?)
+        }
+        VersionSource::NpmPackage { package } => {
+            let url = format!("{}/{package}/latest", endpoints.npm_registry);
+            value_at(
+                &fetch_json(endpoints, &url, timeout_seconds)?,
+                "/version",
+                &url,
+            )?
+        }
+        VersionSource::HomebrewCask { token } => {
+            let url = format!("{}/cask/{token}.json", endpoints.homebrew_api);
+            value_at(
+                &fetch_json(endpoints, &url, timeout_seconds)?,
+                "/version",
+                &url,
+            )?
+        }
+        VersionSource::HomebrewFormula { name } => {
+            let url = format!("{}/formula/{name}.json", endpoints.homebrew_api);
+            value_at(
+                &fetch_json(endpoints, &url, timeout_seconds)?,
+                "/versions/stable",
+                &url,
+            )?
+        }
+        VersionSource::Pypi { package } => {
+            let url = format!("{}/{package}/json", endpoints.pypi);
+            value_at(
+                &fetch_json(endpoints, &url, timeout_seconds)?,
+                "/info/version",
+                &url,
+            )?
+        }
+        VersionSource::CratesIo { name } => {
+            let url = format!("{}/crates/{name}", endpoints.crates_io);
+            value_at(
+                &fetch_json(endpoints, &url, timeout_seconds)?,
+                "/crate/max_stable_version",
+                &url,
+            )?
+        }
+        VersionSource::JsonUrl { url, pointer } => {
+            value_at(&fetch_json(endpoints, url, timeout_seconds)?, pointer, url)?
+        }
+        VersionSource::HtmlRegex { url, pattern } => {
+            match_pattern(&fetch_text(endpoints, url, timeout_seconds)?, pattern, url)?
+        }
+    };
+
+    let trimmed = version.trim();
+    if trimmed.is_empty() {
+        return Err("version source returned an empty version".to_string());
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Host a source will be fetched from, used as the rate-limiting key.
+pub fn source_host(endpoints: &Endpoints, source: &VersionSource) -> Option<String> {
+    let base = match source {
+        VersionSource::GithubRelease { .. } => &endpoints.github_api,
+        VersionSource::NpmPackage { .. } => &endpoints.npm_registry,
+        VersionSource::HomebrewCask { .. } | VersionSource::HomebrewFormula { .. } => {
+            &endpoints.homebrew_api
+        }
+        VersionSource::Pypi { .. } => &endpoints.pypi,
+        VersionSource::CratesIo { .. } => &endpoints.crates_io,
+        VersionSource::JsonUrl { url, .. } | VersionSource::HtmlRegex { url, .. } => url,
+    };
+    Url::parse(base)
+        .ok()?
+        .host_str()
+        .map(str::to_ascii_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use super::*;
+
+    /// A local HTTP server answering `routes` (path with query, status,
+    /// body) and recording each request's head.
+    struct StandIn {
+        base: String,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl StandIn {
+        fn start(routes: &[(&str, u16, &str)]) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let base = format!("http://{}", listener.local_addr().unwrap());
+            let routes: Vec<(String, u16, String)> = routes
+                .iter()
+                .map(|(path, status, body)| (path.to_string(), *status, body.to_string()))
+                .collect();
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let recorded = Arc::clone(&requests);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(mut stream) = stream else { continue };
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut head = String::new();
+                    loop {
+                        let mut line = String::new();
+                        if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                            break;
+                        }
+                        head.push_str(&line);
+                    }
+                    let path = head.split_whitespace().nth(1).unwrap_or("").to_string();
+                    recorded.lock().unwrap().push(head);
+                    let (status, body) = routes
+                        .iter()
+                        .find(|(route, _, _)| *route == path)
+                        .map(|(_, status, body)| (*status, body.as_str()))
+                        .unwrap_or((404, "not found"));
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 {status} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                }
+            });
+            Self { base, requests }
+        }
+
+        fn url(&self, path: &str) -> String {
+            format!("{}{path}", self.base)
+        }
+
+        fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    fn endpoints(stand_in: &StandIn) -> Endpoints {
+        Endpoints {
+            github_api: stand_in.url("/github"),
+            npm_registry: stand_in.url("/npm"),
+            homebrew_api: stand_in.url("/brew"),
+            pypi: stand_in.url("/pypi"),
+            crates_io: stand_in.url("/crates"),
+            github_token: Some("secret".to_string()),
+        }
+    }
+
+    fn has_token(head: &str) -> bool {
+        head.to_ascii_lowercase()
+            .contains("authorization: bearer secret")
+    }
+
+    #[test]
+    fn fetches_each_registry_from_its_endpoint() {
+        let stand_in = StandIn::start(&[
+            (
+                "/github/repos/o/r/releases/latest",
+                200,
+                r#"{"tag_name":"v1.2.3"}"#,
+            ),
+            ("/npm/pkg/latest", 200, r#"{"version":"2.0.0"}"#),
+            ("/brew/cask/app.json", 200, r#"{"version":"3.1"}"#),
+            (
+                "/brew/formula/tool.json",
+                200,
+                r#"{"versions":{"stable":"4.2"}}"#,
+            ),
+            ("/pypi/lib/json", 200, r#"{"info":{"version":"5.0"}}"#),
+            (
+                "/crates/crates/krate",
+                200,
+                r#"{"crate":{"max_stable_version":"6.1.0"}}"#,
+            ),
+        ]);
+        let endpoints = endpoints(&stand_in);
+        let cases = [
+            (
+                VersionSource::GithubRelease {
+                    repo: "o/r".to_string(),
+                },
+                "1.2.3",
+            ),
+            (
+                VersionSource::NpmPackage {
+                    package: "pkg".to_string(),
+                },
+                "2.0.0",
+            ),
+            (
+                VersionSource::HomebrewCask {
+                    token: "app".to_string(),
+                },
+                "3.1",
+            ),
+            (
+                VersionSource::HomebrewFormula {
+                    name: "tool".to_string(),
+                },
+                "4.2",
+            ),
+            (
+                VersionSource::Pypi {
+                    package: "lib".to_string(),
+                },
+                "5.0",
+            ),
+            (
+                VersionSource::CratesIo {
+                    name: "krate".to_string(),
+                },
+                "6.1.0",
+            ),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(fetch_latest_with(&endpoints, &source, 5).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn sends_the_github_token_only_to_the_github_api() {
+        let github = StandIn::start(&[(
+            "/github/repos/o/r/releases/latest",
+            200,
+            r#"{"tag_name":"v1.0.0"}"#,
+        )]);
+        let other = StandIn::start(&[
+            ("/v.json?q=api.github.com", 200, r#"{"v":"1.0"}"#),
+            ("/page?q=api.github.com", 200, "<b>version 2.5</b>"),
+        ]);
+        let endpoints = endpoints(&github);
+        let sources = [
+            VersionSource::JsonUrl {
+                url: other.url("/v.json?q=api.github.com"),
+                pointer: "/v".to_string(),
+            },
+            VersionSource::HtmlRegex {
+                url: other.url("/page?q=api.github.com"),
+                pattern: r"version (\d+\.\d+)".to_string(),
+            },
+            VersionSource::GithubRelease {
+                repo: "o/r".to_string(),
+            },
+        ];
+        let versions: Vec<String> = sources
+            .iter()
+            .map(|source| fetch_latest_with(&endpoints, source, 5).unwrap())
+            .collect();
+
+        assert_eq!(versions, ["1.0", "2.5", "1.0.0"]);
+        assert!(other.requests().iter().all(|head| !has_token(head)));
+        assert!(github.requests().iter().all(|head| has_token(head)));
+    }
+
+    #[test]
+    fn reports_http_errors_and_missing_fields() {
+        let stand_in = StandIn::start(&[("/npm/empty/latest", 200, "{}")]);
+        let endpoints = endpoints(&stand_in);
+        let missing = VersionSource::NpmPackage {
+            package: "missing".to_string(),
+        };
+        let empty = VersionSource::NpmPackage {
+            package: "empty".to_string(),
+        };
+
+        let error = fetch_latest_with(&endpoints, &missing, 5).unwrap_err();
+        assert!(error.contains("HTTP 404"), "{error}");
+        let error = fetch_latest_with(&endpoints, &empty, 5).unwrap_err();
+        assert!(error.contains("/version not found"), "{error}");
+    }
+
+    #[test]
+    fn source_host_follows_the_configured_endpoints() {
+        let endpoints = Endpoints {
+            github_api: "http://127.0.0.1:9/github".to_string(),
+            ..Endpoints::default()
+        };
+        let github = VersionSource::GithubRelease {
+            repo: "o/r".to_string(),
+        };
+        let custom = VersionSource::JsonUrl {
+            url: "https://Example.com/v.json".to_string(),
+            pointer: "/v".to_string(),
+        };
+
+        assert_eq!(
+            source_host(&endpoints, &github).as_deref(),
+            Some("127.0.0.1")
+        );
+        assert_eq!(
+            source_host(&endpoints, &custom).as_deref(),
+            Some("example.com")
+        );
+    }
+}
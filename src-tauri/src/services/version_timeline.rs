@@ -0,0 +1,226 @@
+use chrono::{DateTime, Utc};
+
+use crate::model::{
+    CheckResult, ItemTimeline, TimelineMetrics, UpdateLag, VersionTimelineState, VersionTransition,
+};
+
+use super::store::{Store, StoreFile};
+
+const TIMELINE_FILE: StoreFile<'static> = StoreFile::config("version-timeline.json");
+const HOURS_PER_DAY: f64 = 24.0;
+
+fn same_observation(transition: &VersionTransition, result: &CheckResult) -> bool {
+    transition.current_version == result.current_version
+        && transition.latest_version == result.latest_version
+        && transition.has_update == result.has_update
+}
+
+fn observe(transitions: &mut Vec<VersionTransition>, result: &CheckResult) {
+    match transitions.last_mut() {
+        Some(last) if same_observation(last, result) => {
+            last.last_seen_at = result.checked_at.clone();
+        }
+        _ => transitions.push(VersionTransition {
+            observed_at: result.checked_at.clone(),
+            last_seen_at: result.checked_at.clone(),
+            current_version: result.current_version.clone(),
+            latest_version: result.latest_version.clone(),
+            has_update: result.has_update,
+        }),
+    }
+}
+
+/// Appends a transition for each result whose versions differ from the
+/// item's last one. Failed checks and checks that found no version at all
+/// are skipped.
+pub fn record(store: &dyn Store, results: &[CheckResult]) -> Result<(), String> {
+    let observed: Vec<&CheckResult> = results
+        .iter()
+        .filter(|result| result.error.is_none())
+        .filter(|result| result.current_version.is_some() || result.latest_version.is_some())
+        .collect();
+    if observed.is_empty() {
+        return Ok(());
+    }
+    store.update_json(TIMELINE_FILE, |state: &mut VersionTimelineState| {
+        for result in observed {
+            observe(
+                state.items.entry(result.item_id.clone()).or_default(),
+                result,
+            );
+        }
+    })
+}
+
+fn parse_time(raw: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(raw)
+        .ok()
+        .map(|value| value.with_timezone(&Utc))
+}
+
+fn hours_between(from: &str, to: DateTime<Utc>) -> f64 {
+    parse_time(from)
+        .map(|from| (to - from).num_seconds().max(0) as f64 / 3600.0)
+        .unwrap_or(0.0)
+}
+
+/// Each newly seen latest version, paired with the first transition whose
+/// installed version reached it.
+fn update_lags(transitions: &[VersionTransition]) -> Vec<UpdateLag> {
+    let mut lags = Vec::new();
+    for (index, transition) in transitions.iter().enumerate() {
+        let Some(latest) = transition.latest_version.as_ref() else {
+            continue;
+        };
+        let newly_seen =
+            index == 0 || transitions[index - 1].latest_version.as_ref() != Some(latest);
+        if !newly_seen || transition.current_version.as_ref() == Some(latest) {
+            continue;
+        }
+        let installed = transitions[index..]
+            .iter()
+            .find(|later| later.current_version.as_ref() == Some(latest));
+        if let Some(installed) = installed {
+            let Some(installed_at) = parse_time(&installed.observed_at) else {
+                continue;
+            };
+            lags.push(UpdateLag {
+                version: latest.clone(),
+                first_seen_at: transition.observed_at.clone(),
+                installed_at: installed.observed_at.clone(),
+                hours: hours_between(&transition.observed_at, installed_at),
+            });
+        }
+    }
+    lags
+}
+
+/// Metrics of one item's timeline as of `now`. Time behind runs from a
+/// transition with an update to the next transition (or `now`).
+pub fn metrics(transitions: &[VersionTransition], now: DateTime<Utc>) -> TimelineMetrics {
+    let total_hours_behind: f64 = transitions
+        .iter()
+        .enumerate()
+        .filter(|(_, transition)| transition.has_update)
+        .map(|(index, transition)| {
+            let end = transitions
+                .get(index + 1)
+                .and_then(|next| parse_time(&next.observed_at))
+                .unwrap_or(now);
+            hours_between(&transition.observed_at, end)
+        })
+        .sum();
+    let behind_since = transitions
+        .iter()
+        .rev()
+        .take_while(|transition| transition.has_update)
+        .last();
+    let update_lags = update_lags(transitions);
+    let average_hours_to_update = (!update_lags.is_empty())
+        .then(|| update_lags.iter().map(|lag| lag.hours).sum::<f64>() / update_lags.len() as f64);
+
+    TimelineMetrics {
+        days_behind_latest: behind_since
+            .map(|transition| hours_between(&transition.observed_at, now) / HOURS_PER_DAY),
+        total_days_behind: total_hours_behind / HOURS_PER_DAY,
+        update_lags,
+        average_hours_to_update,
+    }
+}
+
+pub fn load_state(store: &dyn Store) -> Result<VersionTimelineState, String> {
+    Ok(store.read_json(TIMELINE_FILE)?.unwrap_or_default())
+}
+
+pub fn load_item_timeline(store: &dyn Store, item_id: &str) -> Result<ItemTimeline, String> {
+    let transitions = load_state(store)?.items.remove(item_id).unwrap_or_default();
+    Ok(ItemTimeline {
+        item_id: item_id.to_string(),
+        metrics: metrics(&transitions, Utc::now()),
+        transitions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::store::MemoryStore;
+
+    fn result(checked_at: &str, current: &str, latest: &str) -> CheckResult {
+        CheckResult {
+            item_id: "node".to_string(),
+            checked_at: checked_at.to_string(),
+            has_update: current != latest,
+            current_version: Some(current.to_string()),
+            latest_version: Some(latest.to_string()),
+            version_comparison: None,
+            details: String::new(),
+            error: None,
+        }
+    }
+
+    fn at(raw: &str) -> DateTime<Utc> {
+        parse_time(raw).expect("valid timestamp")
+    }
+
+    fn recorded_timeline() -> Vec<VersionTransition> {
+        let store = MemoryStore::new();
+        let failed = CheckResult {
+            error: Some("timed out".to_string()),
+            ..result("2025-01-03T00:00:00Z", "0.9", "1.1")
+        };
+        for result in [
+            result("2025-01-01T00:00:00Z", "1.0", "1.0"),
+            result("2025-01-02T00:00:00Z", "1.0", "1.1"),
+            result("2025-01-02T12:00:00Z", "1.0", "1.1"),
+            failed,
+            result("2025-01-04T00:00:00Z", "1.1", "1.1"),
+            result("2025-01-05T00:00:00Z", "1.1", "1.2"),
+        ] {
+            record(&store, &[result]).unwrap();
+        }
+        load_state(&store).unwrap().items.remove("node").unwrap()
+    }
+
+    #[test]
+    fn repeated_observations_only_bump_last_seen() {
+        let transitions = recorded_timeline();
+        let observed: Vec<&str> = transitions
+            .iter()
+            .map(|transition| transition.observed_at.as_str())
+            .collect();
+        assert_eq!(
+            observed,
+            [
+                "2025-01-01T00:00:00Z",
+                "2025-01-02T00:00:00Z",
+                "2025-01-04T00:00:00Z",
+                "2025-01-05T00:00:00Z",
+            ]
+        );
+        assert_eq!(transitions[1].last_seen_at, "2025-01-02T12:00:00Z");
+    }
+
+    #[test]
+    fn metrics_measure_time_behind_and_to_update() {
+        let metrics = metrics(&recorded_timeline(), at("2025-01-06T12:00:00Z"));
+
+        // Behind on 1.2 since 01-05, plus two days behind on 1.1 before.
+        assert_eq!(metrics.days_behind_latest, Some(1.5));
+        assert_eq!(metrics.total_days_behind, 3.5);
+        assert_eq!(metrics.update_lags.len(), 1);
+        assert_eq!(metrics.update_lags[0].version, "1.1");
+        assert_eq!(metrics.update_lags[0].installed_at, "2025-01-04T00:00:00Z");
+        assert_eq!(metrics.update_lags[0].hours, 48.0);
+        assert_eq!(metrics.average_hours_to_update, Some(48.0));
+    }
+
+    #[test]
+    fn up_to_date_items_are_not_behind() {
+        let transitions = &recorded_timeline()[..3];
+        let metrics = metrics(transitions, at("2025-01-06T12:00:00Z"));
+
+        assert_eq!(metrics.days_behind_latest, None);
+        assert_eq!(metrics.total_days_behind, 2.0);
+    }
+}